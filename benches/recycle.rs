@@ -0,0 +1,56 @@
+//! Compare the conversion of many short messages with and without recycling the allocations
+//!
+//! Run with `cargo bench --bench recycle`.
+
+use std::{
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+use mdast2minimad::{emitter::buffers::Buffers, md_parse_options, Converter, Interner};
+
+const MESSAGES: [&str; 4] = [
+    "Hello **everyone**!",
+    "Did you see the new `release`? It fixes *so many* bugs",
+    "- one\n- two\n- three",
+    "Run `cargo bench` and\nlook at the ~~old~~ new numbers",
+];
+const MESSAGES_PER_RUN: usize = 100_000;
+const RUNS: usize = 10;
+
+fn main() {
+    let interner = Interner::new();
+    let converter = Converter::default();
+    let asts: Vec<_> = MESSAGES
+        .iter()
+        .map(|message| markdown::to_mdast(message, &md_parse_options()).unwrap())
+        .collect();
+    let mut buffers = Buffers::new();
+
+    // the runs are interleaved, and the fastest one is kept to reduce the noise
+    let mut fresh = Duration::MAX;
+    let mut recycled = Duration::MAX;
+    for _ in 0..RUNS {
+        let start = Instant::now();
+        for ast in asts.iter().cycle().take(MESSAGES_PER_RUN) {
+            black_box(converter.convert(ast, &interner).unwrap());
+        }
+        fresh = fresh.min(start.elapsed());
+
+        let start = Instant::now();
+        for ast in asts.iter().cycle().take(MESSAGES_PER_RUN) {
+            let text = converter
+                .convert_recycling(ast, &mut buffers, &interner)
+                .unwrap();
+            buffers.recycle(black_box(text));
+        }
+        recycled = recycled.min(start.elapsed());
+    }
+
+    println!("fresh allocations:    {fresh:?}");
+    println!("recycled allocations: {recycled:?}");
+    println!(
+        "saved: {:.0}%",
+        100. * (1. - recycled.as_secs_f64() / fresh.as_secs_f64())
+    );
+}
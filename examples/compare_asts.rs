@@ -0,0 +1,44 @@
+//! This example compare the two asts generated by direct `minimad` parsing and conversion from `markdown`->`minimad`
+
+use std::{fs, path::PathBuf};
+
+use anyhow::{Context, Result};
+use clap::Parser;
+
+#[derive(Debug, Parser)]
+#[command(version = "0.1.0", name = "compare_asts")]
+/// Compare the two asts generated by direct `minimad` parsing and conversion from `markdown`->`minimad`
+struct Cli {
+    /// Input markdown file
+    markdown: PathBuf,
+}
+
+fn main() -> Result<()> {
+    let interner = mdast2minimad::Interner::new();
+    let Cli { markdown } = Cli::parse();
+
+    // read the sources
+    let src = fs::read_to_string(markdown).context("Cannot read input file")?;
+
+    let comparison =
+        mdast2minimad::compare_with_minimad(&src).context("Error during conversion")?;
+    let differences = comparison.differences();
+    for difference in &differences {
+        println!("line {}:", difference.index);
+        println!("  minimad:   {:?}", difference.minimad);
+        println!("  converted: {:?}", difference.converted);
+    }
+    println!("{} differing lines", differences.len());
+
+    // check that the minimad text survives a trip through the markdown AST
+    let rebuilt = mdast2minimad::from_minimad(&comparison.minimad);
+    let round_trip = mdast2minimad::to_minimad(&rebuilt, &interner)
+        .context("Error during round trip conversion")?;
+    if round_trip == comparison.minimad {
+        println!("The minimad text round trips through mdast");
+    } else {
+        println!("The minimad text changes in a round trip through mdast");
+    }
+
+    Ok(())
+}
@@ -1,4 +1,4 @@
-//! This example compare the two asts generated by direct `minimad` parsing and conversion from `markdown`->`minimad`
+//! This example displays a markdown file, parsed with `markdown` and converted to `minimad`
 
 use std::{fs, path::PathBuf};
 
@@ -7,7 +7,7 @@ use clap::Parser;
 
 #[derive(Debug, Parser)]
 #[command(version = "0.1.0", name = "display")]
-/// Compare the two asts generated by direct `minimad` parsing and conversion from `markdown`->`minimad`
+/// Display a markdown file, parsed with `markdown` and converted to `minimad`
 struct Cli {
     /// Input markdown file
     markdown: PathBuf,
@@ -29,22 +29,16 @@ fn main() -> Result<()> {
     // read the sources
     let src = fs::read_to_string(markdown).context("Cannot read input file")?;
 
+    let rendering;
     let text = if minimad {
-        // Parse with `minimad`
         minimad::parse_text(&src, minimad::Options::default())
     } else {
-        // Parse with `markdown`
-        let ast = markdown::to_mdast(&src, &mdast2minimad::md_parse_options())
-            .expect("Markdown has no syntax errors");
-        // Leak it: the ast must live until the print, and then the program will end.
-        // There is no merit in keeping track of the AST lifetime
-        let ast = &*Box::leak(Box::new(ast));
-
+        rendering = mdast2minimad::render_markdown(&src, Default::default())
+            .context("Error during conversion")?;
         if print_ast {
-            println!("{:#?}", ast)
+            println!("{:#?}", rendering.ast)
         }
-        // Using our converter
-        mdast2minimad::to_minimad(&ast).context("Error during ast conversion")?
+        rendering.text.text()
     };
 
     if print_ast {
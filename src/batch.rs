@@ -0,0 +1,72 @@
+//! Conversion of many documents at once
+
+use std::{num::NonZeroUsize, panic, thread};
+
+use minimad::Text;
+
+use crate::{mdast, to_minimad_with_options, Interner, Options, ToMinimadError};
+
+/// How many threads a batch conversion can use
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Parallelism {
+    /// Convert the documents one after the other, in the calling thread
+    Sequential,
+    /// Use up to the given number of threads
+    Threads(NonZeroUsize),
+    /// Use as many threads as the available parallelism of the machine
+    #[default]
+    Available,
+}
+impl Parallelism {
+    fn threads(self) -> usize {
+        match self {
+            Parallelism::Sequential => 1,
+            Parallelism::Threads(threads) => threads.get(),
+            Parallelism::Available => thread::available_parallelism().map_or(1, NonZeroUsize::get),
+        }
+    }
+}
+
+/// Convert many markdown ASTs, e.g. all the pages of a documentation tree
+///
+/// The synthesized strings are stored in the given interner, and shared between the documents.
+/// The results are in the same order as the ASTs.
+pub fn convert_many<'a>(
+    asts: &'a [mdast::Node],
+    options: Options,
+    parallelism: Parallelism,
+    interner: &'a Interner,
+) -> Vec<Result<Text<'a>, ToMinimadError>> {
+    let threads = parallelism.threads().min(asts.len());
+    if threads <= 1 {
+        return asts
+            .iter()
+            .map(|ast| to_minimad_with_options(ast, options.clone(), interner))
+            .collect();
+    }
+    // each thread converts a contiguous chunk, so that the results stay in order
+    let chunk_size = asts.len().div_ceil(threads);
+    thread::scope(|scope| {
+        let handles: Vec<_> = asts
+            .chunks(chunk_size)
+            .map(|chunk| {
+                let options = options.clone();
+                scope.spawn(move || {
+                    chunk
+                        .iter()
+                        .map(|ast| to_minimad_with_options(ast, options.clone(), interner))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| {
+                // a panic in a handler is propagated to the caller
+                handle
+                    .join()
+                    .unwrap_or_else(|payload| panic::resume_unwind(payload))
+            })
+            .collect()
+    })
+}
@@ -0,0 +1,216 @@
+//! Incremental conversion of documents that change a little at a time
+
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    hash::Hasher,
+    sync::Arc,
+};
+
+use minimad::Text;
+
+use crate::{
+    emitter::state::ContentModel, mdast, sections::hash_node, to_minimad_owned,
+    without_frontmatter, DocumentContext, Emitter, Interner, NodeKind, Options, OwnedText,
+    ToMinimadError, WhileEmitting,
+};
+
+/// Cache of the conversion of the top-level blocks of a document
+///
+/// Editors showing a live preview convert the document at every change, but most of the blocks
+/// stay the same. The cache keeps the lines of each block, keyed by its content, and emits them
+/// again without converting the block.
+///
+/// Blocks whose conversion depends on the rest of the document are always converted: the ones
+/// with footnotes or link references and definitions, the headings when
+/// [`Options::number_headings`] is set, the directives, and the blocks adding links to the
+/// appendix. Only the blocks used by the last conversion are kept.
+/// ```
+/// # use mdast2minimad::{md_parse_options, to_minimad_owned, ConversionCache, Options};
+/// let mut cache = ConversionCache::new(Options::default());
+/// let before = markdown::to_mdast("# Title\n\nSome text", &md_parse_options()).unwrap();
+/// cache.convert(&before).unwrap();
+///
+/// let after = markdown::to_mdast("# Title\n\nSome other text", &md_parse_options()).unwrap();
+/// let text = cache.convert(&after).unwrap();
+/// assert_eq!(text, to_minimad_owned(&after, Options::default()).unwrap());
+/// assert_eq!(cache.reused(), 1);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ConversionCache {
+    options: Options,
+    blocks: HashMap<BlockKey, CachedBlock>,
+    /// Number of blocks reused by the last conversion
+    reused: usize,
+}
+
+/// What the lines of a block depend on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct BlockKey {
+    /// Hash of the content of the block
+    content: u64,
+    /// Kind of the last block that emitted something, for the spacing rules
+    last_block: Option<NodeKind>,
+    /// If an empty line was due before the block
+    spacing: bool,
+    /// If the block is the first to emit something
+    first: bool,
+}
+
+/// The lines emitted by a block
+#[derive(Debug, Clone)]
+struct CachedBlock {
+    text: OwnedText,
+    /// If an empty line is due after the block
+    spacing: bool,
+}
+
+impl ConversionCache {
+    /// Create an empty cache, converting with the given options
+    pub fn new(options: Options) -> Self {
+        Self {
+            options,
+            blocks: HashMap::new(),
+            reused: 0,
+        }
+    }
+
+    /// Convert a document, reusing the blocks converted before
+    ///
+    /// The result is the same as [`to_minimad_owned`]. If the node is not a `Root` it is
+    /// converted without caching.
+    pub fn convert(&mut self, ast: &mdast::Node) -> Result<OwnedText, ToMinimadError> {
+        self.reused = 0;
+        let mdast::Node::Root(mdast::Root { children, .. }) = ast else {
+            return to_minimad_owned(ast, self.options.clone());
+        };
+        let mut reused: Vec<BlockKey> = vec![];
+        let mut converted: Vec<(BlockKey, usize, usize, bool)> = vec![];
+
+        let interner = Interner::new();
+        let mut emitter = Emitter::new(self.options.clone(), &interner);
+        // the blocks are emitted as children of the root
+        emitter.depth = 1;
+        emitter.state.flow();
+        emitter.context = Some(Arc::new(DocumentContext::scan_all(children)));
+        for child in without_frontmatter(children) {
+            if emitter.truncated {
+                emitter.cut = true;
+                break;
+            }
+            let key = BlockKey {
+                content: content_hash(child),
+                last_block: emitter.last_block,
+                spacing: emitter
+                    .state
+                    .model
+                    .as_ref()
+                    .is_some_and(ContentModel::need_spacing),
+                first: emitter.state.lines.is_empty(),
+            };
+            if !emitter.skip_next {
+                if let Some(block) = self.blocks.get(&key) {
+                    let text = block.text.text();
+                    emitter.state.lines.extend(text.lines.iter().cloned());
+                    emitter.state.model = Some(ContentModel::Flow {
+                        spacing: block.spacing,
+                    });
+                    if !text.lines.is_empty() {
+                        emitter.last_block = Some(NodeKind::of(child));
+                        emitter.blocks += 1;
+                    }
+                    emitter.truncated = emitter.over_budget();
+                    reused.push(key);
+                    self.reused += 1;
+                    continue;
+                }
+            }
+
+            let cacheable = !emitter.skip_next && !depends_on_document(child, &self.options);
+            let footnotes = emitter.footnotes.len();
+            let appendix = emitter.link_appendix.len();
+            let start = emitter.state.lines.len();
+            emitter.node(child).while_emitting(ast)?;
+            let spacing = match &emitter.state.model {
+                Some(ContentModel::Flow { spacing }) => Some(*spacing),
+                _ => None,
+            };
+            if let Some(spacing) = spacing.filter(|_| {
+                cacheable
+                    && !emitter.skip_next
+                    && !emitter.truncated
+                    && emitter.footnotes.len() == footnotes
+                    && emitter.link_appendix.len() == appendix
+            }) {
+                converted.push((key, start, emitter.state.lines.len(), spacing));
+            }
+        }
+
+        // the new blocks are copied before the post processing
+        let mut blocks: HashMap<_, _> = converted
+            .into_iter()
+            .map(|(key, start, end, spacing)| {
+                let text = OwnedText::new(&Text {
+                    lines: emitter.state.lines[start..end].to_vec(),
+                });
+                (key, CachedBlock { text, spacing })
+            })
+            .collect();
+        let text = OwnedText::new(&emitter.finish());
+        // only the blocks still in the document are kept
+        for key in reused {
+            if let Some(block) = self.blocks.remove(&key) {
+                blocks.insert(key, block);
+            }
+        }
+        self.blocks = blocks;
+        Ok(text)
+    }
+
+    /// Number of blocks reused by the last conversion
+    pub fn reused(&self) -> usize {
+        self.reused
+    }
+
+    /// Number of blocks in the cache
+    pub fn len(&self) -> usize {
+        self.blocks.len()
+    }
+
+    /// Check if the cache is empty
+    pub fn is_empty(&self) -> bool {
+        self.blocks.is_empty()
+    }
+
+    /// Forget all the cached blocks
+    pub fn clear(&mut self) {
+        self.blocks.clear()
+    }
+}
+
+/// Hash of the content of a block, ignoring positions
+fn content_hash(node: &mdast::Node) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    hash_node(node, &mut hasher);
+    hasher.finish()
+}
+
+/// Check if the conversion of a block depends on the rest of the document
+fn depends_on_document(node: &mdast::Node, options: &Options) -> bool {
+    let mut stack = vec![node];
+    while let Some(node) = stack.pop() {
+        match node {
+            mdast::Node::FootnoteReference(_)
+            | mdast::Node::FootnoteDefinition(_)
+            | mdast::Node::LinkReference(_)
+            | mdast::Node::ImageReference(_)
+            | mdast::Node::Definition(_)
+            | mdast::Node::Html(_) => return true,
+            mdast::Node::Heading(_) if options.number_headings => return true,
+            _ => (),
+        }
+        if let Some(children) = node.children() {
+            stack.extend(children);
+        }
+    }
+    false
+}
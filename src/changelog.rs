@@ -0,0 +1,174 @@
+//! Rendering of changelogs following the [Keep a Changelog](https://keepachangelog.com) format
+//!
+//! Version headings (`## [1.0.0] - 2017-06-20`) are rendered bold and spaced, while the change
+//! groups (`### Added`, `### Fixed`, ...) become bold prefixes. Every line coming from a change group
+//! is tagged with its [`ChangeKind`], so that applications can color them.
+
+use std::{mem, ops::Range};
+
+use minimad::{CompositeStyle, Text};
+
+use crate::{mdast, Emitter, Interner, Options, ToMinimadError, WhileEmitting};
+
+/// Options for the rendering of a changelog
+#[derive(Debug, Clone)]
+pub struct ChangelogOptions {
+    /// Options for the conversion
+    pub options: Options,
+    /// If the `Unreleased` section should be rendered
+    pub unreleased: bool,
+}
+impl Default for ChangelogOptions {
+    fn default() -> Self {
+        let mut options = Options::default();
+        // version headings
+        options.header_spacing[1] = true;
+        Self {
+            options,
+            unreleased: true,
+        }
+    }
+}
+
+/// Kind of a change, as given by the heading of its group
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChangeKind {
+    Added,
+    Changed,
+    Deprecated,
+    Removed,
+    Fixed,
+    Security,
+}
+impl ChangeKind {
+    /// Find the kind of change from the title of a group
+    fn from_title(title: &str) -> Option<Self> {
+        match &*title.trim().to_lowercase() {
+            "added" => Some(Self::Added),
+            "changed" => Some(Self::Changed),
+            "deprecated" => Some(Self::Deprecated),
+            "removed" => Some(Self::Removed),
+            "fixed" => Some(Self::Fixed),
+            "security" => Some(Self::Security),
+            _ => None,
+        }
+    }
+}
+
+/// Lines of the rendered changelog coming from a group of changes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChangeTag {
+    /// Range of lines in the rendered text, prefix included
+    pub lines: Range<usize>,
+    /// Kind of the changes
+    pub kind: ChangeKind,
+}
+
+/// A rendered changelog
+#[derive(Debug, Clone)]
+pub struct Changelog<'a> {
+    /// The rendered text
+    pub text: Text<'a>,
+    /// Kind of the lines in the text
+    pub tags: Vec<ChangeTag>,
+}
+
+/// Convert a changelog to a minimad Text
+pub fn to_minimad<'a>(
+    ast: &'a mdast::Node,
+    options: &ChangelogOptions,
+    interner: &'a Interner,
+) -> Result<Changelog<'a>, ToMinimadError> {
+    let children = match ast {
+        mdast::Node::Root(mdast::Root {
+            children,
+            position: _,
+        }) => &children[..],
+        other => std::slice::from_ref(other),
+    };
+
+    let mut emitter = Emitter::new(options.options.clone(), interner);
+    let mut tags = vec![];
+    // group being emitted, with its first line
+    let mut group: Option<(ChangeKind, usize)> = None;
+    // if we are skipping the unreleased section
+    let mut skipping = false;
+
+    for child in children {
+        let mdast::Node::Heading(mdast::Heading {
+            children: title,
+            position: _,
+            depth,
+        }) = child
+        else {
+            if !skipping {
+                emitter.node(child).while_emitting(ast)?;
+            }
+            continue;
+        };
+        if *depth <= 3 {
+            // any heading of the same or higher level closes the group
+            if let Some((kind, start)) = group.take() {
+                tags.push(ChangeTag {
+                    lines: start..emitter.state.lines.len(),
+                    kind,
+                })
+            }
+        }
+        if *depth <= 2 {
+            skipping = *depth == 2 && !options.unreleased && is_unreleased(child);
+        }
+        if skipping {
+            continue;
+        }
+        match *depth {
+            2 => {
+                // version heading
+                let old_style = mem::replace(&mut emitter.style.bold, true);
+                emitter.node(child).while_emitting(ast)?;
+                emitter.style.bold = old_style;
+            }
+            3 => {
+                let Some(kind) = ChangeKind::from_title(&child.to_string()) else {
+                    emitter.node(child).while_emitting(ast)?;
+                    continue;
+                };
+                // change group: emit as a bold prefix
+                emitter
+                    .phrasing(CompositeStyle::Paragraph, false, |this| {
+                        let old_style = mem::replace(&mut this.style.bold, true);
+                        for child in title {
+                            this.node(child)?;
+                        }
+                        this.style.bold = old_style;
+                        Ok(())
+                    })
+                    .while_emitting(child)
+                    .while_emitting(ast)?;
+                group = Some((kind, emitter.state.lines.len() - 1))
+            }
+            _ => emitter.node(child).while_emitting(ast)?,
+        }
+    }
+    if let Some((kind, start)) = group {
+        tags.push(ChangeTag {
+            lines: start..emitter.state.lines.len(),
+            kind,
+        })
+    }
+
+    Ok(Changelog {
+        text: emitter.finish(),
+        tags,
+    })
+}
+
+/// Check if a heading is the one of the `Unreleased` section
+fn is_unreleased(heading: &mdast::Node) -> bool {
+    heading
+        .to_string()
+        .trim()
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .eq_ignore_ascii_case("unreleased")
+}
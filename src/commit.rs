@@ -0,0 +1,90 @@
+//! Rendering of git commit messages
+//!
+//! The first line (the subject) becomes a heading, the body is rendered as normal paragraphs, keeping
+//! each line break of the message, and the trailers (`Signed-off-by: ...`, `Co-authored-by: ...`)
+//! at the end of the message are rendered as a quote block.
+
+use minimad::{Composite, CompositeStyle, Line, Text};
+
+use crate::{mdast, Emitter, Interner, Options, SoftBreak, ToMinimadError};
+
+/// Convert a commit message to a minimad Text
+///
+/// [`Options::soft_break`] is ignored: each line of the message is kept.
+pub fn to_minimad<'a>(
+    ast: &'a mdast::Node,
+    mut options: Options,
+    interner: &'a Interner,
+) -> Result<Text<'a>, ToMinimadError> {
+    options.soft_break = SoftBreak::Newline;
+    let mut emitter = Emitter::new(options, interner);
+    emitter.node(ast)?;
+    let mut text = emitter.finish();
+
+    // The subject become the heading
+    if let Some(subject) = text.lines.iter().position(|line| !is_empty(line)) {
+        // Subjects that are not a simple line (e.g. a list) keep their formatting
+        if let Line::Normal(Composite {
+            style: style @ CompositeStyle::Paragraph,
+            ..
+        }) = &mut text.lines[subject]
+        {
+            *style = CompositeStyle::Header(1);
+            // if the subject is not followed by a empty line, separate it from the body
+            let next = subject + 1;
+            if text.lines.get(next).is_some_and(|line| !is_empty(line)) {
+                text.lines.insert(next, Line::new_paragraph(vec![]));
+            }
+        }
+    }
+
+    // The last block of lines are trailers if all lines are in the `Key: value` form
+    let end = text
+        .lines
+        .iter()
+        .rposition(|line| !is_empty(line))
+        .map_or(0, |last| last + 1);
+    let start = text.lines[..end]
+        .iter()
+        .rposition(is_empty)
+        .map_or(0, |empty| empty + 1);
+    if start > 0 && text.lines[start..end].iter().all(is_trailer) {
+        for line in &mut text.lines[start..end] {
+            if let Line::Normal(Composite { style, .. }) = line {
+                *style = CompositeStyle::Quote
+            }
+        }
+    }
+
+    Ok(text)
+}
+
+/// Check if a line is an empty paragraph line
+fn is_empty(line: &Line) -> bool {
+    matches!(line, Line::Normal(Composite {
+        style: CompositeStyle::Paragraph,
+        compounds,
+    }) if compounds.iter().all(|compound| compound.src.trim().is_empty()))
+}
+
+/// Check if a line is a trailer (`Key: value`)
+fn is_trailer(line: &Line) -> bool {
+    let Line::Normal(Composite {
+        style: CompositeStyle::Paragraph,
+        compounds,
+    }) = line
+    else {
+        return false;
+    };
+    let Some((key, value)) = compounds
+        .first()
+        .and_then(|compound| compound.src.split_once(':'))
+    else {
+        return false;
+    };
+    !key.is_empty()
+        && key
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || ch == '-')
+        && (value.is_empty() || value.starts_with(' '))
+}
@@ -0,0 +1,82 @@
+//! Rendering and comparison of markdown sources, as done by the examples
+
+use minimad::{Line, Text};
+
+use crate::{md_parse_options, mdast, to_minimad_owned, ConvertError, Options, OwnedText};
+
+/// A markdown source, parsed and converted
+#[derive(Debug, Clone)]
+pub struct Rendering {
+    /// The AST parsed from the source
+    pub ast: mdast::Node,
+    /// The converted text, ready to be formatted
+    pub text: OwnedText,
+}
+
+/// Parse a markdown source and convert it, keeping the AST around
+///
+/// The source is parsed with [`md_parse_options`]. The text is the same returned by
+/// [`convert`](crate::convert), but the AST is kept for inspection.
+pub fn render_markdown(source: &str, options: Options) -> Result<Rendering, ConvertError> {
+    let ast = markdown::to_mdast(source, &md_parse_options())
+        .map_err(|message| ConvertError::Parse { message })?;
+    let text = to_minimad_owned(&ast, options)?;
+    Ok(Rendering { ast, text })
+}
+
+/// The text parsed directly by `minimad`, next to the one converted from `markdown`
+///
+/// ```
+/// # use mdast2minimad::compare_with_minimad;
+/// let comparison = compare_with_minimad("Some *simple* text").unwrap();
+/// assert!(comparison.is_identical());
+/// ```
+#[derive(Debug, Clone)]
+pub struct Comparison<'s> {
+    /// The text parsed by `minimad`
+    pub minimad: Text<'s>,
+    /// The text converted from the `markdown` AST
+    pub converted: OwnedText,
+}
+
+/// A line that differs between the two texts of a [`Comparison`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineDifference<'c> {
+    /// Index of the line
+    pub index: usize,
+    /// The line parsed by `minimad`, if the text is long enough
+    pub minimad: Option<Line<'c>>,
+    /// The converted line, if the text is long enough
+    pub converted: Option<Line<'c>>,
+}
+
+impl<'s> Comparison<'s> {
+    /// The lines that differ, in order
+    pub fn differences(&self) -> Vec<LineDifference<'_>> {
+        let converted = self.converted.text();
+        let len = self.minimad.lines.len().max(converted.lines.len());
+        (0..len)
+            .filter_map(|index| {
+                let minimad = self.minimad.lines.get(index);
+                let converted = converted.lines.get(index);
+                (minimad != converted).then(|| LineDifference {
+                    index,
+                    minimad: minimad.cloned(),
+                    converted: converted.cloned(),
+                })
+            })
+            .collect()
+    }
+
+    /// If the two texts are the same
+    pub fn is_identical(&self) -> bool {
+        self.minimad == self.converted.text()
+    }
+}
+
+/// Parse a markdown source both with `minimad` and through the conversion, to compare the results
+pub fn compare_with_minimad(source: &str) -> Result<Comparison<'_>, ConvertError> {
+    let minimad = minimad::parse_text(source, minimad::Options::default());
+    let converted = render_markdown(source, Options::default())?.text;
+    Ok(Comparison { minimad, converted })
+}
@@ -0,0 +1,94 @@
+//! Collection of the definitions of a document
+
+use std::collections::HashMap;
+
+use crate::mdast;
+
+/// What a document defines: link definitions, footnotes and headings
+///
+/// The conversion of a document scans it before emitting anything, so that e.g. the references to
+/// footnotes that are never defined are recognized. Applications can use the same scan to query a
+/// document without walking the AST themselves:
+/// ```
+/// # use mdast2minimad::DocumentContext;
+/// let ast = markdown::to_mdast(
+///     "# Title\n\nSee the note[^note].\n\n[^note]: A note.",
+///     &markdown::ParseOptions::gfm(),
+/// )
+/// .unwrap();
+/// let context = DocumentContext::scan(&ast);
+/// assert_eq!(context.headings().len(), 1);
+/// assert!(context.footnote("note").is_some());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct DocumentContext<'a> {
+    /// Link definitions, by identifier
+    definitions: HashMap<&'a str, &'a mdast::Definition>,
+    /// Footnote definitions, by identifier
+    footnotes: HashMap<&'a str, &'a mdast::FootnoteDefinition>,
+    /// Headings, in document order
+    headings: Vec<&'a mdast::Heading>,
+}
+
+impl<'a> DocumentContext<'a> {
+    /// Collect the definitions of a document
+    ///
+    /// As in markdown, the first definition of an identifier wins.
+    pub fn scan(ast: &'a mdast::Node) -> Self {
+        Self::scan_all(std::slice::from_ref(ast))
+    }
+
+    /// Collect the definitions of a sequence of nodes
+    pub(crate) fn scan_all(nodes: &'a [mdast::Node]) -> Self {
+        let mut context = Self::default();
+        // the AST is walked without recursion, so deep documents cannot overflow the stack
+        let mut stack: Vec<_> = nodes.iter().rev().collect();
+        while let Some(node) = stack.pop() {
+            match node {
+                mdast::Node::Definition(definition) => {
+                    context
+                        .definitions
+                        .entry(&definition.identifier)
+                        .or_insert(definition);
+                }
+                mdast::Node::FootnoteDefinition(footnote) => {
+                    context
+                        .footnotes
+                        .entry(&footnote.identifier)
+                        .or_insert(footnote);
+                }
+                mdast::Node::Heading(heading) => context.headings.push(heading),
+                _ => (),
+            }
+            if let Some(children) = node.children() {
+                stack.extend(children.iter().rev());
+            }
+        }
+        context
+    }
+
+    /// The link definition with the given identifier
+    pub fn definition(&self, identifier: &str) -> Option<&'a mdast::Definition> {
+        self.definitions.get(identifier).copied()
+    }
+
+    /// The footnote definition with the given identifier
+    pub fn footnote(&self, identifier: &str) -> Option<&'a mdast::FootnoteDefinition> {
+        self.footnotes.get(identifier).copied()
+    }
+
+    /// All the link definitions, in no particular order
+    pub fn definitions(&self) -> impl Iterator<Item = &'a mdast::Definition> + '_ {
+        self.definitions.values().copied()
+    }
+
+    /// All the footnote definitions, in no particular order
+    pub fn footnotes(&self) -> impl Iterator<Item = &'a mdast::FootnoteDefinition> + '_ {
+        self.footnotes.values().copied()
+    }
+
+    /// The headings, in document order
+    pub fn headings(&self) -> &[&'a mdast::Heading] {
+        &self.headings
+    }
+}
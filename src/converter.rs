@@ -0,0 +1,489 @@
+//! Reusable, configured conversions
+
+use std::{borrow::Cow, fmt, marker::PhantomData, mem, sync::Arc};
+
+use minimad::{Composite, Text};
+
+use crate::{
+    convert_many,
+    emitter::{buffers::Buffers, state::ContentState},
+    md_parse_options, mdast, to_composite_with_options, to_minimad_lines, to_minimad_lossy,
+    to_minimad_lossy_into, to_minimad_owned, to_minimad_reporting, to_minimad_with_options,
+    transform::TextTransform,
+    ConversionReport, ConversionWarning, ConvertError, DeepHeading, Directive, Directives, Emitter,
+    Handled, Handler, HtmlMode, ImageText, IndentPolicy, Interner, KbdStyle, LineSink, LinesIter,
+    LinkMode, Numbering, Options, OwnedText, Parallelism, QuotedCode, SoftBreak, Spacing, Styling,
+    ToMinimadError, WarningSink, WidthClass,
+};
+
+/// A configured converter, that can be used on many documents
+///
+/// ```
+/// # use mdast2minimad::{Converter, Styling};
+/// let converter = Converter::builder()
+///     .header_spacing(2, true)
+///     .links_style(Styling {
+///         italic: Some(true),
+///         ..Default::default()
+///     })
+///     .build();
+///
+/// let ast = markdown::to_mdast("## Title", &mdast2minimad::md_parse_options()).unwrap();
+/// let interner = mdast2minimad::Interner::new();
+/// let text = converter.convert(&ast, &interner).unwrap();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Converter {
+    options: Options,
+}
+
+impl Converter {
+    /// Create a converter with the given options
+    pub fn new(options: Options) -> Self {
+        Self { options }
+    }
+
+    /// Start building a converter
+    pub fn builder() -> ConverterBuilder {
+        ConverterBuilder::default()
+    }
+
+    /// The options used by this converter
+    pub fn options(&self) -> &Options {
+        &self.options
+    }
+
+    /// Convert the markdown AST to a minimad Text, storing the synthesized strings in the given interner
+    pub fn convert<'a>(
+        &self,
+        ast: &'a mdast::Node,
+        interner: &'a Interner,
+    ) -> Result<Text<'a>, ToMinimadError> {
+        to_minimad_with_options(ast, self.options.clone(), interner)
+    }
+
+    /// Convert many markdown ASTs, possibly in parallel
+    ///
+    /// See [`convert_many`].
+    pub fn convert_many<'a>(
+        &self,
+        asts: &'a [mdast::Node],
+        parallelism: Parallelism,
+        interner: &'a Interner,
+    ) -> Vec<Result<Text<'a>, ToMinimadError>> {
+        convert_many(asts, self.options.clone(), parallelism, interner)
+    }
+
+    /// Convert the markdown AST to a minimad Text, reusing the allocations of the given buffers
+    ///
+    /// The text can then be given back to the buffers with [`Buffers::recycle`].
+    pub fn convert_recycling<'a>(
+        &self,
+        ast: &'a mdast::Node,
+        buffers: &mut Buffers,
+        interner: &'a Interner,
+    ) -> Result<Text<'a>, ToMinimadError> {
+        let mut emitter = Emitter::new(self.options.clone(), interner);
+        emitter.state = ContentState::from_buffers(buffers);
+        let res = emitter.node(ast);
+        buffers.return_compounds(mem::take(&mut emitter.state.spare));
+        res?;
+        Ok(emitter.finish())
+    }
+
+    /// Convert the markdown AST lazily, one line at a time
+    pub fn convert_lines<'a>(&self, ast: &'a mdast::Node, interner: &'a Interner) -> LinesIter<'a> {
+        to_minimad_lines(ast, self.options.clone(), interner)
+    }
+
+    /// Convert phrasing content to a single minimad Composite
+    ///
+    /// See [`to_composite`](crate::to_composite).
+    pub fn convert_composite<'a>(
+        &self,
+        ast: &'a mdast::Node,
+        interner: &'a Interner,
+    ) -> Result<Composite<'a>, ToMinimadError> {
+        to_composite_with_options(ast, self.options.clone(), interner)
+    }
+
+    /// Convert the markdown AST to a minimad Text that does not borrow from the AST
+    pub fn convert_owned(&self, ast: &mdast::Node) -> Result<OwnedText, ToMinimadError> {
+        to_minimad_owned(ast, self.options.clone())
+    }
+
+    /// Parse a markdown source and convert it to a minimad Text
+    ///
+    /// The source is parsed with [`md_parse_options`].
+    pub fn convert_str(&self, source: &str) -> Result<OwnedText, ConvertError> {
+        let ast = markdown::to_mdast(source, &md_parse_options())
+            .map_err(|message| ConvertError::Parse { message })?;
+        Ok(self.convert_owned(&ast)?)
+    }
+
+    /// Convert the markdown AST to a minimad Text, skipping the nodes that cannot be converted
+    ///
+    /// See [`to_minimad_lossy`].
+    pub fn convert_lossy<'a>(
+        &self,
+        ast: &'a mdast::Node,
+        interner: &'a Interner,
+    ) -> (Text<'a>, Vec<ConversionWarning>) {
+        to_minimad_lossy(ast, self.options.clone(), interner)
+    }
+
+    /// Convert the markdown AST to a minimad Text, skipping the nodes that cannot be converted and
+    /// sending the warnings to `sink`
+    ///
+    /// See [`to_minimad_lossy_into`].
+    pub fn convert_lossy_into<'a>(
+        &self,
+        ast: &'a mdast::Node,
+        interner: &'a Interner,
+        sink: &mut dyn WarningSink,
+    ) -> Text<'a> {
+        to_minimad_lossy_into(ast, self.options.clone(), interner, sink)
+    }
+
+    /// Convert the markdown AST to a minimad Text, reporting on the converted content
+    pub fn convert_reporting<'a>(
+        &self,
+        ast: &'a mdast::Node,
+        interner: &'a Interner,
+    ) -> Result<(Text<'a>, ConversionReport<'a>), ToMinimadError> {
+        to_minimad_reporting(ast, self.options.clone(), interner)
+    }
+}
+
+impl From<Options> for Converter {
+    fn from(options: Options) -> Self {
+        Self::new(options)
+    }
+}
+
+/// Builder for [`Options`], or for a [`Converter`] using them
+///
+/// The setters are shared, only `build` differs. Start from [`Options::builder`] or
+/// [`Converter::builder`]: the fields not set keep their default value.
+pub struct Builder<T> {
+    options: Options,
+    target: PhantomData<fn() -> T>,
+}
+
+/// Builder for [`Options`]
+pub type OptionsBuilder = Builder<Options>;
+
+/// Builder for a [`Converter`]
+pub type ConverterBuilder = Builder<Converter>;
+
+impl<T> Default for Builder<T> {
+    fn default() -> Self {
+        Self {
+            options: Options::default(),
+            target: PhantomData,
+        }
+    }
+}
+
+impl<T> Clone for Builder<T> {
+    fn clone(&self) -> Self {
+        Self {
+            options: self.options.clone(),
+            target: PhantomData,
+        }
+    }
+}
+
+impl<T> fmt::Debug for Builder<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Builder")
+            .field("options", &self.options)
+            .finish()
+    }
+}
+
+impl<T> Builder<T> {
+    /// Start from the given options
+    pub fn options(mut self, options: Options) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Set if the headers of the given depth (1 to 6) need spacing after
+    ///
+    /// Other depths are ignored.
+    pub fn header_spacing(mut self, depth: u8, spacing: bool) -> Self {
+        if let Some(slot) = (depth as usize)
+            .checked_sub(1)
+            .and_then(|idx| self.options.header_spacing.get_mut(idx))
+        {
+            *slot = spacing
+        }
+        self
+    }
+
+    /// Set the empty lines around the blocks other than headers
+    pub fn spacing(mut self, spacing: Spacing) -> Self {
+        self.options.spacing = spacing;
+        self
+    }
+
+    /// Set how to style the headings of the given depth (1 to 6)
+    ///
+    /// Other depths are ignored.
+    pub fn heading_style(mut self, depth: u8, styling: Styling) -> Self {
+        if let Some(slot) = (depth as usize)
+            .checked_sub(1)
+            .and_then(|idx| self.options.heading_style.get_mut(idx))
+        {
+            *slot = styling
+        }
+        self
+    }
+
+    /// Set the shift of the depth of the headings
+    pub fn heading_offset(mut self, heading_offset: i8) -> Self {
+        self.options.heading_offset = heading_offset;
+        self
+    }
+
+    /// Set the maximum depth of the headings, and how to render the deeper ones
+    pub fn max_heading_depth(mut self, max_depth: u8, deep_heading: DeepHeading) -> Self {
+        self.options.max_heading_depth = (max_depth, deep_heading);
+        self
+    }
+
+    /// Set if the headings are prefixed with their section number
+    pub fn number_headings(mut self, number_headings: bool) -> Self {
+        self.options.number_headings = number_headings;
+        self
+    }
+
+    /// Set how to style the links
+    pub fn links_style(mut self, links_style: Styling) -> Self {
+        self.options.links_style = links_style;
+        self
+    }
+
+    /// Set how to style the autolinks, or `None` to style them as the other links
+    pub fn autolink_style(mut self, autolink_style: Option<Styling>) -> Self {
+        self.options.autolink_style = autolink_style;
+        self
+    }
+
+    /// Set how to style the links with the given URL schemes
+    pub fn scheme_styles(
+        mut self,
+        scheme_styles: impl IntoIterator<Item = (impl Into<Cow<'static, str>>, Styling)>,
+    ) -> Self {
+        self.options.scheme_styles = scheme_styles
+            .into_iter()
+            .map(|(scheme, styling)| (scheme.into(), styling))
+            .collect();
+        self
+    }
+
+    /// Set how to render the link targets, or `None` to follow the width class
+    pub fn link_mode(mut self, link_mode: Option<LinkMode>) -> Self {
+        self.options.link_mode = link_mode;
+        self
+    }
+
+    /// Set if the link text should be wrapped in OSC 8 escape sequences, making it clickable
+    #[cfg(feature = "osc8")]
+    pub fn hyperlinks(mut self, hyperlinks: bool) -> Self {
+        self.options.hyperlinks = hyperlinks;
+        self
+    }
+
+    /// Set where to take the text of the images from, in order of preference
+    pub fn image_text(mut self, image_text: impl Into<Arc<[ImageText]>>) -> Self {
+        self.options.image_text = image_text.into();
+        self
+    }
+
+    /// Set how to handle raw HTML
+    pub fn html_mode(mut self, html_mode: HtmlMode) -> Self {
+        self.options.html_mode = html_mode;
+        self
+    }
+
+    /// Set if form feeds in the text are page breaks
+    pub fn form_feed_page_breaks(mut self, form_feed_page_breaks: bool) -> Self {
+        self.options.form_feed_page_breaks = form_feed_page_breaks;
+        self
+    }
+
+    /// Set the prefix and the handler of the directives in HTML comments
+    pub fn directives(
+        mut self,
+        prefix: &'static str,
+        handler: impl for<'a> Fn(&'a str, &mut LineSink<'_, 'a>) -> Directive + Send + Sync + 'static,
+    ) -> Self {
+        self.options.directives = Some(Directives {
+            prefix,
+            handler: Handler::from(Arc::new(handler) as Arc<_>),
+        });
+        self
+    }
+
+    /// Set if math blocks should be labelled with a `math` line
+    pub fn math_label(mut self, math_label: bool) -> Self {
+        self.options.math_label = math_label;
+        self
+    }
+
+    /// Set if code blocks should be surrounded by code fences
+    pub fn code_fences(mut self, code_fences: bool) -> Self {
+        self.options.code_fences = code_fences;
+        self
+    }
+
+    /// Set how code blocks inside quotes are rendered
+    pub fn quoted_code(mut self, quoted_code: QuotedCode) -> Self {
+        self.options.quoted_code = quoted_code;
+        self
+    }
+
+    /// Set if inline badges (`[[key: value]]`) should be recognized
+    pub fn badges(mut self, badges: bool) -> Self {
+        self.options.badges = badges;
+        self
+    }
+
+    /// Set if inline footnotes (`^[text]`) should be recognized
+    pub fn inline_footnotes(mut self, inline_footnotes: bool) -> Self {
+        self.options.inline_footnotes = inline_footnotes;
+        self
+    }
+
+    /// Set the width class of the target terminal
+    pub fn width_class(mut self, width_class: WidthClass) -> Self {
+        self.options.width_class = width_class;
+        self
+    }
+
+    /// Set how to render the soft breaks inside paragraphs
+    pub fn soft_break(mut self, soft_break: SoftBreak) -> Self {
+        self.options.soft_break = soft_break;
+        self
+    }
+
+    /// Set how to render keyboard shortcuts, or `None` to leave them as text
+    pub fn kbd(mut self, kbd: Option<KbdStyle>) -> Self {
+        self.options.kbd = kbd;
+        self
+    }
+
+    /// Set if consecutive compounds with the same style should be merged
+    pub fn merge_compounds(mut self, merge_compounds: bool) -> Self {
+        self.options.merge_compounds = merge_compounds;
+        self
+    }
+
+    /// Set the spaces added before each line
+    pub fn indent(mut self, indent: u8) -> Self {
+        self.options.indent = indent;
+        self
+    }
+
+    /// Add a transform to apply to the converted text, after the ones already added
+    pub fn post(mut self, transform: impl TextTransform + 'static) -> Self {
+        self.options
+            .post
+            .push(Handler::from(Arc::new(transform) as Arc<_>));
+        self
+    }
+
+    /// Set what to do when the indentation exceeds what `minimad` can represent
+    pub fn indent_policy(mut self, indent_policy: IndentPolicy) -> Self {
+        self.options.indent_policy = indent_policy;
+        self
+    }
+
+    /// Set the prefix of the lines continuing a list item
+    pub fn list_continuation(mut self, list_continuation: impl Into<Cow<'static, str>>) -> Self {
+        self.options.list_continuation = list_continuation.into();
+        self
+    }
+
+    /// Set the width of the list bullets of the target `termimad` skin
+    pub fn bullet_width(mut self, bullet_width: Option<u8>) -> Self {
+        self.options.bullet_width = bullet_width;
+        self
+    }
+
+    /// Set how the items of ordered lists are numbered
+    pub fn numbering(mut self, numbering: Numbering) -> Self {
+        self.options.numbering = numbering;
+        self
+    }
+
+    /// Set the maximum number of bytes of text in the output, or `None` for no limit
+    pub fn max_output_bytes(mut self, max_output_bytes: Option<usize>) -> Self {
+        self.options.max_output_bytes = max_output_bytes;
+        self
+    }
+
+    /// Set the maximum number of top-level blocks in the output, or `None` for no limit
+    pub fn max_blocks(mut self, max_blocks: Option<usize>) -> Self {
+        self.options.max_blocks = max_blocks;
+        self
+    }
+
+    /// Set the maximum number of lines in the output, or `None` for no limit
+    pub fn max_lines(mut self, max_lines: Option<usize>) -> Self {
+        self.options.max_lines = max_lines;
+        self
+    }
+
+    /// Set the text of the line ending a truncated output
+    pub fn truncation_mark(mut self, truncation_mark: impl Into<Cow<'static, str>>) -> Self {
+        self.options.truncation_mark = truncation_mark.into();
+        self
+    }
+
+    /// Set the maximum nesting of the nodes of the AST
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.options.max_depth = max_depth;
+        self
+    }
+
+    /// Set the handler of the unsupported nodes
+    pub fn on_unsupported(
+        mut self,
+        handler: impl for<'a> Fn(&'a mdast::Node, &mut LineSink<'_, 'a>) -> Handled
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        self.options.on_unsupported = Some(Handler::from(Arc::new(handler) as Arc<_>));
+        self
+    }
+
+    /// Set a filter selecting the nodes to emit
+    pub fn filter(mut self, filter: impl Fn(&mdast::Node) -> bool + Send + Sync + 'static) -> Self {
+        self.options.filter = Some(Handler::from(Arc::new(filter) as Arc<_>));
+        self
+    }
+
+    /// Set if invalid ASTs should be an error
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.options.strict = strict;
+        self
+    }
+}
+
+impl Builder<Converter> {
+    /// Build the converter
+    pub fn build(self) -> Converter {
+        Converter::new(self.options)
+    }
+}
+
+impl Builder<Options> {
+    /// Build the options
+    pub fn build(self) -> Options {
+        self.options
+    }
+}
@@ -0,0 +1,101 @@
+//! Building blocks of the emitter, for extensions that emit their own content
+//!
+//! The [`Emitter`] builds a document incrementally, mixing converted markdown with lines
+//! generated by the application:
+//! ```
+//! # use mdast2minimad::{emitter::Emitter, md_parse_options, Interner, Options};
+//! # use minimad::{Compound, Line};
+//! let help = markdown::to_mdast("# Help\n\nPress `q` to quit", &md_parse_options()).unwrap();
+//! let interner = Interner::new();
+//! let mut emitter = Emitter::new(Options::default(), &interner);
+//! emitter.push_node(&help).unwrap();
+//! emitter.push_rule();
+//! emitter.push_paragraph(vec![Compound::raw_str("Connected").bold()]);
+//! let text = emitter.finish();
+//! assert_eq!(
+//!     text.lines.last(),
+//!     Some(&Line::new_paragraph(vec![Compound::raw_str("Connected").bold()]))
+//! );
+//! ```
+
+use std::mem;
+
+use minimad::{CompositeStyle, Compound, Line, Text};
+
+use self::{buffers::Buffers, state::ContentState};
+use crate::{mdast, Interner, Options, ToMinimadError};
+
+pub mod buffers;
+pub mod sink;
+pub mod state;
+
+/// Incremental builder of a converted document
+pub struct Emitter<'a> {
+    inner: crate::Emitter<'a>,
+}
+
+impl<'a> Emitter<'a> {
+    /// Create a new, empty document storing the synthesized strings in the given interner
+    pub fn new(options: Options, interner: &'a Interner) -> Self {
+        let mut inner = crate::Emitter::new(options, interner);
+        inner.state.flow();
+        Self { inner }
+    }
+
+    /// Create a new, empty document reusing the given allocations
+    pub fn with_buffers(options: Options, buffers: &mut Buffers, interner: &'a Interner) -> Self {
+        let mut emitter = Self::new(options, interner);
+        emitter.inner.state = ContentState::from_buffers(buffers);
+        emitter.inner.state.flow();
+        emitter
+    }
+
+    /// Empty the document to start a new one, keeping the options and the allocations
+    pub fn reset(&mut self) {
+        let mut buffers = Buffers::new();
+        let inner = crate::Emitter::new(self.inner.options.clone(), self.inner.interner);
+        mem::replace(&mut self.inner, inner)
+            .state
+            .recycle(&mut buffers);
+        self.inner.state = ContentState::from_buffers(&mut buffers);
+        self.inner.state.flow();
+    }
+
+    /// Convert a markdown node, and append it to the document
+    ///
+    /// After an error the document may contain part of the node.
+    pub fn push_node(&mut self, node: &'a mdast::Node) -> Result<(), ToMinimadError> {
+        self.inner.node(node)
+    }
+
+    /// Append a paragraph made of a single line
+    pub fn push_paragraph(&mut self, compounds: Vec<Compound<'a>>) {
+        self.inner
+            .phrasing(CompositeStyle::Paragraph, true, |this| {
+                this.state.line().extend(compounds)
+            })
+    }
+
+    /// Append a block made of the given lines, spaced as a paragraph
+    pub fn push_lines(&mut self, lines: impl IntoIterator<Item = Line<'a>>) {
+        self.inner
+            .phrasing(CompositeStyle::Paragraph, true, |this| {
+                this.state.lines.extend(lines);
+                // no line was opened
+                this.state.model = None;
+            })
+    }
+
+    /// Append a horizontal rule
+    pub fn push_rule(&mut self) {
+        self.inner
+            .phrasing(CompositeStyle::Paragraph, false, |this| {
+                this.state.lines.push(Line::HorizontalRule)
+            })
+    }
+
+    /// Complete the document, adding the footnotes and the appendixes
+    pub fn finish(self) -> Text<'a> {
+        self.inner.finish()
+    }
+}
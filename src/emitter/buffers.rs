@@ -0,0 +1,92 @@
+//! Allocations recycled between conversions
+//!
+//! Converting many small documents (e.g. chat messages) allocates a vector of lines and a vector
+//! of compounds for each line every time. Giving the converted texts back to a [`Buffers`] once
+//! they are no longer needed allows the next conversions to reuse those allocations:
+//! ```
+//! # use mdast2minimad::{emitter::buffers::Buffers, md_parse_options, Converter, Interner};
+//! let interner = Interner::new();
+//! let converter = Converter::default();
+//! let mut buffers = Buffers::new();
+//! for message in ["Hello!", "How are *you*?", "`ls -la`"] {
+//!     let ast = markdown::to_mdast(message, &md_parse_options()).unwrap();
+//!     let text = converter.convert_recycling(&ast, &mut buffers, &interner).unwrap();
+//!     // ... display the text ...
+//!     buffers.recycle(text);
+//! }
+//! ```
+//!
+//! The texts do not need to come from the same document: the buffers are free of lifetimes.
+//!
+//! The win is modest: on short chat messages the `recycle` benchmark measures about 3% less time
+//! per conversion, as most allocations are avoided but most of the time goes in the conversion
+//! itself.
+
+use minimad::{Compound, Line, Text};
+
+/// Allocations of converted texts, to be reused by the next conversions
+#[derive(Debug, Default)]
+pub struct Buffers {
+    /// The largest vector of lines recycled
+    lines: Vec<Line<'static>>,
+    /// Vectors of compounds recycled
+    compounds: Vec<Vec<Compound<'static>>>,
+}
+
+impl Buffers {
+    /// Create an empty set of buffers
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Give back the allocations of a text that is no longer needed
+    pub fn recycle(&mut self, text: Text<'_>) {
+        let mut lines = text.lines;
+        for line in lines.drain(..) {
+            match line {
+                Line::Normal(composite) => self.recycle_compounds(composite.compounds),
+                Line::TableRow(row) => {
+                    for cell in row.cells {
+                        self.recycle_compounds(cell.compounds)
+                    }
+                }
+                Line::TableRule(_) | Line::HorizontalRule | Line::CodeFence(_) => (),
+            }
+        }
+        if lines.capacity() > self.lines.capacity() {
+            self.lines = emptied(lines);
+        }
+    }
+
+    /// Give back a vector of compounds
+    pub(crate) fn recycle_compounds(&mut self, compounds: Vec<Compound<'_>>) {
+        if compounds.capacity() > 0 {
+            self.compounds.push(emptied(compounds))
+        }
+    }
+
+    /// Take the recycled vector of lines, that is empty
+    pub(crate) fn take_lines<'a>(&mut self) -> Vec<Line<'a>> {
+        std::mem::take(&mut self.lines)
+    }
+
+    /// Take the recycled vectors of compounds, that are empty
+    pub(crate) fn take_compounds<'a>(&mut self) -> Vec<Vec<Compound<'a>>> {
+        std::mem::take(&mut self.compounds)
+    }
+
+    /// Give back the recycled vectors of compounds that were not used
+    pub(crate) fn return_compounds(&mut self, compounds: Vec<Vec<Compound<'_>>>) {
+        // keep the allocation of the pool too
+        let mut compounds: Vec<_> = compounds.into_iter().map(emptied).collect();
+        compounds.append(&mut self.compounds);
+        self.compounds = compounds;
+    }
+}
+
+/// Empty a vector, reusing its allocation for a type differing only in lifetimes
+fn emptied<T, U>(mut vec: Vec<T>) -> Vec<U> {
+    vec.clear();
+    // collecting from the iterator of a vector reuses its allocation if the layouts match
+    vec.into_iter().map(|_| unreachable!()).collect()
+}
@@ -0,0 +1,71 @@
+//! Output of the extensions that emit their own content
+
+use std::borrow::Cow;
+
+use minimad::{CompositeStyle, Compound};
+
+use super::state::ContentState;
+use crate::Interner;
+
+/// If an extension took care of a node
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Handled {
+    /// The node was emitted
+    Yes,
+    /// The node was left alone, and nothing was pushed
+    No,
+}
+
+/// Where an extension emits the content of a node
+///
+/// Inside a line (e.g. for an inline node) the compounds are appended to the line. Blocks are
+/// spaced from the surrounding content as the ones emitted by the crate.
+pub struct LineSink<'s, 'a> {
+    state: &'s mut ContentState<'a>,
+    interner: &'a Interner,
+}
+
+impl<'s, 'a> LineSink<'s, 'a> {
+    pub(crate) fn new(state: &'s mut ContentState<'a>, interner: &'a Interner) -> Self {
+        Self { state, interner }
+    }
+
+    /// Store a synthesized string, so that it lives as long as the converted text
+    pub fn intern<'t>(&self, string: impl Into<Cow<'t, str>>) -> &'a str {
+        self.interner.intern(string)
+    }
+
+    /// Append a compound to the current line
+    ///
+    /// Outside of a line a new paragraph is started.
+    pub fn push(&mut self, compound: Compound<'a>) {
+        self.state.line().push(compound)
+    }
+
+    /// Append some unstyled text to the current line
+    pub fn push_str<'t>(&mut self, text: impl Into<Cow<'t, str>>) {
+        let text = self.intern(text);
+        self.push(Compound::raw_str(text))
+    }
+
+    /// Start a new line
+    pub fn newline(&mut self) {
+        self.state.newline()
+    }
+
+    /// Emit a block made of the given lines
+    pub fn block(
+        &mut self,
+        style: CompositeStyle,
+        lines: impl IntoIterator<Item = Vec<Compound<'a>>>,
+    ) {
+        let outer = self.state.open_phrasing(style);
+        for (idx, line) in lines.into_iter().enumerate() {
+            if idx > 0 {
+                self.state.newline();
+            }
+            self.state.line().extend(line);
+        }
+        self.state.close_phrasing(outer, true);
+    }
+}
@@ -0,0 +1,283 @@
+//! The content model state machine of the emitter
+//!
+//! The emitter is always in one of two content models:
+//! - [`ContentModel::Flow`], between blocks. No line is open, and `spacing` tells if the next
+//!   block must be preceded by an empty line.
+//! - [`ContentModel::Phrasing`], inside a block. A line is open, and its compounds are collected in
+//!   the model until a new line is started or the block is closed.
+//!
+//! Before anything is emitted the model is `None`, that is treated as flow without spacing.
+//!
+//! A block opens a phrasing context with [`ContentState::open_phrasing`], emits its text with
+//! [`ContentState::line`] and [`ContentState::newline`], and closes it with
+//! [`ContentState::close_phrasing`], passing back the [`OuterModel`] it got when opening. This
+//! keeps the spacing between the blocks and flushes the open line.
+//!
+//! Transitions that only make sense in invalid ASTs (a block inside a line, text outside a block)
+//! never fail: the state recovers as best as it can and records the reason, that can be taken with
+//! [`ContentState::take_invalid`].
+//!
+//! ```
+//! # use mdast2minimad::emitter::state::ContentState;
+//! # use minimad::{CompositeStyle, Compound, Line};
+//! let mut state = ContentState::default();
+//! let outer = state.open_phrasing(CompositeStyle::Paragraph);
+//! state.line().push(Compound::raw_str("first"));
+//! state.newline();
+//! state.line().push(Compound::raw_str("second"));
+//! state.close_phrasing(outer, true);
+//! let outer = state.open_phrasing(CompositeStyle::Paragraph);
+//! state.line().push(Compound::raw_str("third"));
+//! state.close_phrasing(outer, true);
+//!
+//! assert_eq!(state.take_invalid(), None);
+//! assert_eq!(
+//!     state.lines,
+//!     [
+//!         Line::new_paragraph(vec![Compound::raw_str("first")]),
+//!         Line::new_paragraph(vec![Compound::raw_str("second")]),
+//!         Line::new_paragraph(vec![]),
+//!         Line::new_paragraph(vec![Compound::raw_str("third")]),
+//!     ]
+//! );
+//! ```
+
+use std::mem;
+
+use minimad::{Composite, CompositeStyle, Compound, Line};
+
+use super::buffers::Buffers;
+
+/// Represent the current content model of the emitter
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ContentModel<'a> {
+    /// Flow content represent the sections of document.
+    Flow {
+        /// If the last flow element need spacing
+        spacing: bool,
+    },
+    /// Phrasing content represent the text in a document, and its markup.
+    Phrasing {
+        /// Style of the lines
+        style: CompositeStyle,
+        /// Line being built
+        compounds: Vec<Compound<'a>>,
+    },
+}
+impl ContentModel<'_> {
+    /// Name of the model, for the traces
+    pub fn name(&self) -> &'static str {
+        match self {
+            ContentModel::Flow { .. } => "flow",
+            ContentModel::Phrasing { .. } => "phrasing",
+        }
+    }
+
+    /// If an empty line is needed before the next block
+    pub fn need_spacing(&self) -> bool {
+        match self {
+            ContentModel::Flow { spacing } => *spacing,
+            ContentModel::Phrasing { .. } => {
+                // Spacing has no sense between phrasing elements.
+                // Should appear only in invalid ASTs
+                // Defaulting to not giving it
+                false
+            }
+        }
+    }
+
+    /// Set if an empty line is needed before the next block
+    pub fn set_spacing(&mut self, new_spacing: bool) {
+        match self {
+            ContentModel::Flow { spacing } => *spacing = new_spacing,
+            ContentModel::Phrasing { .. } => {
+                // Here the spacing has no sense.
+                // Should appear only in invalid ASTs
+            }
+        }
+    }
+}
+
+/// The model that was current when a phrasing context was opened
+///
+/// It must be given back to [`ContentState::close_phrasing`].
+#[derive(Debug)]
+#[must_use = "the phrasing context must be closed"]
+pub struct OuterModel<'a>(ContentModel<'a>);
+
+/// The lines emitted up to now, and the current content model
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ContentState<'a> {
+    /// Lines already emitted
+    pub lines: Vec<Line<'a>>,
+    /// Current content model
+    pub model: Option<ContentModel<'a>>,
+    /// Why the AST is invalid, if an invalid transition happened
+    invalid: Option<&'static str>,
+    /// Empty vectors of compounds, to be reused for the next lines
+    pub(crate) spare: Vec<Vec<Compound<'a>>>,
+}
+
+impl<'a> ContentState<'a> {
+    /// Create an empty state, reusing the given allocations
+    pub fn from_buffers(buffers: &mut Buffers) -> Self {
+        Self {
+            lines: buffers.take_lines(),
+            spare: buffers.take_compounds(),
+            ..Self::default()
+        }
+    }
+
+    /// Give back the allocations of the state, including the emitted lines
+    pub fn recycle(self, buffers: &mut Buffers) {
+        buffers.recycle(minimad::Text { lines: self.lines });
+        if let Some(ContentModel::Phrasing { compounds, .. }) = self.model {
+            buffers.recycle_compounds(compounds)
+        }
+        buffers.return_compounds(self.spare);
+    }
+
+    /// Enter flow content, if nothing was emitted yet
+    pub fn flow(&mut self) {
+        if self.model.is_none() {
+            #[cfg(feature = "tracing")]
+            tracing::trace!("enter flow");
+            self.model = Some(ContentModel::Flow { spacing: false });
+        }
+    }
+
+    /// Name of the current content model, for the traces
+    pub fn model_name(&self) -> &'static str {
+        self.model.as_ref().map_or("undefined", ContentModel::name)
+    }
+
+    /// Check if a line is open
+    pub fn is_phrasing(&self) -> bool {
+        matches!(self.model, Some(ContentModel::Phrasing { .. }))
+    }
+
+    /// The compounds of the open line, if any
+    pub fn open_line(&self) -> &[Compound<'a>] {
+        match &self.model {
+            Some(ContentModel::Phrasing { compounds, .. }) => compounds,
+            None | Some(ContentModel::Flow { .. }) => &[],
+        }
+    }
+
+    /// Open a phrasing context, whose lines have the given style
+    ///
+    /// An empty line is emitted first if the previous block asked for spacing.
+    pub fn open_phrasing(&mut self, style: CompositeStyle) -> OuterModel<'a> {
+        // remove the old model, and if it was undefined set it to flow
+        let mut old_model = self
+            .model
+            .take()
+            .unwrap_or(ContentModel::Flow { spacing: false });
+        if let ContentModel::Phrasing { style, compounds } = &mut old_model {
+            // the old model was in the middle of a line. This can happen only in invalid ASTs, as the nodes that use `Phrasing`
+            // as inner content should be called only in `Flow` model. Anyway, let's not mix up the content emitting that line
+            self.invalid("flow content inside phrasing content");
+            self.lines.push(Line::Normal(Composite {
+                style: *style,
+                compounds: mem::take(compounds),
+            }));
+        }
+        // put a spacing newline between flows element
+        if old_model.need_spacing() {
+            self.emptyline()
+        }
+        #[cfg(feature = "tracing")]
+        tracing::trace!(?style, from = old_model.name(), "open phrasing");
+        // set the new model as phrasing with the given style
+        self.model = Some(ContentModel::Phrasing {
+            style,
+            compounds: self.spare.pop().unwrap_or_default(),
+        });
+        OuterModel(old_model)
+    }
+
+    /// Close a phrasing context, emitting the open line
+    ///
+    /// `spacing` tells if the next block must be preceded by an empty line.
+    pub fn close_phrasing(&mut self, OuterModel(mut old_model): OuterModel<'a>, spacing: bool) {
+        #[cfg(feature = "tracing")]
+        tracing::trace!(to = old_model.name(), "close phrasing");
+        // put the old model back, settign spacing
+        old_model.set_spacing(spacing);
+        let residuals = self.model.replace(old_model);
+        // if some compounds remains, emit them
+        if let Some(ContentModel::Phrasing { style, compounds }) = residuals {
+            self.lines
+                .push(Line::Normal(Composite { style, compounds }));
+        }
+    }
+
+    /// Return the current line
+    ///
+    /// Outside of a phrasing context a new paragraph is started.
+    pub fn line(&mut self) -> &mut Vec<Compound<'a>> {
+        if !self.is_phrasing() {
+            // If not phrasing (only in invalid ASTs), or if the model is undefined, assume we begin a new paragraph
+            if self.model.is_some() {
+                self.invalid("phrasing content outside of a paragraph");
+            }
+            #[cfg(feature = "tracing")]
+            tracing::trace!(from = self.model_name(), "open implicit paragraph");
+            self.model = Some(ContentModel::Phrasing {
+                style: CompositeStyle::Paragraph,
+                compounds: self.spare.pop().unwrap_or_default(),
+            });
+        }
+        let Some(ContentModel::Phrasing {
+            style: _,
+            compounds,
+        }) = &mut self.model
+        else {
+            unreachable!()
+        };
+        compounds
+    }
+
+    /// Start a new line
+    pub fn newline(&mut self) {
+        match &mut self.model {
+            Some(ContentModel::Phrasing { style, compounds }) => {
+                let fresh = self.spare.pop().unwrap_or_default();
+                self.lines.push(Line::Normal(Composite {
+                    style: *style,
+                    compounds: mem::replace(compounds, fresh),
+                }))
+            }
+            None | Some(ContentModel::Flow { .. }) => {
+                // In this models a newline has no meaning. The method should only be called when in phrasing contexts.
+                // Anyway ignoring to be lenient on malformed ASTs
+                self.invalid("line break outside of a paragraph")
+            }
+        }
+    }
+
+    /// Emit a empty line
+    pub fn emptyline(&mut self) {
+        self.lines.push(Line::new_paragraph(vec![]))
+    }
+
+    /// Emit the open line, if any, leaving the model undefined
+    pub fn flush(&mut self) {
+        if let Some(ContentModel::Phrasing { style, compounds }) = self.model.take() {
+            self.lines
+                .push(Line::Normal(Composite { style, compounds }))
+        }
+    }
+
+    /// Record that an invalid transition happened
+    ///
+    /// Only the first reason is kept, until it is taken.
+    pub fn invalid(&mut self, reason: &'static str) {
+        self.invalid.get_or_insert(reason);
+    }
+
+    /// Take the reason of the first invalid transition since the last call
+    pub fn take_invalid(&mut self) -> Option<&'static str> {
+        self.invalid.take()
+    }
+}
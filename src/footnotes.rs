@@ -0,0 +1,77 @@
+//! Collection of the footnotes of a document
+
+use std::collections::HashMap;
+
+use minimad::{Compound, Line};
+
+/// The footnotes of a document, numbered in order of first reference
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Footnotes<'a> {
+    /// Number of each referenced footnote
+    numbers: HashMap<&'a str, usize>,
+    /// Content of the referenced footnotes, by number. `None` if not defined (yet).
+    notes: Vec<Option<Vec<Line<'a>>>>,
+    /// Definitions of footnotes that were not referenced yet
+    pending: HashMap<&'a str, Vec<Line<'a>>>,
+    /// Number of the footnote of each link target
+    links: HashMap<&'a str, usize>,
+}
+
+impl<'a> Footnotes<'a> {
+    /// Reference a footnote, returning its number
+    pub(crate) fn reference(&mut self, identifier: &'a str) -> usize {
+        if let Some(&number) = self.numbers.get(identifier) {
+            return number;
+        }
+        self.notes.push(self.pending.remove(identifier));
+        let number = self.notes.len();
+        self.numbers.insert(identifier, number);
+        number
+    }
+
+    /// Define the content of a footnote
+    pub(crate) fn define(&mut self, identifier: &'a str, lines: Vec<Line<'a>>) {
+        match self.numbers.get(identifier) {
+            Some(&number) => self.notes[number - 1] = Some(lines),
+            None => {
+                self.pending.insert(identifier, lines);
+            }
+        }
+    }
+
+    /// Add a footnote defined where it is referenced, returning its number
+    pub(crate) fn inline(&mut self, lines: Vec<Line<'a>>) -> usize {
+        self.notes.push(Some(lines));
+        self.notes.len()
+    }
+
+    /// Add a footnote with a link target, returning its number
+    ///
+    /// Each target is listed only once.
+    pub(crate) fn link(&mut self, url: &'a str) -> usize {
+        if let Some(&number) = self.links.get(url) {
+            return number;
+        }
+        let number = self.inline(vec![Line::new_paragraph(vec![Compound::raw_str(url)])]);
+        self.links.insert(url, number);
+        number
+    }
+
+    /// Check if no footnote was referenced
+    pub(crate) fn is_empty(&self) -> bool {
+        self.notes.is_empty()
+    }
+
+    /// Number of footnotes referenced or defined
+    pub(crate) fn len(&self) -> usize {
+        self.notes.len() + self.pending.len()
+    }
+
+    /// The referenced and defined footnotes, with their number
+    pub(crate) fn into_notes(self) -> impl Iterator<Item = (usize, Vec<Line<'a>>)> {
+        self.notes
+            .into_iter()
+            .enumerate()
+            .filter_map(|(idx, lines)| Some((idx + 1, lines?)))
+    }
+}
@@ -0,0 +1,53 @@
+//! Callbacks stored in the options
+
+use std::{fmt, ops::Deref, sync::Arc};
+
+/// A callback stored in the [`Options`](crate::Options)
+///
+/// The callback is shared behind an [`Arc`], so that cloning the options is cheap, and must be
+/// `'static`, `Send` and `Sync`. The options never borrow anything, and the converted text borrows
+/// only from the AST.
+///
+/// ```
+/// # use std::sync::Arc;
+/// # use mdast2minimad::Handler;
+/// let shout: Handler<dyn Fn(&str) -> String + Send + Sync> =
+///     Handler::from(Arc::new(|text: &str| text.to_uppercase()) as Arc<_>);
+/// assert_eq!(shout("hello"), "HELLO");
+/// ```
+///
+/// Callbacks that borrow from their environment are rejected:
+/// ```compile_fail
+/// # use std::sync::Arc;
+/// # use mdast2minimad::Handler;
+/// let prefix = String::from("> ");
+/// let quote: Handler<dyn Fn(&str) -> String + Send + Sync> =
+///     Handler::from(Arc::new(|text: &str| format!("{}{text}", &prefix)) as Arc<_>);
+/// ```
+pub struct Handler<F: ?Sized + Send + Sync + 'static>(Arc<F>);
+
+impl<F: ?Sized + Send + Sync + 'static> From<Arc<F>> for Handler<F> {
+    fn from(callback: Arc<F>) -> Self {
+        Self(callback)
+    }
+}
+
+impl<F: ?Sized + Send + Sync + 'static> Deref for Handler<F> {
+    type Target = F;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<F: ?Sized + Send + Sync + 'static> Clone for Handler<F> {
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+impl<F: ?Sized + Send + Sync + 'static> fmt::Debug for Handler<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Handler").finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,227 @@
+//! Help texts written in markdown, with parts filled at runtime
+//!
+//! The help document can contain placeholders in the form `${name}`. A placeholder that stands
+//! alone in its paragraph is an expansion area, that can be replaced by any number of lines (e.g.
+//! a table of the options), while the others are replaced inline by a string.
+//!
+//! ```
+//! # use mdast2minimad::{help::HelpTemplate, Interner, Options};
+//! let interner = Interner::new();
+//! let mut parse_options = mdast2minimad::md_parse_options();
+//! // `$` would otherwise start inline math
+//! parse_options.constructs.math_text = false;
+//! let ast = markdown::to_mdast(
+//!     "Usage: `${name} [OPTIONS]`\n\n${options}",
+//!     &parse_options,
+//! )
+//! .unwrap();
+//!
+//! let template = HelpTemplate::new(&ast, Options::default(), &interner).unwrap();
+//! let text = template
+//!     .expander()
+//!     .set("name", "mytool")
+//!     .set_lines("options", vec![minimad::Line::raw_str("-h: print this help")])
+//!     .expand();
+//! assert_eq!(text.lines.len(), 3);
+//! ```
+
+use std::collections::HashMap;
+
+use minimad::{Composite, Line, Text};
+
+use crate::{mdast, Emitter, Interner, Options, ToMinimadError};
+
+/// A help text with placeholders
+#[derive(Debug, Clone)]
+pub struct HelpTemplate<'a> {
+    /// The converted text
+    text: Text<'a>,
+    /// Inline placeholders
+    args: Vec<Arg<'a>>,
+    /// Expansion areas
+    areas: Vec<Area<'a>>,
+}
+
+/// A inline placeholder
+#[derive(Debug, Clone, Copy)]
+struct Arg<'a> {
+    name: &'a str,
+    line: usize,
+    /// Cell of the table row, if the placeholder is inside a table
+    cell: Option<usize>,
+    compound: usize,
+}
+
+/// A line to be replaced by the expansion
+#[derive(Debug, Clone, Copy)]
+struct Area<'a> {
+    name: &'a str,
+    line: usize,
+}
+
+impl<'a> HelpTemplate<'a> {
+    /// Convert a help document into a template
+    pub fn new(
+        ast: &'a mdast::Node,
+        options: Options,
+        interner: &'a Interner,
+    ) -> Result<Self, ToMinimadError> {
+        let mut emitter = Emitter::new(options, interner);
+        emitter.node(ast)?;
+        let mut text = emitter.finish();
+
+        let mut args = vec![];
+        let mut areas = vec![];
+        for (line_idx, line) in text.lines.iter_mut().enumerate() {
+            match line {
+                Line::Normal(composite) => {
+                    if let Some(name) = area_name(composite) {
+                        areas.push(Area {
+                            name,
+                            line: line_idx,
+                        })
+                    } else {
+                        find_args(composite, &mut args, line_idx, None)
+                    }
+                }
+                Line::TableRow(row) => {
+                    for (cell_idx, cell) in row.cells.iter_mut().enumerate() {
+                        find_args(cell, &mut args, line_idx, Some(cell_idx))
+                    }
+                }
+                Line::TableRule(_) | Line::HorizontalRule | Line::CodeFence(_) => (),
+            }
+        }
+
+        Ok(Self { text, args, areas })
+    }
+
+    /// Names of the inline placeholders
+    pub fn placeholders(&self) -> impl Iterator<Item = &'a str> + '_ {
+        self.args.iter().map(|arg| arg.name)
+    }
+
+    /// Names of the expansion areas
+    pub fn areas(&self) -> impl Iterator<Item = &'a str> + '_ {
+        self.areas.iter().map(|area| area.name)
+    }
+
+    /// Start filling the template
+    pub fn expander(&self) -> HelpExpander<'a, '_> {
+        HelpExpander {
+            template: self,
+            values: HashMap::new(),
+            areas: HashMap::new(),
+        }
+    }
+}
+
+/// Values to fill a [`HelpTemplate`] with
+///
+/// Placeholders and areas that are not set are removed from the expanded text.
+#[derive(Debug, Clone)]
+pub struct HelpExpander<'a, 't> {
+    template: &'t HelpTemplate<'a>,
+    values: HashMap<&'t str, &'a str>,
+    areas: HashMap<&'t str, Vec<Line<'a>>>,
+}
+
+impl<'a, 't> HelpExpander<'a, 't> {
+    /// Set the value of a inline placeholder
+    pub fn set(&mut self, name: &'t str, value: &'a str) -> &mut Self {
+        self.values.insert(name, value);
+        self
+    }
+
+    /// Set the lines of an expansion area
+    pub fn set_lines(&mut self, name: &'t str, lines: Vec<Line<'a>>) -> &mut Self {
+        self.areas.insert(name, lines);
+        self
+    }
+
+    /// Fill the template
+    pub fn expand(&self) -> Text<'a> {
+        let mut lines = self.template.text.lines.clone();
+        for arg in &self.template.args {
+            let composite = match (&mut lines[arg.line], arg.cell) {
+                (Line::Normal(composite), None) => composite,
+                (Line::TableRow(row), Some(cell)) => &mut row.cells[cell],
+                _ => unreachable!("Args are recorded only on normal lines and table rows"),
+            };
+            composite.compounds[arg.compound].src =
+                self.values.get(arg.name).copied().unwrap_or_default();
+        }
+        // replace the areas, from the last so the indices stay valid
+        for area in self.template.areas.iter().rev() {
+            lines.splice(
+                area.line..=area.line,
+                self.areas.get(area.name).into_iter().flatten().cloned(),
+            );
+        }
+        Text { lines }
+    }
+}
+
+/// Check if a composite is only a placeholder, and return its name
+fn area_name<'a>(composite: &Composite<'a>) -> Option<&'a str> {
+    let [compound] = &composite.compounds[..] else {
+        return None;
+    };
+    let name = compound.src.trim().strip_prefix("${")?.strip_suffix('}')?;
+    is_valid_name(name).then_some(name)
+}
+
+/// Split the compounds of a composite so that each placeholder is in its own compound
+fn find_args<'a>(
+    composite: &mut Composite<'a>,
+    args: &mut Vec<Arg<'a>>,
+    line: usize,
+    cell: Option<usize>,
+) {
+    let mut compounds = Vec::with_capacity(composite.compounds.len());
+    for compound in &composite.compounds {
+        let mut rest = compound.clone();
+        while let Some((start, name)) = find_placeholder(rest.src) {
+            if start > 0 {
+                compounds.push(rest.sub(0, start));
+            }
+            args.push(Arg {
+                name,
+                line,
+                cell,
+                compound: compounds.len(),
+            });
+            let end = start + name.len() + 3;
+            compounds.push(rest.sub(start, end));
+            rest = rest.tail(end);
+        }
+        if !rest.is_empty() {
+            compounds.push(rest);
+        }
+    }
+    composite.compounds = compounds;
+}
+
+/// Find the first placeholder in a string, returning its start and name
+fn find_placeholder(src: &str) -> Option<(usize, &str)> {
+    let mut from = 0;
+    while let Some(start) = src[from..].find("${").map(|idx| idx + from) {
+        let name_start = start + 2;
+        if let Some(len) = src[name_start..].find('}') {
+            let name = &src[name_start..name_start + len];
+            if is_valid_name(name) {
+                return Some((start, name));
+            }
+        }
+        from = name_start;
+    }
+    None
+}
+
+/// Placeholder names follow the same rules as `minimad` templates
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-')
+}
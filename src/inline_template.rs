@@ -0,0 +1,129 @@
+//! Conversion to the inline markup understood by `minimad`
+
+use std::borrow::Cow;
+
+use minimad::{Composite, Compound};
+
+use crate::{mdast, to_composite_with_options, Interner, Options, ToMinimadError};
+
+/// Convert phrasing content to a string of `minimad` inline markup (`**bold** *italic* `code``)
+///
+/// This is useful for the APIs that take markup strings, like `termimad`'s `MadSkin::inline`. The
+/// characters that `minimad` would misparse are escaped, except the backticks inside code, that
+/// are moved out of it. As [`to_composite`](crate::to_composite), the content must fit in a single
+/// line.
+///
+/// ```
+/// # use mdast2minimad::{md_parse_options, to_inline_template};
+/// let ast = markdown::to_mdast("Run **`cargo *`** now", &md_parse_options()).unwrap();
+/// let template = to_inline_template(&ast).unwrap();
+/// assert_eq!(template, "Run **`cargo *`** now");
+/// ```
+pub fn to_inline_template(ast: &mdast::Node) -> Result<String, ToMinimadError> {
+    to_inline_template_with_options(ast, Options::default())
+}
+
+/// Convert phrasing content to a string of `minimad` inline markup, with the given options
+///
+/// See [`to_inline_template`].
+pub fn to_inline_template_with_options(
+    ast: &mdast::Node,
+    options: Options,
+) -> Result<String, ToMinimadError> {
+    let interner = Interner::new();
+    Ok(inline_template(&to_composite_with_options(
+        ast, options, &interner,
+    )?))
+}
+
+/// Write the markup of a composite
+fn inline_template(composite: &Composite<'_>) -> String {
+    let mut template = String::new();
+    // style of the markup written up to now
+    let mut current = Compound::raw_str("");
+    for compound in &composite.compounds {
+        if compound.src.is_empty() {
+            continue;
+        }
+        let restyle = compound.bold != current.bold
+            || compound.italic != current.italic
+            || compound.strikeout != current.strikeout;
+        // nothing is parsed inside code, so it must be closed to change the style
+        if current.code && (restyle || !compound.code) {
+            template.push('`');
+            current.code = false;
+        }
+        let lone_star = toggle_style(&mut template, &mut current, compound);
+        if compound.code {
+            if !current.code {
+                template.push('`');
+                current.code = true;
+            }
+            // code cannot contain backticks: escaping them outside of the code
+            template.push_str(&compound.src.replace('`', "`\\``"));
+            continue;
+        }
+        let escaped = escape_inline(compound.src);
+        if lone_star && escaped.starts_with('\\') {
+            // `minimad` does not recognize a lone star followed by an escape. A double
+            // strikeout toggle is a no-op that completes it.
+            template.push_str("~~~~");
+        }
+        template.push_str(&escaped);
+    }
+    if current.code {
+        template.push('`');
+    }
+    toggle_style(&mut template, &mut current, &Compound::raw_str(""));
+    template
+}
+
+/// Escape the characters that `minimad` would parse as inline markup
+///
+/// The result is parsed back by `minimad` as the same plain text, and can be used to build markup
+/// strings by hand.
+///
+/// ```
+/// # use mdast2minimad::escape_inline;
+/// assert_eq!(escape_inline("a*b | c"), "a\\*b \\| c");
+/// assert_eq!(
+///     minimad::Composite::from_inline(&format!("**{}**", escape_inline("2*3"))).compounds,
+///     [minimad::Compound::raw_str("2").bold(), minimad::Compound::raw_str("*3").bold()]
+/// );
+/// ```
+pub fn escape_inline(text: &str) -> Cow<'_, str> {
+    const SPECIAL: [char; 5] = ['\\', '*', '~', '`', '|'];
+    if !text.contains(SPECIAL) {
+        return Cow::Borrowed(text);
+    }
+    let mut escaped = String::with_capacity(text.len() + 1);
+    for ch in text.chars() {
+        if SPECIAL.contains(&ch) {
+            escaped.push('\\');
+        }
+        escaped.push(ch);
+    }
+    Cow::Owned(escaped)
+}
+
+/// Write the markers that change the style of `current` into the one of `target`
+///
+/// Return `true` if the last marker is the lone star of italic.
+fn toggle_style(template: &mut String, current: &mut Compound<'_>, target: &Compound<'_>) -> bool {
+    let mut lone_star = false;
+    if current.bold != target.bold {
+        template.push_str("**");
+        current.bold = target.bold;
+    }
+    if current.italic != target.italic {
+        template.push('*');
+        current.italic = target.italic;
+        lone_star = true;
+    }
+    if current.strikeout != target.strikeout {
+        template.push_str("~~");
+        current.strikeout = target.strikeout;
+        lone_star = false;
+    }
+    lone_star
+}
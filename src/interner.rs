@@ -0,0 +1,60 @@
+//! Storage for the strings synthesized during the conversion
+
+use std::{
+    borrow::Cow,
+    collections::HashSet,
+    sync::{Mutex, PoisonError},
+};
+
+/// Deduplicating storage of strings
+///
+/// `minimad` compounds can only borrow their content. The strings that are not in the AST are
+/// stored here, and live as long as the interner.
+///
+/// An interner can be shared between many conversions (e.g. all the messages of a chat history),
+/// so that each synthesized string is stored only once. The strings are freed when the interner
+/// is dropped: conversions returning an [`OwnedText`](crate::OwnedText) use their own.
+#[derive(Debug, Default)]
+pub struct Interner {
+    strings: Mutex<HashSet<Box<str>>>,
+}
+
+impl Interner {
+    /// Create a new, empty interner
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Store a string, returning a reference to the stored copy
+    ///
+    /// Equal strings are stored only once.
+    pub fn intern<'s>(&self, string: impl Into<Cow<'s, str>>) -> &str {
+        let string = string.into();
+        let mut strings = self.strings.lock().unwrap_or_else(PoisonError::into_inner);
+        let stored: *const str = match strings.get(&*string) {
+            Some(stored) => &**stored,
+            None => {
+                let stored = string.into_owned().into_boxed_str();
+                let ptr: *const str = &*stored;
+                strings.insert(stored);
+                ptr
+            }
+        };
+        // SAFETY: the strings are boxed, so their content does not move when the set is
+        // reallocated, and they are never removed or mutated until the interner is dropped.
+        unsafe { &*stored }
+    }
+
+    /// Number of stored strings
+    pub fn len(&self) -> usize {
+        self.strings
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .len()
+    }
+
+    /// Check if no string is stored
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
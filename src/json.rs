@@ -0,0 +1,46 @@
+//! Reading of markdown ASTs serialized as JSON, e.g. by `remark`
+
+use derive_more::derive::{Display, Error};
+
+use crate::mdast;
+
+/// Read a markdown AST serialized as JSON
+///
+/// The JSON follows the [mdast](https://github.com/syntax-tree/mdast) specification, as produced
+/// by `remark` and the other tools of the unified ecosystem. This allows converting documents
+/// processed by a JavaScript pipeline without parsing the markdown again.
+///
+/// The JSON can be nested at most 128 levels deep, so each node takes two levels (the node and its
+/// `children`): deeper documents are rejected instead of overflowing the stack.
+/// ```
+/// # use mdast2minimad::{from_mdast_json, to_minimad, Interner};
+/// # use minimad::{Compound, Line};
+/// let interner = Interner::new();
+/// let ast = from_mdast_json(
+///     r#"{
+///         "type": "root",
+///         "children": [
+///             {"type": "heading", "depth": 1, "children": [{"type": "text", "value": "Title"}]}
+///         ]
+///     }"#,
+/// )
+/// .unwrap();
+/// assert_eq!(
+///     to_minimad(&ast, &interner).unwrap().lines,
+///     [Line::new_header(1, vec![Compound::raw_str("Title")])]
+/// );
+/// ```
+pub fn from_mdast_json(json: &str) -> Result<mdast::Node, JsonError> {
+    serde_json::from_str(json).map_err(|error| JsonError {
+        message: error.to_string(),
+    })
+}
+
+/// Error while reading a JSON AST
+#[derive(Debug, Clone, PartialEq, Eq, Display, Error)]
+#[display("Invalid mdast JSON: {message}")]
+pub struct JsonError {
+    /// What went wrong
+    #[error(not(source))]
+    pub message: String,
+}
@@ -1,52 +1,221 @@
 #![doc = include_str!("../README.md")]
 
-use std::mem;
+use std::{borrow::Cow, collections::HashMap, iter, mem, ops::Range, sync::Arc};
 
-use derive_more::derive::{Debug, Display, Error};
+use derive_more::derive::{Debug, Display, Error, From};
 pub use markdown::mdast;
-use minimad::{Composite, CompositeStyle, Compound, Line, TableRow, TableRule, Text};
+use markdown::unist::Position;
+use minimad::{
+    Composite, CompositeStyle, Compound, Line, TableRow, TableRule, Text, MAX_HEADER_DEPTH,
+};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+mod batch;
+mod cache;
+#[cfg(feature = "changelog")]
+pub mod changelog;
+#[cfg(feature = "commit")]
+pub mod commit;
+mod compare;
+mod context;
+mod converter;
+pub mod emitter;
+mod footnotes;
+mod handler;
+#[cfg(feature = "help")]
+pub mod help;
+mod inline_template;
+mod interner;
+#[cfg(feature = "mdast-json")]
+mod json;
+mod lines_iter;
+#[cfg(feature = "mdbook")]
+pub mod mdbook;
+mod mdx;
+pub mod model;
+mod node_kind;
+mod owned;
+pub mod pages;
+mod plain;
+mod reverse;
+pub mod sections;
+mod serialize;
+pub mod skeleton;
+mod skin_hints;
+pub mod slides;
+#[cfg(feature = "termimad")]
+mod terminal;
+pub mod toc;
+pub mod transform;
+
+pub use batch::{convert_many, Parallelism};
+pub use cache::ConversionCache;
+pub use compare::{compare_with_minimad, render_markdown, Comparison, LineDifference, Rendering};
+pub use context::DocumentContext;
+pub use converter::{Builder, Converter, ConverterBuilder, OptionsBuilder};
+pub use emitter::sink::{Handled, LineSink};
+use emitter::state::{ContentModel, ContentState};
+use footnotes::Footnotes;
+pub use handler::Handler;
+pub use inline_template::{escape_inline, to_inline_template, to_inline_template_with_options};
+pub use interner::Interner;
+#[cfg(feature = "mdast-json")]
+pub use json::{from_mdast_json, JsonError};
+pub use lines_iter::LinesIter;
+pub use mdx::{to_mdx_template, to_mdx_template_with_options, MdxTemplate, Placeholder};
+pub use node_kind::{NodeKind, UnknownNodeKind};
+pub use owned::OwnedText;
+pub use plain::{plain_text, to_plain_text, to_plain_text_with_options};
+pub use reverse::from_minimad;
+pub use serialize::to_markdown;
+pub use skin_hints::{skin_hints, SkinHints};
+#[cfg(feature = "termimad")]
+pub use terminal::{print, render_to_string};
+use transform::{LimitBlankRuns, TextTransform};
 
 #[derive(Clone, Debug, Display, Error)]
 /// Error while converting the AST into a `minimad` text
+///
+/// Each variant carries the position in the source of the node that caused it, if the AST has
+/// positions.
 pub enum ToMinimadError {
     #[display("While emitting a `{node}` node")]
     WhileEmitting {
-        node: &'static str,
+        node: NodeKind,
+        position: Option<Position>,
         source: Box<ToMinimadError>,
     },
     #[display("`{node}` node is not supported")]
-    UnsupportedNode { node: &'static str },
+    UnsupportedNode {
+        node: NodeKind,
+        position: Option<Position>,
+    },
     #[display("`{child}` node is not supported as a child")]
-    UnsupportedChildNode { child: &'static str },
-    #[display("Numbered lists are not supported")]
-    UnsupportedNumberedLists,
+    UnsupportedChildNode {
+        child: NodeKind,
+        position: Option<Position>,
+    },
     #[display("`minimad` supports nested list only up to 255 levels")]
-    ListTooMuchNested,
+    ListTooMuchNested { position: Option<Position> },
     #[display("`minimad` does not support multiline table cells")]
-    MultilineTableCell,
+    MultilineTableCell { position: Option<Position> },
     // This error should only appear on malformed ASTs
     #[display("A table cell can contain only normal lines")]
-    InvalidLineTypeInTableCell,
+    InvalidLineTypeInTableCell { position: Option<Position> },
+    /// Only returned by [`to_composite`]
+    #[display("The content does not fit in a single line")]
+    MultilineComposite { position: Option<Position> },
+    /// Only returned if the AST is nested deeper than [`Options::max_depth`]
+    #[display("The AST is nested deeper than {max_depth} nodes")]
+    TooDeep {
+        max_depth: usize,
+        position: Option<Position>,
+    },
+    /// Only returned if [`Options::strict`] is set
+    #[display("Invalid AST: {reason}")]
+    InvalidAst {
+        reason: &'static str,
+        position: Option<Position>,
+    },
 }
 impl ToMinimadError {
-    fn unsupported_node(node: &mdast::Node) -> Self {
+    /// The error for a node that cannot be emitted
+    ///
+    /// Custom emitters can use it for the nodes they do not support, as the crate does.
+    pub fn unsupported_node(node: &mdast::Node) -> Self {
         Self::UnsupportedNode {
-            node: type_of(node),
+            node: NodeKind::of(node),
+            position: node.position().cloned(),
         }
     }
-    fn unsupported_child_node(child: &mdast::Node) -> Self {
+
+    /// The error for a node that cannot be emitted as a child of its parent
+    pub fn unsupported_child_node(child: &mdast::Node) -> Self {
         Self::UnsupportedChildNode {
-            child: type_of(child),
+            child: NodeKind::of(child),
+            position: child.position().cloned(),
+        }
+    }
+
+    /// The error for an invalid AST, as found in [`Options::strict`] mode
+    pub fn invalid_ast(reason: &'static str, node: &mdast::Node) -> Self {
+        Self::InvalidAst {
+            reason,
+            position: node.position().cloned(),
         }
     }
+
+    /// The error at the end of the chain of [`ToMinimadError::WhileEmitting`]
+    pub fn root_cause(&self) -> &Self {
+        match self {
+            Self::WhileEmitting { source, .. } => source.root_cause(),
+            other => other,
+        }
+    }
+
+    /// Position in the source of the node that caused the error
+    ///
+    /// This is the innermost known position: if the failing node has no position, the one of the
+    /// nearest ancestor is returned.
+    pub fn position(&self) -> Option<&Position> {
+        match self {
+            Self::WhileEmitting {
+                position, source, ..
+            } => source.position().or(position.as_ref()),
+            Self::UnsupportedNode { position, .. }
+            | Self::UnsupportedChildNode { position, .. }
+            | Self::InvalidAst { position, .. }
+            | Self::TooDeep { position, .. }
+            | Self::ListTooMuchNested { position }
+            | Self::MultilineTableCell { position }
+            | Self::InvalidLineTypeInTableCell { position }
+            | Self::MultilineComposite { position } => position.as_ref(),
+        }
+    }
+
+    /// Types of the nodes that were being emitted when the error occurred, from the outermost
+    pub fn path(&self) -> Vec<NodeKind> {
+        let mut path = vec![];
+        let mut error = self;
+        while let Self::WhileEmitting { node, source, .. } = error {
+            path.push(*node);
+            error = source;
+        }
+        path
+    }
 }
-trait WhileEmitting {
+/// Chaining of the errors with the nodes that were being emitted
+///
+/// Each node wraps the errors of its children in a [`ToMinimadError::WhileEmitting`], so that
+/// [`ToMinimadError::path`] leads to the failing node. Custom emitters that recurse on the
+/// children of a node should do the same:
+/// ```
+/// # use mdast2minimad::{mdast, ToMinimadError, WhileEmitting};
+/// fn emit(node: &mdast::Node) -> Result<(), ToMinimadError> {
+///     match node {
+///         mdast::Node::Root(root) => {
+///             for child in &root.children {
+///                 emit(child).while_emitting(node)?;
+///             }
+///             Ok(())
+///         }
+///         other => Err(ToMinimadError::unsupported_node(other)),
+///     }
+/// }
+/// let ast = markdown::to_mdast("# Title", &Default::default()).unwrap();
+/// let error = emit(&ast).unwrap_err();
+/// assert_eq!(error.path(), [mdast2minimad::NodeKind::Root]);
+/// ```
+pub trait WhileEmitting {
+    /// Record that the error happened while emitting `parent`
     fn while_emitting(self, parent: &mdast::Node) -> Self;
 }
 impl WhileEmitting for ToMinimadError {
     fn while_emitting(self, parent: &mdast::Node) -> Self {
         Self::WhileEmitting {
-            node: type_of(parent),
+            node: NodeKind::of(parent),
+            position: parent.position().cloned(),
             source: Box::new(self),
         }
     }
@@ -57,16 +226,413 @@ impl<T> WhileEmitting for Result<T, ToMinimadError> {
     }
 }
 
+#[derive(Clone, Debug, Display, Error, From)]
+/// Error while converting a markdown source into a `minimad` text
+pub enum ConvertError {
+    #[display("Error while parsing the markdown: {message}")]
+    Parse { message: markdown::message::Message },
+    #[display("Error while converting the AST")]
+    Convert { source: ToMinimadError },
+}
+
+/// Parse a markdown source and convert it to a minimad Text
+///
+/// The source is parsed with [`md_parse_options`]. As the AST does not outlive the call, the text
+/// is returned as an [`OwnedText`].
+pub fn convert(source: &str) -> Result<OwnedText, ConvertError> {
+    Converter::default().convert_str(source)
+}
+
 /// Convert the markdown AST to a minimad Text
-pub fn to_minimad<'a>(ast: &'a mdast::Node) -> Result<minimad::Text<'a>, ToMinimadError> {
-    let mut emitter = Emitter::new(Options::default());
+///
+/// The strings that are not in the AST (e.g. the numbers of the list items) are stored in
+/// `interner`, that can be shared between many conversions.
+pub fn to_minimad<'a>(
+    ast: &'a mdast::Node,
+    interner: &'a Interner,
+) -> Result<minimad::Text<'a>, ToMinimadError> {
+    to_minimad_with_options(ast, Options::default(), interner)
+}
+
+/// Convert the markdown AST to a minimad Text, with the given options
+pub fn to_minimad_with_options<'a>(
+    ast: &'a mdast::Node,
+    options: Options,
+    interner: &'a Interner,
+) -> Result<minimad::Text<'a>, ToMinimadError> {
+    let mut emitter = Emitter::new(options, interner);
     emitter.node(ast)?;
     Ok(emitter.finish())
 }
 
+/// Convert phrasing content (e.g. a paragraph) to a single minimad Composite
+///
+/// A root or a paragraph are converted as their content, any other node as inline content. The
+/// content must fit in a single line.
+pub fn to_composite<'a>(
+    ast: &'a mdast::Node,
+    interner: &'a Interner,
+) -> Result<Composite<'a>, ToMinimadError> {
+    to_composite_with_options(ast, Options::default(), interner)
+}
+
+/// Convert phrasing content to a single minimad Composite, with the given options
+///
+/// See [`to_composite`].
+pub fn to_composite_with_options<'a>(
+    ast: &'a mdast::Node,
+    options: Options,
+    interner: &'a Interner,
+) -> Result<Composite<'a>, ToMinimadError> {
+    let children = match ast {
+        mdast::Node::Root(mdast::Root {
+            children,
+            position: _,
+        }) => match &children[..] {
+            [child] => {
+                return to_composite_with_options(child, options, interner).while_emitting(ast)
+            }
+            children => children,
+        },
+        mdast::Node::Paragraph(mdast::Paragraph {
+            children,
+            position: _,
+        }) => &children[..],
+        other => std::slice::from_ref(other),
+    };
+    let mut emitter = Emitter::new(options, interner);
+    emitter
+        .phrasing(CompositeStyle::Paragraph, false, |this| {
+            for child in children {
+                this.node(child)?;
+            }
+            Ok(())
+        })
+        .while_emitting(ast)?;
+    let Text { mut lines } = emitter.finish();
+    match (lines.pop(), &lines[..]) {
+        (None, _) => Ok(Composite {
+            style: CompositeStyle::Paragraph,
+            compounds: vec![],
+        }),
+        (Some(Line::Normal(composite)), []) => Ok(composite),
+        _ => Err(ToMinimadError::MultilineComposite {
+            position: ast.position().cloned(),
+        }),
+    }
+}
+
+/// Convert the markdown AST lazily, one line at a time
+///
+/// See [`LinesIter`].
+pub fn to_minimad_lines<'a>(
+    ast: &'a mdast::Node,
+    options: Options,
+    interner: &'a Interner,
+) -> LinesIter<'a> {
+    LinesIter::new(ast, options, interner)
+}
+
+/// Convert the markdown AST to a minimad Text that does not borrow from the AST
+pub fn to_minimad_owned(ast: &mdast::Node, options: Options) -> Result<OwnedText, ToMinimadError> {
+    let interner = Interner::new();
+    to_minimad_with_options(ast, options, &interner).map(OwnedText::from)
+}
+
+/// Convert the markdown AST to a minimad Text, skipping the nodes that cannot be converted
+///
+/// Unsupported nodes inside a line are replaced by a placeholder with their type, while blocks are
+/// dropped. Each skipped node is reported in the returned warnings.
+pub fn to_minimad_lossy<'a>(
+    ast: &'a mdast::Node,
+    options: Options,
+    interner: &'a Interner,
+) -> (minimad::Text<'a>, Vec<ConversionWarning>) {
+    let mut warnings = vec![];
+    let text = to_minimad_lossy_into(ast, options, interner, &mut |warning| {
+        warnings.push(warning)
+    });
+    (text, warnings)
+}
+
+/// Convert the markdown AST to a minimad Text, skipping the nodes that cannot be converted and
+/// sending the warnings to `sink`
+///
+/// The warnings are sent as soon as each top level block is converted, so they can be logged
+/// while a long document is still being converted. See [`to_minimad_lossy`].
+pub fn to_minimad_lossy_into<'a>(
+    ast: &'a mdast::Node,
+    options: Options,
+    interner: &'a Interner,
+    sink: &mut dyn WarningSink,
+) -> minimad::Text<'a> {
+    let mut emitter = Emitter::lossy(options, interner);
+    let children = match ast {
+        mdast::Node::Root(mdast::Root {
+            children,
+            position: _,
+        }) => {
+            // root contains flow content
+            emitter.state.flow();
+            // as in `Emitter::root`, the definitions are collected before emitting the document
+            emitter.context = Some(Arc::new(DocumentContext::scan_all(children)));
+            without_frontmatter(children)
+        }
+        other => std::slice::from_ref(other),
+    };
+    for child in children {
+        emitter
+            .node(child)
+            .expect("In lossy mode, errors are converted into warnings");
+        for warning in emitter.report.warnings.drain(..) {
+            sink.warn(warning)
+        }
+    }
+    emitter.finish()
+}
+
+/// Receiver of the warnings of a lossy conversion
+///
+/// Implemented by all closures taking a [`ConversionWarning`].
+pub trait WarningSink {
+    /// Receive a warning
+    fn warn(&mut self, warning: ConversionWarning);
+}
+impl<F> WarningSink for F
+where
+    F: FnMut(ConversionWarning),
+{
+    fn warn(&mut self, warning: ConversionWarning) {
+        self(warning)
+    }
+}
+
+/// Convert the markdown AST to a minimad Text, reporting on the converted content
+pub fn to_minimad_reporting<'a>(
+    ast: &'a mdast::Node,
+    options: Options,
+    interner: &'a Interner,
+) -> Result<(minimad::Text<'a>, ConversionReport<'a>), ToMinimadError> {
+    let mut emitter = Emitter::new(options, interner);
+    emitter.node(ast)?;
+    let mut report = mem::take(&mut emitter.report);
+    let text = emitter.finish();
+    report.stats.count_text(&text);
+    Ok((text, report))
+}
+
+/// Report on a conversion
+#[derive(Debug, Clone, Default)]
+pub struct ConversionReport<'a> {
+    /// Nodes skipped by a lossy conversion
+    pub warnings: Vec<ConversionWarning>,
+    /// Badges found in the text, if [`Options::badges`] is set
+    pub badges: Vec<Badge<'a>>,
+    /// Layout of the tables, in order of appearance
+    pub tables: Vec<TableLayout>,
+    /// Index of the lines that must start a new page, in order
+    ///
+    /// Page breaks are given by [`Directive::PageBreak`], or by form feeds if
+    /// [`Options::form_feed_page_breaks`] is set. They are ignored inside list items and
+    /// footnotes. Transforms in [`Options::post`] that add or remove lines do not update them.
+    /// See [`pages::paginate`].
+    pub page_breaks: Vec<usize>,
+    /// The headings, with their slug, in order
+    ///
+    /// Only the headings outside of lists, quotes and footnotes are listed, but all of them are
+    /// counted to make the slugs unique. As for `page_breaks`, transforms in [`Options::post`]
+    /// that add or remove lines do not update them.
+    pub anchors: Vec<HeadingAnchor>,
+    /// Statistics of the document, e.g. to estimate its reading time
+    pub stats: DocumentStats,
+    /// The content that was not rendered faithfully, in order of emission
+    ///
+    /// These are the choices made by the options, like dropping the URL of the links, not the
+    /// nodes skipped by a lossy conversion, that are in `warnings`.
+    pub losses: Vec<LossyDecision>,
+}
+impl ConversionReport<'_> {
+    /// Move all the content of `other` into `self`
+    fn append(&mut self, other: &mut Self) {
+        self.warnings.append(&mut other.warnings);
+        self.badges.append(&mut other.badges);
+        self.tables.append(&mut other.tables);
+        self.stats.add(&mem::take(&mut other.stats));
+        self.losses.append(&mut other.losses);
+        // the lines of `other` are not in their final position
+        other.page_breaks.clear();
+        other.anchors.clear();
+    }
+}
+
+/// Content not rendered faithfully, see [`ConversionReport::losses`]
+#[derive(Clone, Debug, PartialEq, Eq, Display)]
+#[display("`{node}` node: {loss}")]
+pub struct LossyDecision {
+    /// Type of the node
+    pub node: NodeKind,
+    /// Position of the node in the source
+    pub position: Option<Position>,
+    /// What was lost
+    pub loss: Loss,
+}
+
+/// What was lost in a [`LossyDecision`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Display)]
+#[non_exhaustive]
+pub enum Loss {
+    /// The URL of a link or an image is not shown
+    #[display("URL dropped")]
+    DroppedUrl,
+    /// Raw HTML was dropped, as set by [`Options::html_mode`]
+    #[display("HTML dropped")]
+    DroppedHtml,
+    /// A heading is rendered at a lower depth, as set by [`Options::max_heading_depth`]
+    #[display("heading of depth {depth} rendered at depth {rendered}")]
+    ClampedHeading { depth: u8, rendered: u8 },
+    /// A heading is rendered as a bold paragraph, as set by [`Options::max_heading_depth`]
+    #[display("heading of depth {depth} rendered as a paragraph")]
+    HeadingAsParagraph { depth: u8 },
+    /// Some lines of a code block were cut, as the terminal is [`WidthClass::Narrow`]
+    #[display("code lines truncated")]
+    TruncatedCode,
+}
+
+/// Statistics of a converted document, see [`ConversionReport::stats`]
+///
+/// The counters are collected while converting, so they cover only the emitted content: nodes
+/// dropped by a filter, a directive or a truncation are not counted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DocumentStats {
+    /// Words of the converted text
+    pub words: usize,
+    /// Number of headings of each depth in the source, `headings[0]` counting the `#` ones
+    pub headings: [usize; 6],
+    /// Number of code blocks
+    pub code_blocks: usize,
+    /// Number of links, autolinks included
+    pub links: usize,
+    /// Lines of the converted text
+    ///
+    /// This is an estimate of the rendered lines: the renderer adds the lines of the wrapped
+    /// text, and may add some around the tables and the code blocks.
+    pub lines: usize,
+}
+impl DocumentStats {
+    /// Minutes needed to read the document at the given speed, rounded up
+    ///
+    /// ```
+    /// # use mdast2minimad::DocumentStats;
+    /// let stats = DocumentStats {
+    ///     words: 450,
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(stats.reading_minutes(200), 3);
+    /// ```
+    pub fn reading_minutes(&self, words_per_minute: usize) -> usize {
+        self.words.div_ceil(words_per_minute.max(1))
+    }
+
+    /// Add the counters of `other`
+    fn add(&mut self, other: &Self) {
+        self.words += other.words;
+        for (headings, other) in self.headings.iter_mut().zip(other.headings) {
+            *headings += other;
+        }
+        self.code_blocks += other.code_blocks;
+        self.links += other.links;
+        self.lines += other.lines;
+    }
+
+    /// Count the words and the lines of the converted text
+    fn count_text(&mut self, text: &Text<'_>) {
+        self.lines += text.lines.len();
+        for line in &text.lines {
+            let composites = match line {
+                Line::Normal(composite) => std::slice::from_ref(composite),
+                Line::TableRow(TableRow { cells }) => &cells[..],
+                Line::TableRule(_) | Line::HorizontalRule | Line::CodeFence(_) => &[],
+            };
+            for composite in composites {
+                // a word can be split between compounds with different styles
+                let mut in_word = false;
+                for ch in composite.compounds.iter().flat_map(|c| c.src.chars()) {
+                    if ch.is_whitespace() {
+                        in_word = false;
+                    } else if !in_word {
+                        in_word = true;
+                        self.words += 1;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// A heading that can be jumped to, see [`ConversionReport::anchors`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeadingAnchor {
+    /// The GitHub style slug of the heading, unique in the document
+    pub slug: String,
+    /// Depth of the heading in the source
+    pub depth: u8,
+    /// Index of the first line of the heading
+    pub line: usize,
+}
+
+#[derive(Clone, Debug, Display)]
+#[display("Skipped `{node}` node: {error}")]
+/// A node skipped by [`to_minimad_lossy`]
+pub struct ConversionWarning {
+    /// Type of the skipped node
+    pub node: NodeKind,
+    /// Position of the skipped node in the source
+    pub position: Option<Position>,
+    /// Why the node was skipped
+    pub error: ToMinimadError,
+}
+
+/// The frontmatter of a document
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Frontmatter<'a> {
+    /// A `+++` delimited TOML frontmatter
+    Toml(&'a str),
+    /// A `---` delimited YAML frontmatter
+    Yaml(&'a str),
+}
+
+/// Extract the frontmatter of a document
+///
+/// Only the first node of the document is a frontmatter, that is not rendered by the conversion.
+/// `Toml` and `Yaml` nodes found elsewhere are rendered as code blocks.
+pub fn frontmatter(ast: &mdast::Node) -> Option<Frontmatter<'_>> {
+    match ast.children()?.first()? {
+        mdast::Node::Toml(mdast::Toml { value, .. }) => Some(Frontmatter::Toml(value)),
+        mdast::Node::Yaml(mdast::Yaml { value, .. }) => Some(Frontmatter::Yaml(value)),
+        _ => None,
+    }
+}
+
 /// Return the [`markdown::ParseOptions`] containing all the constructs supported by the conversion
 ///
 /// If the ast is generated by [`markdown::to_mdast`] using these then [`to_minimad`] should never fail.
+///
+/// The enabled constructs are the CommonMark ones, math, and with the `gfm` feature (on by
+/// default) the GitHub Flavored Markdown ones: tables, strikethrough, task lists, footnotes and
+/// literal autolinks. The constructs the emitter cannot render are disabled:
+/// - raw HTML, that fails the conversion unless [`Options::html_mode`] is set;
+/// - link and image references with their definitions;
+/// - frontmatter, that can be enabled to read it with [`frontmatter`];
+/// - MDX.
+///
+/// ```
+/// # use mdast2minimad::{md_parse_options, to_minimad, Interner};
+/// let interner = Interner::new();
+/// let source = "| Done | Task |\n|---|---|\n| yes | ~~write~~ the docs[^1] |\n\n[^1]: www.example.com";
+/// let ast = markdown::to_mdast(source, &md_parse_options()).unwrap();
+/// assert!(to_minimad(&ast, &interner).is_ok());
+/// # assert_eq!(md_parse_options().constructs.gfm_table, cfg!(feature = "gfm"));
+/// ```
 pub const fn md_parse_options() -> markdown::ParseOptions {
     markdown::ParseOptions {
         constructs: markdown::Constructs {
@@ -80,24 +646,24 @@ pub const fn md_parse_options() -> markdown::ParseOptions {
             code_text: true,
             definition: false,
             frontmatter: false,
-            gfm_autolink_literal: true,
-            gfm_footnote_definition: false,
-            gfm_label_start_footnote: false,
-            gfm_strikethrough: true,
-            gfm_table: true,
-            gfm_task_list_item: false,
+            gfm_autolink_literal: cfg!(feature = "gfm"),
+            gfm_footnote_definition: cfg!(feature = "gfm"),
+            gfm_label_start_footnote: cfg!(feature = "gfm"),
+            gfm_strikethrough: cfg!(feature = "gfm"),
+            gfm_table: cfg!(feature = "gfm"),
+            gfm_task_list_item: cfg!(feature = "gfm"),
             hard_break_escape: true,
             hard_break_trailing: true,
             heading_atx: true,
             heading_setext: true,
             html_flow: false,
             html_text: false,
-            label_start_image: false,
+            label_start_image: true,
             label_start_link: true,
             label_end: true,
             list_item: true,
-            math_flow: false,
-            math_text: false,
+            math_flow: true,
+            math_text: true,
             mdx_esm: false,
             mdx_expression_flow: false,
             mdx_expression_text: false,
@@ -106,155 +672,1148 @@ pub const fn md_parse_options() -> markdown::ParseOptions {
             thematic_break: true,
         },
         gfm_strikethrough_single_tilde: false,
-        math_text_single_dollar: false,
+        math_text_single_dollar: true,
         mdx_expression_parse: None,
         mdx_esm_parse: None,
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 /// Options for the conversion
+///
+/// The options do not borrow anything: callbacks are stored as [`Handler`]s.
+///
+/// With the `serde` feature the options can be loaded from a configuration file, e.g. a
+/// `[markdown]` section of a TOML file. The missing fields keep their default value, and the
+/// callbacks (`directives`, `post`, `on_unsupported` and `filter`) are never serialized: they
+/// must be set by the application.
+///
+/// New options are added often, so the struct is non exhaustive: build it with
+/// [`Options::builder`], or change the fields of [`Options::default`].
+/// ```
+/// # use mdast2minimad::{Options, WidthClass};
+/// let options = Options::builder()
+///     .width_class(WidthClass::Wide)
+///     .number_headings(true)
+///     .build();
+/// # assert!(options.number_headings);
+/// ```
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(default))]
+#[non_exhaustive]
 pub struct Options {
     /// If each header need spacing after
     pub header_spacing: [bool; 6],
+    /// Empty lines around the other blocks
+    pub spacing: Spacing,
+    /// How to style the headings of each level
+    pub heading_style: [Styling; 6],
+    /// Shift of the depth of the headings, e.g. `2` renders `#` headings as `###`
+    ///
+    /// The shifted depth is at least 1, and deeper headings are handled as set by
+    /// `max_heading_depth`. Spacing and styling follow the depth in the source.
+    pub heading_offset: i8,
+    /// Maximum depth of the headings, and how to render the deeper ones
+    ///
+    /// The depth is capped at [`minimad::MAX_HEADER_DEPTH`].
+    pub max_heading_depth: (u8, DeepHeading),
+    /// If the headings are prefixed with their section number, e.g. `1.2`
+    ///
+    /// The shallowest heading of the document is numbered `1.`. Headings skipping a level are
+    /// numbered `0` at the missing level.
+    pub number_headings: bool,
     /// How to style the links
     pub links_style: Styling,
+    /// How to style the autolinks (`<https://example.com>`), if differently from the links
+    pub autolink_style: Option<Styling>,
+    /// How to style the links with the given URL schemes (e.g. `mailto`), over the links style
+    ///
+    /// The scheme `#` matches the links internal to the document.
+    pub scheme_styles: Arc<[(Cow<'static, str>, Styling)]>,
+    /// How to render the link targets
+    ///
+    /// If `None`, it depends on the [`WidthClass`]: [`LinkMode::Appendix`] for narrow terminals,
+    /// [`LinkMode::TextOnly`] for normal ones and [`LinkMode::PARENTHESIZED`] for wide ones.
+    pub link_mode: Option<LinkMode>,
+    /// If the link text should be wrapped in OSC 8 escape sequences, making it clickable
+    ///
+    /// Only terminals that support OSC 8 hide the sequences, others will show them as garbage.
+    #[cfg(feature = "osc8")]
+    pub hyperlinks: bool,
+    /// Where to take the text of the images from, in order of preference
+    ///
+    /// The first source that gives a non empty text is used, falling back to
+    /// [`IMAGE_PLACEHOLDER`].
+    pub image_text: Arc<[ImageText]>,
+    /// How to handle raw HTML
+    pub html_mode: HtmlMode,
+    /// If form feeds (`\f`) in the text are page breaks
+    ///
+    /// See [`ConversionReport::page_breaks`].
+    pub form_feed_page_breaks: bool,
+    /// If set, the HTML comments starting with a prefix are directives for the emitter
+    ///
+    /// The other comments, and the directives the handler does not know, follow `html_mode`.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub directives: Option<Directives>,
+    /// If math blocks should be labelled with a `math` line
+    pub math_label: bool,
+    /// If code blocks should be surrounded by code fences, preserving the language
+    pub code_fences: bool,
+    /// How code blocks inside quotes are rendered
+    pub quoted_code: QuotedCode,
+    /// If inline badges (`[[key: value]]`) should be recognized
+    pub badges: bool,
+    /// If inline footnotes (`^[text]`) should be recognized
+    ///
+    /// The text of the footnote must not contain markup.
+    pub inline_footnotes: bool,
+    /// Width of the target terminal
+    pub width_class: WidthClass,
+    /// How to render the soft breaks (single newlines) inside paragraphs
+    pub soft_break: SoftBreak,
+    /// If set, keyboard shortcuts (`<kbd>` HTML tags and `Ctrl+X` patterns) are rendered as code
+    pub kbd: Option<KbdStyle>,
+    /// If consecutive compounds with the same style should be merged into one
+    ///
+    /// The merged text is stored in the interner. This makes the output smaller and faster to lay
+    /// out, at the cost of a copy of the text.
+    pub merge_compounds: bool,
+    /// Spaces added before each line, e.g. to nest the document inside a panel
+    ///
+    /// List items are indented by raising their depth, the other lines with a text prefix. Tables
+    /// and rules cannot be indented. List items that would be nested deeper than `minimad` can
+    /// represent follow `indent_policy`, but are clamped instead of failing.
+    pub indent: u8,
+    /// Transforms applied in order to the converted text
+    ///
+    /// See the [`transform`] module.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub post: Vec<PostTransform>,
+    /// What to do when the indentation exceeds what `minimad` can represent
+    pub indent_policy: IndentPolicy,
+    /// Prefix of the lines continuing a list item after its first line
+    ///
+    /// It is added to the paragraphs, headings, code and quotes inside the items, once for each
+    /// level of nesting. Nested lists are indented by their depth instead.
+    pub list_continuation: Cow<'static, str>,
+    /// Width of the list bullets of the target `termimad` skin, e.g. `1` for the default `•`
+    ///
+    /// If set, the lines continuing a list item are aligned under its text as `termimad` renders
+    /// it, and nested lists start under the text of their parent item, ignoring
+    /// `list_continuation`. Ordered lists are aligned after their number.
+    pub bullet_width: Option<u8>,
+    /// How the items of ordered lists are numbered
+    ///
+    /// `minimad` has no numbered list items: they are paragraphs starting with their number.
+    pub numbering: Numbering,
+    /// If set, the output is truncated after this many bytes of text
+    ///
+    /// The truncated text ends with a `truncation_mark` line, that is not counted.
+    pub max_output_bytes: Option<usize>,
+    /// If set, the output stops after this many top-level blocks, e.g. to show an excerpt
+    ///
+    /// Only the blocks that emit something are counted. The footnotes and the appendix of the
+    /// blocks shown are still emitted.
+    pub max_blocks: Option<usize>,
+    /// If set, the output is truncated after this many lines, `truncation_mark` excluded
+    pub max_lines: Option<usize>,
+    /// Text of the line ending a truncated output, in italic
+    pub truncation_mark: Cow<'static, str>,
+    /// Maximum nesting of the nodes of the AST
+    ///
+    /// The emitter recurses on the nodes, so deeper ASTs could overflow the stack. Deeper nodes
+    /// fail with [`ToMinimadError::TooDeep`].
+    pub max_depth: usize,
+    /// If set, called on the nodes that are not supported, to emit them in a custom way
+    ///
+    /// If the handler returns [`Handled::No`] the node is an error as usual.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub on_unsupported: Option<UnsupportedHandler>,
+    /// If set, only the nodes for which the filter returns `true` are emitted
+    ///
+    /// A dropped node is dropped with all its children.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub filter: Option<NodeFilter>,
+    /// If invalid ASTs (e.g. flow content inside a paragraph) should be an error
+    ///
+    /// By default the emitter tries to make sense of them, but this can hide bugs in tools that
+    /// build the AST programmatically.
+    pub strict: bool,
 }
 impl Options {
+    /// The default options, with the given header spacing and links style
+    ///
+    /// These were the only options of the first versions of the crate.
+    pub fn new(header_spacing: [bool; 6], links_style: Styling) -> Self {
+        Self {
+            header_spacing,
+            links_style,
+            ..Default::default()
+        }
+    }
+
+    /// Start building the options from the default ones
+    pub fn builder() -> OptionsBuilder {
+        OptionsBuilder::default()
+    }
+
     fn header_spacing(&self, depth: u8) -> bool {
-        self.header_spacing
-            .get((depth - 1) as usize)
+        usize::from(depth)
+            .checked_sub(1)
+            .and_then(|idx| self.header_spacing.get(idx))
             .copied()
             .unwrap_or(false) // default to no spacing. Only in invalid ASTs
     }
+    fn link_mode(&self) -> LinkMode {
+        self.link_mode.clone().unwrap_or(match self.width_class {
+            WidthClass::Narrow => LinkMode::Appendix,
+            WidthClass::Normal => LinkMode::TextOnly,
+            WidthClass::Wide => LinkMode::PARENTHESIZED,
+        })
+    }
+    /// Depth of a heading in the output, or `None` if it must be rendered as a paragraph
+    fn heading_depth(&self, depth: u8) -> Option<u8> {
+        let depth = (i16::from(depth) + i16::from(self.heading_offset)).max(1);
+        let (max, deep_heading) = self.max_heading_depth;
+        let max = max.clamp(1, MAX_HEADER_DEPTH as u8);
+        if depth <= i16::from(max) {
+            return Some(depth as u8);
+        }
+        match deep_heading {
+            DeepHeading::Clamp => Some(max),
+            DeepHeading::BoldParagraph => None,
+        }
+    }
+    fn scheme_style(&self, url: &str) -> Option<Styling> {
+        let scheme = if url.starts_with('#') {
+            "#"
+        } else {
+            url.split_once(':')?.0
+        };
+        self.scheme_styles
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(scheme))
+            .map(|(_, styling)| *styling)
+    }
+    fn heading_style(&self, depth: u8) -> Styling {
+        (depth as usize)
+            .checked_sub(1)
+            .and_then(|idx| self.heading_style.get(idx))
+            .copied()
+            .unwrap_or_default() // default to no styling. Only in invalid ASTs
+    }
 }
 impl Default for Options {
     fn default() -> Self {
         Self {
             header_spacing: [true, false, false, false, false, false],
+            spacing: Spacing::default(),
+            heading_style: [Styling::default(); 6],
+            heading_offset: 0,
+            max_heading_depth: (MAX_HEADER_DEPTH as u8, DeepHeading::Clamp),
+            number_headings: false,
             links_style: Styling {
                 bold: None,
                 italic: None,
+                code: None,
                 strikeout: None,
             },
+            autolink_style: None,
+            scheme_styles: Arc::new([]),
+            link_mode: None,
+            #[cfg(feature = "osc8")]
+            hyperlinks: false,
+            image_text: Arc::new([
+                ImageText::Alt,
+                ImageText::Title,
+                ImageText::UrlBasename,
+                ImageText::Placeholder,
+            ]),
+            html_mode: HtmlMode::Error,
+            form_feed_page_breaks: false,
+            directives: None,
+            math_label: false,
+            code_fences: false,
+            quoted_code: QuotedCode::Quote,
+            badges: false,
+            inline_footnotes: false,
+            width_class: WidthClass::Normal,
+            soft_break: SoftBreak::Newline,
+            kbd: None,
+            merge_compounds: false,
+            indent: 0,
+            post: vec![],
+            indent_policy: IndentPolicy::Error,
+            list_continuation: Cow::Borrowed("  "),
+            bullet_width: None,
+            numbering: Numbering::Outline,
+            max_output_bytes: None,
+            max_blocks: None,
+            max_lines: None,
+            truncation_mark: Cow::Borrowed(TRUNCATION_MARK),
+            max_depth: DEFAULT_MAX_DEPTH,
+            on_unsupported: None,
+            filter: None,
+            strict: false,
         }
     }
 }
 
-/// Set up the styling of a node
+/// The optional cargo features this crate was compiled with
 ///
-/// If a value is none, it will follow the style of the surrounding text
-#[derive(Debug, Clone, Copy, Default)]
-pub struct Styling {
-    /// Set if the node is bold
-    pub bold: Option<bool>,
-    /// Set if the node is italic
-    pub italic: Option<bool>,
-    /// Set if the node is strikeout
-    pub strikeout: Option<bool>,
+/// Applications can use it to adapt to the available capabilities, e.g. to offer to open links
+/// only if `osc8` hyperlinks are available:
+/// ```
+/// let hyperlinks = mdast2minimad::features().contains(&"osc8");
+/// # assert_eq!(hyperlinks, cfg!(feature = "osc8"));
+/// ```
+pub const fn features() -> &'static [&'static str] {
+    &[
+        #[cfg(feature = "gfm")]
+        "gfm",
+        #[cfg(feature = "changelog")]
+        "changelog",
+        #[cfg(feature = "commit")]
+        "commit",
+        #[cfg(feature = "help")]
+        "help",
+        #[cfg(feature = "mdbook")]
+        "mdbook",
+        #[cfg(feature = "transliterate")]
+        "transliterate",
+        #[cfg(feature = "osc8")]
+        "osc8",
+        #[cfg(feature = "tracing")]
+        "tracing",
+        #[cfg(feature = "serde")]
+        "serde",
+        #[cfg(feature = "mdast-json")]
+        "mdast-json",
+        #[cfg(feature = "termimad")]
+        "termimad",
+    ]
 }
 
-/// Represent the current content model of the emitter
+/// Default value of [`Options::max_depth`]
+pub const DEFAULT_MAX_DEPTH: usize = 256;
+
+/// Where empty lines are put between the blocks, see [`Options::spacing`]
+///
+/// An empty line separates two blocks if the first one asks for it after itself, or if the
+/// second one forces it before itself.
 #[derive(Debug, Clone, PartialEq, Eq)]
-enum ContentModel<'a> {
-    /// Flow content represent the sections of document.
-    Flow {
-        /// If the last flow element need spacing
-        spacing: bool,
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(default))]
+pub struct Spacing {
+    /// If an empty line is forced before code and math blocks
+    pub before_code: bool,
+    /// If code and math blocks ask for an empty line after them
+    pub after_code: bool,
+    /// If an empty line is forced before lists
+    pub before_lists: bool,
+    /// If paragraphs ask for an empty line after them
+    pub after_paragraphs: bool,
+    /// If the items of loose lists are separated by empty lines
+    ///
+    /// A list is loose if it, or any of its items, is `spread`: in the source the items are
+    /// separated by blank lines. Disable it to render all lists as tight ones.
+    pub loose_items: bool,
+    /// If consecutive empty lines are collapsed into one
+    pub collapse_blank_lines: bool,
+    /// Spacing between two kinds of consecutive blocks, overriding the other fields
+    ///
+    /// The first rule matching the pair of blocks is used. The default is
+    /// [`DEFAULT_SPACING_RULES`].
+    pub rules: Arc<[SpacingRule]>,
+}
+impl Default for Spacing {
+    fn default() -> Self {
+        Self {
+            before_code: false,
+            after_code: true,
+            before_lists: false,
+            after_paragraphs: true,
+            loose_items: true,
+            collapse_blank_lines: false,
+            rules: Arc::from(DEFAULT_SPACING_RULES),
+        }
+    }
+}
+impl Spacing {
+    /// If an empty line separates two consecutive blocks, or `None` if no rule is set for them
+    pub fn between(&self, after: NodeKind, before: NodeKind) -> Option<bool> {
+        self.rules
+            .iter()
+            .find(|rule| rule.after == after && rule.before == before)
+            .map(|rule| rule.empty_line)
+    }
+}
+
+/// Spacing between two kinds of consecutive blocks, see [`Spacing::rules`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SpacingRule {
+    /// Kind of the first block
+    pub after: NodeKind,
+    /// Kind of the second block
+    pub before: NodeKind,
+    /// If an empty line separates them
+    pub empty_line: bool,
+}
+
+/// Default value of [`Spacing::rules`]
+///
+/// Consecutive lists and code blocks are kept apart even if the other fields would join them, so
+/// they do not read as a single one.
+pub const DEFAULT_SPACING_RULES: &[SpacingRule] = &[
+    SpacingRule {
+        after: NodeKind::List,
+        before: NodeKind::List,
+        empty_line: true,
     },
-    /// Phrasing content represent the text in a document, and its markup.
-    Phrasing {
-        /// Style of the lines
-        style: minimad::CompositeStyle,
-        /// Line being built
-        compounds: Vec<minimad::Compound<'a>>,
+    SpacingRule {
+        after: NodeKind::Code,
+        before: NodeKind::Code,
+        empty_line: true,
     },
+];
+
+/// Default text of the line added at the end of a truncated output
+///
+/// See [`Options::truncation_mark`].
+pub const TRUNCATION_MARK: &str = "…";
+
+/// Rendering of keyboard shortcuts
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(default))]
+pub struct KbdStyle {
+    /// Text before the shortcut
+    pub open: Cow<'static, str>,
+    /// Text after the shortcut
+    pub close: Cow<'static, str>,
 }
-impl ContentModel<'_> {
-    fn need_spacing(&self) -> bool {
-        match self {
-            ContentModel::Flow { spacing } => *spacing,
-            ContentModel::Phrasing { .. } => {
-                // Spacing has no sense between phrasing elements.
-                // Should appear only in invalid ASTs
-                // Defaulting to not giving it
-                false
-            }
+impl Default for KbdStyle {
+    fn default() -> Self {
+        Self {
+            open: Cow::Borrowed("["),
+            close: Cow::Borrowed("]"),
         }
     }
+}
+
+/// How soft breaks (single newlines inside a paragraph) are rendered
+///
+/// Hard breaks (a backslash or two spaces at the end of the line) always start a new line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum SoftBreak {
+    /// Start a new line, as `minimad` does
+    #[default]
+    Newline,
+    /// Join the lines with a space, following the CommonMark specification
+    Space,
+    /// Keep the newline inside the text, for renderers that handle it themselves
+    Preserve,
+}
 
-    fn set_spacing(&mut self, new_spacing: bool) {
+/// Class of width of the target terminal, switching the rendering of wide content
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum WidthClass {
+    /// Narrow terminal
+    ///
+    /// Tables are rendered as key/value pairs, link URLs are collected in an appendix at the end
+    /// of the document, and code lines are truncated to [`NARROW_CODE_WIDTH`] characters.
+    Narrow,
+    /// Normal terminal
+    #[default]
+    Normal,
+    /// Wide terminal
+    ///
+    /// Link URLs are shown after the link text.
+    Wide,
+}
+
+/// How the target of the links is rendered
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum LinkMode {
+    /// Only the link text is shown
+    TextOnly,
+    /// The URL is shown after the link text, between `open` and `close`
+    TextWithUrl {
+        open: Cow<'static, str>,
+        close: Cow<'static, str>,
+    },
+    /// Only the URL is shown, in place of the link text
+    UrlOnly,
+    /// The URLs are listed in an appendix at the end of the document, with the link text
+    Appendix,
+    /// The link text is followed by a number, and the URLs are listed with the footnotes
+    ReferenceFootnotes,
+}
+impl LinkMode {
+    /// The URL between parentheses after the link text
+    pub const PARENTHESIZED: Self = Self::TextWithUrl {
+        open: Cow::Borrowed(" ("),
+        close: Cow::Borrowed(")"),
+    };
+}
+
+/// Directives given to the emitter in HTML comments, see [`Options::directives`]
+///
+/// A comment like `<!-- md2term: skip -->`, with `md2term:` as prefix, calls the handler with
+/// `skip`. The handler can push content to the sink, or ask for an effect on the next content.
+///
+/// ```
+/// # use mdast2minimad::{Converter, Directive, Interner};
+/// # use minimad::{Compound, Line};
+/// let interner = Interner::new();
+/// let ast = markdown::to_mdast(
+///     "<!-- md2term: skip -->\nOnly for the web\n\nFor all",
+///     &markdown::ParseOptions::default(),
+/// )
+/// .unwrap();
+/// let converter = Converter::builder()
+///     .directives("md2term:", |directive, _| match directive {
+///         "skip" => Directive::SkipNext,
+///         _ => Directive::Unknown,
+///     })
+///     .build();
+/// let text = converter.convert(&ast, &interner).unwrap();
+/// assert_eq!(text.lines, [Line::new_paragraph(vec![Compound::raw_str("For all")])]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Directives {
+    /// Prefix of the comments that are directives, e.g. `md2term:`
+    pub prefix: &'static str,
+    /// Called with the directive, without the prefix and trimmed
+    pub handler: DirectiveHandler,
+}
+
+/// Handler of the directives, see [`Directives`]
+pub type DirectiveHandler =
+    Handler<dyn for<'a> Fn(&'a str, &mut LineSink<'_, 'a>) -> Directive + Send + Sync>;
+
+/// Effect of a directive
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Directive {
+    /// The directive was executed, pushing its content (if any) to the sink
+    Done,
+    /// Drop the next block
+    SkipNext,
+    /// Start a new page, see [`ConversionReport::page_breaks`]
+    PageBreak,
+    /// The directive is not known: the comment is handled as any other HTML
+    Unknown,
+}
+
+/// A transform of the converted text, see [`Options::post`]
+pub type PostTransform = Handler<dyn TextTransform>;
+
+/// Filter selecting the nodes to emit, see [`Options::filter`]
+pub type NodeFilter = Handler<dyn Fn(&mdast::Node) -> bool + Send + Sync>;
+
+/// Handler of the unsupported nodes, see [`Options::on_unsupported`]
+pub type UnsupportedHandler =
+    Handler<dyn for<'a> Fn(&'a mdast::Node, &mut LineSink<'_, 'a>) -> Handled + Send + Sync>;
+
+/// How code blocks inside quotes are rendered, see [`Options::quoted_code`]
+///
+/// `minimad` lines have a single style, so one of the two must win.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum QuotedCode {
+    /// As quote lines, whose text is styled as inline code
+    #[default]
+    Quote,
+    /// As code lines, outside of the quote
+    Code,
+}
+
+/// How the headings deeper than [`Options::max_heading_depth`] are rendered
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum DeepHeading {
+    /// As headings of the maximum depth
+    #[default]
+    Clamp,
+    /// As bold paragraphs
+    BoldParagraph,
+}
+
+/// How the items of ordered lists are numbered, see [`Options::numbering`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Numbering {
+    /// `1.` at every level
+    Decimal,
+    /// `1.` for the outer lists, then `a.`, then `i.`, repeating for deeper lists
+    #[default]
+    Outline,
+    /// The numbers of all the enclosing items: `1.`, then `1.1.`, then `1.1.1.`
+    Hierarchical,
+}
+impl Numbering {
+    /// The marker of an item, given the numbers of the enclosing ordered items
+    fn marker(self, enclosing: &[usize], number: usize) -> String {
         match self {
-            ContentModel::Flow { spacing } => *spacing = new_spacing,
-            ContentModel::Phrasing { .. } => {
-                // Here the spacing has no sense.
-                // Should appear only in invalid ASTs
+            Numbering::Decimal => format!("{number}."),
+            Numbering::Outline => match enclosing.len() % 3 {
+                1 if number > 0 => format!("{}.", alphabetic(number)),
+                2 if number > 0 => format!("{}.", roman(number)),
+                _ => format!("{number}."),
+            },
+            Numbering::Hierarchical => {
+                let mut marker = String::new();
+                for number in enclosing.iter().chain([&number]) {
+                    marker += &format!("{number}.");
+                }
+                marker
             }
         }
     }
 }
 
-/// Represent the current style of the emitter
+/// Letters numbering, as in `a`, `b`, ..., `z`, `aa`, `ab`...
+fn alphabetic(mut number: usize) -> String {
+    let mut letters = vec![];
+    while number > 0 {
+        number -= 1;
+        letters.push(b'a' + (number % 26) as u8);
+        number /= 26;
+    }
+    letters
+        .iter()
+        .rev()
+        .map(|&letter| char::from(letter))
+        .collect()
+}
+
+/// Lowercase roman numerals
+fn roman(mut number: usize) -> String {
+    const DIGITS: [(usize, &str); 13] = [
+        (1000, "m"),
+        (900, "cm"),
+        (500, "d"),
+        (400, "cd"),
+        (100, "c"),
+        (90, "xc"),
+        (50, "l"),
+        (40, "xl"),
+        (10, "x"),
+        (9, "ix"),
+        (5, "v"),
+        (4, "iv"),
+        (1, "i"),
+    ];
+    let mut numeral = String::new();
+    for (value, digit) in DIGITS {
+        while number >= value {
+            numeral += digit;
+            number -= value;
+        }
+    }
+    numeral
+}
+
+/// What to do when the indentation exceeds what `minimad` can represent
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum IndentPolicy {
+    /// Fail the conversion
+    #[default]
+    Error,
+    /// Keep the maximum indentation
+    Clamp,
+    /// Continue the indentation with spaces at the start of the text
+    TextPrefix,
+}
+
+/// Maximum width of a code line in [`WidthClass::Narrow`] mode
+pub const NARROW_CODE_WIDTH: usize = 40;
+
+/// A source for the text of an image
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ImageText {
+    /// The alternative text
+    Alt,
+    /// The title
+    Title,
+    /// The last segment of the URL, e.g. `cat.png`
+    UrlBasename,
+    /// The [`IMAGE_PLACEHOLDER`]
+    Placeholder,
+}
+
+/// Text of the images that have no other text
+pub const IMAGE_PLACEHOLDER: &str = "image";
+
+/// How raw HTML nodes are converted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum HtmlMode {
+    /// Fail the conversion on any HTML node
+    #[default]
+    Error,
+    /// Silently drop all HTML, comments included
+    Strip,
+    /// Render the raw HTML as code
+    Code,
+    /// Translate a small set of tags (`<b>`, `<i>`, `<code>`, `<s>`, `<br>`) into the equivalent
+    /// minimad styles, dropping everything else
+    Translate,
+}
+
+/// Set up the styling of a node
+///
+/// If a value is none, it will follow the style of the surrounding text
+#[derive(Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Styling {
+    /// Set if the node is bold
+    pub bold: Option<bool>,
+    /// Set if the node is italic
+    pub italic: Option<bool>,
+    /// Set if the node is rendered as inline code
+    pub code: Option<bool>,
+    /// Set if the node is strikeout
+    pub strikeout: Option<bool>,
+}
+impl Styling {
+    /// Apply the styling over the style of the surrounding text
+    fn apply(&self, style: Style) -> Style {
+        Style {
+            bold: self.bold.unwrap_or(style.bold),
+            italic: self.italic.unwrap_or(style.italic),
+            code: self.code.unwrap_or(style.code),
+            strikeout: self.strikeout.unwrap_or(style.strikeout),
+        }
+    }
+}
+
+/// Represent the current style of the emitter
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 struct Style {
     bold: bool,
     italic: bool,
+    code: bool,
     strikeout: bool,
 }
-impl Default for Style {
-    fn default() -> Self {
-        Self {
-            bold: false,
-            italic: false,
-            strikeout: false,
-        }
-    }
-}
 
 /// Minimad code emitter
 struct Emitter<'a> {
-    /// Lines already emitted
-    lines: Vec<minimad::Line<'a>>,
-    /// Current content model
-    model: Option<ContentModel<'a>>,
+    /// Lines already emitted, and current content model
+    state: ContentState<'a>,
     /// Current style of the emitter
     style: Style,
+    /// HTML tags opened in `HtmlMode::Translate`, with the style to restore when they are closed
+    html_tags: Vec<(&'static str, Style)>,
     /// Conversion options
     options: Options,
+    /// If unsupported nodes should be skipped instead of failing
+    lossy: bool,
+    /// Report on the conversion
+    report: ConversionReport<'a>,
+    /// Links to list in the appendix, with their text
+    link_appendix: Vec<(Vec<Compound<'a>>, &'a str)>,
+    /// Footnotes to list at the end of the document
+    footnotes: Footnotes<'a>,
+    /// Definitions of the document, if the whole document is being emitted
+    context: Option<Arc<DocumentContext<'a>>>,
+    /// Storage for the synthesized strings
+    interner: &'a Interner,
+    /// Number of lines whose bytes are counted in `bytes`
+    counted_lines: usize,
+    /// Bytes of text in the counted lines
+    bytes: usize,
+    /// Number of compounds of the open line whose bytes are counted in `open_bytes`
+    counted_compounds: usize,
+    /// Bytes of text in the counted compounds of the open line
+    open_bytes: usize,
+    /// If the output exceeded `Options::max_output_bytes`, `max_blocks` or `max_lines`
+    truncated: bool,
+    /// If some content was dropped because the output was truncated
+    cut: bool,
+    /// Number of top-level blocks that emitted something
+    blocks: usize,
+    /// Nesting of the top-level blocks, set when the first one is emitted
+    ///
+    /// The children of the root can be emitted through the root or one by one.
+    top_depth: Option<usize>,
+    /// Number of lines already taken out of `state.lines`, e.g. by a `LinesIter`
+    taken_lines: usize,
+    /// If the next block must be dropped, as asked by a directive
+    skip_next: bool,
+    /// Number of nodes being emitted, checked against `Options::max_depth`
+    depth: usize,
+    /// Numbers of the enclosing items of ordered lists
+    numbering: Vec<usize>,
+    /// Numbers of the last headings at each depth, if `Options::number_headings` is set
+    heading_numbers: Vec<usize>,
+    /// Times each slug was given to a heading
+    slugs: HashMap<String, usize>,
+    /// If set, MDX expressions are emitted as template placeholders, collected here
+    placeholders: Option<Vec<Placeholder>>,
+    /// Kind of the last block that emitted something
+    last_block: Option<NodeKind>,
+    /// Spacing before the block being emitted set by `Spacing::rules`, if any
+    spacing_rule: Option<bool>,
+    /// Why the AST being emitted is invalid, if `Options::strict` is set
+    invalid: Option<&'static str>,
 }
 
 // --- Emitter API ---
 
 impl<'a> Emitter<'a> {
-    /// Create a new, empty emitter
-    fn new(options: Options) -> Self {
+    /// Create a new, empty emitter storing the synthesized strings in the given interner
+    fn new(options: Options, interner: &'a Interner) -> Self {
         Self {
-            lines: vec![],
-            model: None,
+            state: ContentState::default(),
             style: Style::default(),
+            html_tags: vec![],
             options,
+            lossy: false,
+            report: ConversionReport::default(),
+            link_appendix: vec![],
+            footnotes: Footnotes::default(),
+            context: None,
+            interner,
+            counted_lines: 0,
+            bytes: 0,
+            counted_compounds: 0,
+            open_bytes: 0,
+            truncated: false,
+            cut: false,
+            blocks: 0,
+            top_depth: None,
+            taken_lines: 0,
+            skip_next: false,
+            depth: 0,
+            numbering: vec![],
+            heading_numbers: vec![],
+            slugs: HashMap::new(),
+            placeholders: None,
+            last_block: None,
+            spacing_rule: None,
+            invalid: None,
+        }
+    }
+
+    /// Create a new, empty emitter that skips unsupported nodes
+    fn lossy(options: Options, interner: &'a Interner) -> Self {
+        Self {
+            lossy: true,
+            ..Self::new(options, interner)
         }
     }
 
     /// Complete the emission
     fn finish(mut self) -> minimad::Text<'a> {
+        self.complete();
+        self.post_process();
+        let mut truncated = self.cut;
+        if let Some(max) = self.options.max_lines {
+            if self.state.lines.len() > max {
+                self.state.lines.truncate(max);
+                truncated = true;
+            }
+        }
+        if let Some(max) = self.options.max_output_bytes {
+            truncated |= truncate_lines(&mut self.state.lines, max);
+        }
+        if truncated {
+            self.state
+                .lines
+                .push(Line::new_paragraph(vec![Compound::raw_str(
+                    self.str(self.options.truncation_mark.clone()),
+                )
+                .italic()]));
+        }
+        minimad::Text {
+            lines: self.state.lines,
+        }
+    }
+
+    /// Apply the post-processing set in the options to the emitted lines
+    fn post_process(&mut self) {
+        if self.options.merge_compounds {
+            merge_compounds(&mut self.state.lines, self.interner);
+        }
+        if self.options.indent > 0 {
+            self.indent();
+        }
+        if self.options.spacing.collapse_blank_lines {
+            let mut text = minimad::Text {
+                lines: mem::take(&mut self.state.lines),
+            };
+            LimitBlankRuns(1).apply(&mut text, self.interner);
+            self.state.lines = text.lines;
+        }
+        if self.options.post.is_empty() {
+            return;
+        }
+        let mut text = minimad::Text {
+            lines: mem::take(&mut self.state.lines),
+        };
+        for transform in &self.options.post {
+            transform.apply(&mut text, self.interner);
+        }
+        self.state.lines = text.lines;
+    }
+
+    /// Indent the emitted lines as set by `Options::indent`
+    fn indent(&mut self) {
+        let indent = self.options.indent;
+        let prefix = self.str(" ".repeat(indent.into()).into());
+        for line in &mut self.state.lines {
+            let Line::Normal(Composite { style, compounds }) = line else {
+                continue;
+            };
+            match style {
+                CompositeStyle::ListItem(depth) => match depth.checked_add(indent) {
+                    Some(new_depth) => *depth = new_depth,
+                    None if self.options.indent_policy == IndentPolicy::TextPrefix => {
+                        // the bullet is rendered by `minimad` only before the indentation
+                        let prefix = " ".repeat(usize::from(*depth) + usize::from(indent)) + "• ";
+                        *style = CompositeStyle::Paragraph;
+                        compounds.insert(0, Compound::raw_str(self.interner.intern(prefix)))
+                    }
+                    None => *depth = u8::MAX,
+                },
+                // blank lines are left alone
+                _ if compounds
+                    .iter()
+                    .all(|compound| compound.src.trim().is_empty()) => {}
+                CompositeStyle::Paragraph
+                | CompositeStyle::Header(_)
+                | CompositeStyle::Code
+                | CompositeStyle::Quote => compounds.insert(0, Compound::raw_str(prefix)),
+            }
+        }
+    }
+
+    /// Put an empty line before the next block, if something was emitted before it
+    fn force_spacing(&mut self) {
+        if self.state.lines.is_empty() || self.spacing_rule == Some(false) {
+            return;
+        }
+        if let Some(model @ ContentModel::Flow { .. }) = &mut self.state.model {
+            model.set_spacing(true)
+        }
+    }
+
+    /// Apply the spacing rule between the last block and the one starting
+    fn block_spacing(&mut self, kind: NodeKind) {
+        self.spacing_rule = self
+            .last_block
+            .and_then(|last| self.options.spacing.between(last, kind));
+        match self.spacing_rule {
+            Some(true) => self.force_spacing(),
+            Some(false) => {
+                if let Some(model @ ContentModel::Flow { .. }) = &mut self.state.model {
+                    model.set_spacing(false)
+                }
+            }
+            None => (),
+        }
+    }
+
+    /// Start a new page at the next line
+    fn page_break(&mut self) {
+        if !self.state.open_line().is_empty() {
+            self.state.newline();
+        }
+        self.report.page_breaks.push(self.state.lines.len());
+    }
+
+    /// Emit the open line and the sections at the end of the document
+    fn complete(&mut self) {
         // emit last text if a line is still open
-        if let Some(ContentModel::Phrasing { style, compounds }) = self.model {
-            self.lines
-                .push(Line::Normal(Composite { style, compounds }))
+        self.state.flush();
+        if !self.footnotes.is_empty() {
+            self.footnotes_section();
+        }
+        if !self.link_appendix.is_empty() {
+            self.appendix();
         }
-        minimad::Text { lines: self.lines }
+    }
+
+    /// Check if the text emitted up to now fills `Options::max_blocks` or `max_lines`, or exceeds
+    /// `max_output_bytes`
+    fn over_budget(&mut self) -> bool {
+        if self
+            .options
+            .max_blocks
+            .is_some_and(|max| self.blocks >= max)
+            || self
+                .options
+                .max_lines
+                .is_some_and(|max| self.taken_lines + self.state.lines.len() >= max)
+        {
+            return true;
+        }
+        let Some(max) = self.options.max_output_bytes else {
+            return false;
+        };
+        let emitted = &self.state.lines[self.counted_lines..];
+        let open = self.state.open_line();
+        if !emitted.is_empty() || open.len() < self.counted_compounds {
+            // the counted compounds were emitted or dropped: the open line is a new one
+            self.counted_compounds = 0;
+            self.open_bytes = 0;
+        }
+        self.bytes += emitted.iter().map(line_bytes).sum::<usize>();
+        self.counted_lines = self.state.lines.len();
+        self.open_bytes += compounds_bytes(&open[self.counted_compounds..]);
+        self.counted_compounds = open.len();
+        self.bytes + self.open_bytes > max
+    }
+
+    /// Emit the appendix with the links
+    fn appendix(&mut self) {
+        self.state.emptyline();
+        self.state
+            .lines
+            .push(Line::new_paragraph(vec![Compound::raw_str("Links").bold()]));
+        for (mut compounds, url) in mem::take(&mut self.link_appendix) {
+            if !compounds.is_empty() {
+                compounds.push(Compound::raw_str(": "));
+            }
+            compounds.push(Compound::raw_str(url));
+            self.state.lines.push(Line::new_list_item(0, compounds));
+        }
+    }
+
+    /// Emit the section with the footnotes
+    fn footnotes_section(&mut self) {
+        self.state.emptyline();
+        self.state.lines.push(Line::new_paragraph(vec![
+            Compound::raw_str("Footnotes").bold()
+        ]));
+        for (number, mut lines) in mem::take(&mut self.footnotes).into_notes() {
+            let marker = self.footnote_marker(number);
+            // the following paragraphs, lists and code are aligned after the marker
+            let width = marker.src.chars().count() + 1;
+            let prefix = self.str(" ".repeat(width).into());
+            let step = u8::try_from(width).unwrap_or(u8::MAX);
+            match lines.first_mut() {
+                Some(Line::Normal(Composite {
+                    style: CompositeStyle::Paragraph,
+                    compounds,
+                })) => {
+                    compounds.splice(0..0, [marker, Compound::raw_str(" ")]);
+                }
+                _ => lines.insert(0, Line::new_paragraph(vec![marker])),
+            }
+            // there is no node to blame for a nesting too deep, so the lists are clamped
+            let indent_policy = match self.options.indent_policy {
+                IndentPolicy::Error => IndentPolicy::Clamp,
+                policy => policy,
+            };
+            self.continue_lines(&mut lines[1..], prefix, step, indent_policy)
+                .expect("clamped lists cannot fail");
+            self.state.lines.append(&mut lines);
+        }
+    }
+
+    /// The marker of a footnote
+    fn footnote_marker(&self, number: usize) -> Compound<'a> {
+        Compound::raw_str(self.str(Cow::Owned(format!("[{number}]"))))
     }
 
     /// Emit an arbitrary node
     fn node(&mut self, node: &'a mdast::Node) -> Result<(), ToMinimadError> {
+        // once the budget is exceeded, nothing else will be shown
+        if self.truncated {
+            self.cut = true;
+            return Ok(());
+        }
+        if let Some(filter) = &self.options.filter {
+            if !filter(node) {
+                return Ok(());
+            }
+        }
+        // a block starts when no line is open
+        if self.skip_next && !self.state.is_phrasing() {
+            self.skip_next = false;
+            return Ok(());
+        }
+        // the AST could have been found invalid by the parent before emitting this node
+        self.invalid_transitions();
+        let parent_invalid = self.invalid.take();
+        // the spacing between blocks can be set by a rule
+        let block = (!self.state.is_phrasing()).then(|| NodeKind::of(node));
+        let lines = self.state.lines.len();
+        if let Some(kind) = block {
+            self.block_spacing(kind);
+            if kind != NodeKind::Root {
+                self.top_depth.get_or_insert(self.depth);
+            }
+        }
+        #[cfg(feature = "tracing")]
+        let _span = tracing::trace_span!(
+            "node",
+            kind = %NodeKind::of(node),
+            position = ?node.position(),
+            model = self.state.model_name(),
+        )
+        .entered();
         // emit the node
+        let res = if self.depth < self.options.max_depth {
+            self.depth += 1;
+            let res = self.dispatch(node);
+            self.depth -= 1;
+            res
+        } else {
+            Err(ToMinimadError::TooDeep {
+                max_depth: self.options.max_depth,
+                position: node.position().cloned(),
+            })
+        };
+        if let Some(kind) = block {
+            self.spacing_rule = None;
+            if self.state.lines.len() > lines {
+                self.last_block = Some(kind);
+                if self.top_depth == Some(self.depth) {
+                    self.blocks += 1;
+                }
+            }
+        }
+        // give the unsupported nodes to the extension, if any
+        let res = match res {
+            Err(error @ ToMinimadError::UnsupportedNode { .. }) => self.unsupported(node, error),
+            res => res,
+        };
+        self.truncated = self.over_budget();
+        self.invalid_transitions();
+        // the innermost node that found the AST invalid fails
+        let res = match mem::replace(&mut self.invalid, parent_invalid) {
+            Some(reason) => res.and(Err(ToMinimadError::invalid_ast(reason, node))),
+            None => res,
+        };
+        match res {
+            // Errors from the children were already handled by them
+            Err(error) if self.lossy && !matches!(error, ToMinimadError::WhileEmitting { .. }) => {
+                self.skip(node, error);
+                Ok(())
+            }
+            res => res.while_emitting(node),
+        }
+    }
+
+    /// Emit a node with the method for its type
+    fn dispatch(&mut self, node: &'a mdast::Node) -> Result<(), ToMinimadError> {
         match node {
             mdast::Node::Root(root) => self.root(root),
             mdast::Node::Heading(heading) => self.heading(heading),
             mdast::Node::Text(text) => self.text(text),
+            mdast::Node::Break(hard_break) => self.hard_break(hard_break),
             mdast::Node::Paragraph(paragraph) => self.paragraph(paragraph),
+            mdast::Node::Blockquote(blockquote) => self.blockquote(blockquote),
             mdast::Node::Code(code) => self.code(code),
+            mdast::Node::Toml(toml) => self.toml(toml),
+            mdast::Node::Yaml(yaml) => self.yaml(yaml),
             mdast::Node::Strong(strong) => self.strong(strong),
             mdast::Node::Emphasis(emphasis) => self.emphasis(emphasis),
             mdast::Node::InlineCode(inline_code) => self.inline_code(inline_code),
@@ -263,6 +1822,23 @@ impl<'a> Emitter<'a> {
             mdast::Node::List(list) => self.list(list),
             mdast::Node::Table(table) => self.table(table),
             mdast::Node::ThematicBreak(thematic_break) => self.thematic_break(thematic_break),
+            mdast::Node::Html(html) => self.html(html),
+            mdast::Node::InlineMath(inline_math) => self.inline_math(inline_math),
+            mdast::Node::Image(image) => self.image(image),
+            mdast::Node::Math(math) => self.math(math),
+            mdast::Node::FootnoteReference(reference) => self.footnote_reference(reference),
+            mdast::Node::FootnoteDefinition(definition) => self.footnote_definition(definition),
+            mdast::Node::MdxTextExpression(expression) if self.placeholders.is_some() => {
+                self.placeholder(&expression.value);
+                Ok(())
+            }
+            mdast::Node::MdxFlowExpression(expression) if self.placeholders.is_some() => {
+                let spacing = self.options.spacing.after_paragraphs;
+                self.phrasing(CompositeStyle::Paragraph, spacing, |this| {
+                    this.placeholder(&expression.value);
+                    Ok(())
+                })
+            }
             // Nodes that are supported only as child of others
             node @ (mdast::Node::ListItem(_)
             | mdast::Node::TableCell(_)
@@ -270,7 +1846,107 @@ impl<'a> Emitter<'a> {
             // Catch all for unsupported nodes
             other => Err(ToMinimadError::unsupported_node(other)),
         }
-        .while_emitting(node)
+    }
+
+    /// Emit content in a separate emitter, returning the emitted text
+    ///
+    /// Used for content that needs to be processed before being added to the document
+    fn isolated(
+        &mut self,
+        fun: impl FnOnce(&mut Self) -> Result<(), ToMinimadError>,
+    ) -> Result<Text<'a>, ToMinimadError> {
+        let mut emitter = Emitter {
+            lossy: self.lossy,
+            depth: self.depth,
+            numbering: self.numbering.clone(),
+            context: self.context.clone(),
+            // the budget is enforced on the whole document
+            ..Emitter::new(
+                Options {
+                    max_output_bytes: None,
+                    max_blocks: None,
+                    max_lines: None,
+                    // the indentation and the transforms are applied to the whole document
+                    indent: 0,
+                    post: vec![],
+                    ..self.options.clone()
+                },
+                self.interner,
+            )
+        };
+        // footnotes and headings are numbered in the whole document
+        emitter.footnotes = mem::take(&mut self.footnotes);
+        emitter.heading_numbers = mem::take(&mut self.heading_numbers);
+        emitter.slugs = mem::take(&mut self.slugs);
+        emitter.placeholders = self.placeholders.take();
+        emitter.state.spare = mem::take(&mut self.state.spare);
+        let res = fun(&mut emitter);
+        self.state.spare = mem::take(&mut emitter.state.spare);
+        self.report.append(&mut emitter.report);
+        // the appendix and the footnotes belong to the whole document
+        self.link_appendix.append(&mut emitter.link_appendix);
+        self.footnotes = mem::take(&mut emitter.footnotes);
+        self.heading_numbers = mem::take(&mut emitter.heading_numbers);
+        self.slugs = mem::take(&mut emitter.slugs);
+        self.placeholders = emitter.placeholders.take();
+        res.map(|()| emitter.finish())
+    }
+
+    /// Record that the AST is invalid, if the emitter is strict
+    ///
+    /// The emitter will then fail at the end of the current node. Otherwise the emitter carries on
+    /// with a lenient fallback.
+    fn invalid(&mut self, reason: &'static str) {
+        if self.options.strict {
+            self.invalid.get_or_insert(reason);
+        }
+    }
+
+    /// Record that some content of a node was not rendered faithfully
+    fn record_loss(&mut self, node: NodeKind, position: &Option<Position>, loss: Loss) {
+        self.report.losses.push(LossyDecision {
+            node,
+            position: position.clone(),
+            loss,
+        });
+    }
+
+    /// Record the invalid transitions of the content model, if the emitter is strict
+    fn invalid_transitions(&mut self) {
+        if let Some(reason) = self.state.take_invalid() {
+            #[cfg(feature = "tracing")]
+            tracing::trace!(reason, "invalid transition");
+            self.invalid(reason)
+        }
+    }
+
+    /// Emit an unsupported node with [`Options::on_unsupported`], or fail with `error`
+    fn unsupported(
+        &mut self,
+        node: &'a mdast::Node,
+        error: ToMinimadError,
+    ) -> Result<(), ToMinimadError> {
+        let Some(handler) = &self.options.on_unsupported else {
+            return Err(error);
+        };
+        match handler(node, &mut LineSink::new(&mut self.state, self.interner)) {
+            Handled::Yes => Ok(()),
+            Handled::No => Err(error),
+        }
+    }
+
+    /// Skip a node that could not be emitted, leaving a placeholder if inside a line
+    fn skip(&mut self, node: &'a mdast::Node, error: ToMinimadError) {
+        if let Some(ContentModel::Phrasing { compounds, .. }) = &mut self.state.model {
+            compounds.push(Compound::raw_str("[").italic());
+            compounds.push(Compound::raw_str(NodeKind::of(node).as_str()).italic());
+            compounds.push(Compound::raw_str("]").italic());
+        }
+        self.report.warnings.push(ConversionWarning {
+            node: NodeKind::of(node),
+            position: node.position().cloned(),
+            error,
+        })
     }
 }
 
@@ -285,48 +1961,178 @@ impl<'a> Emitter<'a> {
             position: _,
         }: &'a mdast::Root,
     ) -> Result<(), ToMinimadError> {
+        // root contains flow content
+        self.state.flow();
+        // the whole document is known: the definitions are collected before emitting it
+        self.context = Some(Arc::new(DocumentContext::scan_all(children)));
         // root does not limit his content in any way
-        for child in children {
+        for child in without_frontmatter(children) {
             self.node(child)?;
         }
         Ok(())
     }
 
     /// emit a `Heading` node
-    fn heading(
-        &mut self,
-        mdast::Heading {
+    fn heading(&mut self, heading: &'a mdast::Heading) -> Result<(), ToMinimadError> {
+        let mdast::Heading {
             children,
-            position: _,
+            position,
             depth,
-        }: &'a mdast::Heading,
-    ) -> Result<(), ToMinimadError> {
-        // Open a new phrasing session
-        self.phrasing(
-            minimad::CompositeStyle::Header(*depth),
-            self.options.header_spacing(*depth),
-            |this| {
-                // emit the childrens in phrasing mode
-                for child in children {
-                    this.node(child)?;
+        } = heading;
+        if !(1..=6).contains(depth) {
+            self.invalid("heading depth outside of 1..=6");
+        }
+        self.report.stats.headings[usize::from((*depth).clamp(1, 6)) - 1] += 1;
+        let mut styling = self.options.heading_style(*depth);
+        let (style, spacing) = match self.options.heading_depth(*depth) {
+            Some(output_depth) => {
+                let shifted = i16::from(*depth) + i16::from(self.options.heading_offset);
+                if i16::from(output_depth) < shifted {
+                    let loss = Loss::ClampedHeading {
+                        depth: *depth,
+                        rendered: output_depth,
+                    };
+                    self.record_loss(NodeKind::Heading, position, loss);
                 }
-                Ok(())
-            },
-        )
+                (
+                    CompositeStyle::Header(output_depth),
+                    self.options.header_spacing(*depth),
+                )
+            }
+            None => {
+                // degrade into a paragraph
+                let loss = Loss::HeadingAsParagraph { depth: *depth };
+                self.record_loss(NodeKind::Heading, position, loss);
+                styling.bold = Some(true);
+                (CompositeStyle::Paragraph, true)
+            }
+        };
+        let number = self
+            .options
+            .number_headings
+            .then(|| self.heading_number(*depth));
+        let slug =
+            sections::unique_slug(sections::slug(&sections::title(heading)), &mut self.slugs);
+        // Open a new phrasing session
+        self.phrasing(style, spacing, |this| {
+            this.report.anchors.push(HeadingAnchor {
+                slug,
+                depth: *depth,
+                line: this.state.lines.len(),
+            });
+            let new_style = styling.apply(this.style);
+            let old_style = mem::replace(&mut this.style, new_style);
+            if let Some(number) = number {
+                let number = this.str(number.into());
+                let number = this.styled(number);
+                this.state.line().push(number);
+            }
+            // emit the childrens in phrasing mode
+            for child in children {
+                this.node(child)?;
+            }
+            this.style = old_style;
+            Ok(())
+        })
+    }
+
+    /// Advance the section numbers with a heading, and return its number followed by a space
+    fn heading_number(&mut self, depth: u8) -> String {
+        // number from the shallowest heading, if the whole document is known
+        let top = self
+            .context
+            .as_ref()
+            .and_then(|context| context.headings().iter().map(|heading| heading.depth).min())
+            .unwrap_or(1);
+        let level = usize::from(depth.saturating_sub(top));
+        self.heading_numbers.resize(level + 1, 0);
+        self.heading_numbers[level] += 1;
+        match &self.heading_numbers[..] {
+            [number] => format!("{number}. "),
+            numbers => {
+                let numbers: Vec<_> = numbers.iter().map(ToString::to_string).collect();
+                numbers.join(".") + " "
+            }
+        }
     }
 
     /// emit a `Text` node
     fn text(
         &mut self,
-        mdast::Text { value, position: _ }: &'a mdast::Text,
+        mdast::Text { value, position }: &'a mdast::Text,
     ) -> Result<(), ToMinimadError> {
-        self.fmt_text(
-            &value,
-            self.style.bold,
-            self.style.italic,
-            false,
-            self.style.strikeout,
-        );
+        if !self.options.form_feed_page_breaks {
+            self.text_value(value, position);
+            return Ok(());
+        }
+        for (idx, part) in value.split('\u{c}').enumerate() {
+            if idx > 0 {
+                self.page_break();
+            }
+            if !part.is_empty() {
+                self.text_value(part, position);
+            }
+        }
+        Ok(())
+    }
+
+    /// Emit the value of a text node, recognizing inline footnotes
+    fn text_value(&mut self, mut value: &'a str, position: &Option<Position>) {
+        if self.options.inline_footnotes {
+            while let Some((range, note)) = find_inline_footnote(value) {
+                if range.start > 0 {
+                    self.badges_text(&value[..range.start], position);
+                }
+                let number = self
+                    .footnotes
+                    .inline(vec![Line::new_paragraph(vec![Compound::raw_str(note)])]);
+                let marker = self.styled(self.footnote_marker(number).src);
+                self.state.line().push(marker);
+                value = &value[range.end..];
+                if value.is_empty() {
+                    return;
+                }
+            }
+        }
+        self.badges_text(value, position);
+    }
+
+    /// Emit the text of a paragraph, recognizing badges
+    fn badges_text(&mut self, mut value: &'a str, position: &Option<Position>) {
+        if self.options.badges {
+            while let Some((range, key, badge_value)) = find_badge(value) {
+                if range.start > 0 {
+                    self.shortcuts_text(&value[..range.start]);
+                }
+                // Keep only the inner brackets
+                let badge = Compound {
+                    src: &value[range.start + 1..range.end - 1],
+                    bold: true,
+                    italic: self.style.italic,
+                    code: false,
+                    strikeout: self.style.strikeout,
+                };
+                self.state.line().push(badge);
+                self.report.badges.push(Badge {
+                    key,
+                    value: badge_value,
+                    position: position.clone(),
+                });
+                value = &value[range.end..];
+                if value.is_empty() {
+                    return;
+                }
+            }
+        }
+        self.shortcuts_text(value);
+    }
+
+    /// emit a `Break` node
+    fn hard_break(
+        &mut self,
+        mdast::Break { position: _ }: &'a mdast::Break,
+    ) -> Result<(), ToMinimadError> {
+        self.state.newline();
         Ok(())
     }
 
@@ -338,7 +2144,8 @@ impl<'a> Emitter<'a> {
             position: _,
         }: &'a mdast::Paragraph,
     ) -> Result<(), ToMinimadError> {
-        self.phrasing(minimad::CompositeStyle::Paragraph, true, |this| {
+        let spacing = self.options.spacing.after_paragraphs;
+        self.phrasing(minimad::CompositeStyle::Paragraph, spacing, |this| {
             for child in children {
                 this.node(child)?
             }
@@ -351,19 +2158,85 @@ impl<'a> Emitter<'a> {
         &mut self,
         mdast::Code {
             value,
-            position: _,
-            lang: _,
+            position,
+            lang,
             meta: _,
         }: &'a mdast::Code,
     ) -> Result<(), ToMinimadError> {
-        self.phrasing(minimad::CompositeStyle::Code, true, |this| {
+        self.report.stats.code_blocks += 1;
+        self.code_block(value, lang.as_deref(), (NodeKind::Code, position))
+    }
+
+    /// emit a `Toml` node. Only found outside of the frontmatter in malformed documents.
+    fn toml(
+        &mut self,
+        mdast::Toml { value, position }: &'a mdast::Toml,
+    ) -> Result<(), ToMinimadError> {
+        self.code_block(value, Some("toml"), (NodeKind::Toml, position))
+    }
+
+    /// emit a `Yaml` node. Only found outside of the frontmatter in malformed documents.
+    fn yaml(
+        &mut self,
+        mdast::Yaml { value, position }: &'a mdast::Yaml,
+    ) -> Result<(), ToMinimadError> {
+        self.code_block(value, Some("yaml"), (NodeKind::Yaml, position))
+    }
+
+    /// Emit a block of code
+    fn code_block(
+        &mut self,
+        value: &'a str,
+        lang: Option<&'a str>,
+        (node, position): (NodeKind, &Option<Position>),
+    ) -> Result<(), ToMinimadError> {
+        let code_fences = self.options.code_fences;
+        let Spacing {
+            before_code,
+            after_code,
+            ..
+        } = self.options.spacing;
+        if before_code {
+            self.force_spacing();
+        }
+        self.phrasing(minimad::CompositeStyle::Code, after_code, |this| {
+            if code_fences {
+                this.state.lines.push(Line::new_code_fence(
+                    lang.iter().map(|lang| Compound::raw_str(lang)).collect(),
+                ));
+            }
+            if this.options.width_class == WidthClass::Narrow {
+                let mut truncated = false;
+                for (idx, line) in split_lines(value).enumerate() {
+                    if idx > 0 {
+                        this.state.newline();
+                    }
+                    match line.char_indices().nth(NARROW_CODE_WIDTH) {
+                        Some((end, _)) => {
+                            truncated = true;
+                            this.state
+                                .line()
+                                .extend([Compound::raw_str(&line[..end]), Compound::raw_str("…")])
+                        }
+                        None => this.state.line().push(Compound::raw_str(line)),
+                    }
+                }
+                if truncated {
+                    this.record_loss(node, position, Loss::TruncatedCode);
+                }
+                return Ok(());
+            }
             this.fmt_text(
-                &value, false, false,
+                value, false, false,
                 false, // weird, but this is how minimad set is AST. Following to avoid surprises.
                 false,
             );
             Ok(())
-        })
+        })?;
+        if code_fences {
+            self.state.lines.push(Line::empty_code_fence());
+        }
+        Ok(())
     }
 
     /// emit a `Strong` node
@@ -404,7 +2277,7 @@ impl<'a> Emitter<'a> {
         mdast::InlineCode { value, position: _ }: &'a mdast::InlineCode,
     ) -> Result<(), ToMinimadError> {
         self.fmt_text(
-            &value,
+            value,
             self.style.bold,
             self.style.italic,
             true,
@@ -434,25 +2307,85 @@ impl<'a> Emitter<'a> {
         &mut self,
         mdast::Link {
             children,
-            position: _,
-            url: _,
+            position,
+            url,
             title: _,
         }: &'a mdast::Link,
     ) -> Result<(), ToMinimadError> {
-        let new_style = Style {
-            bold: self.options.links_style.bold.unwrap_or(self.style.bold),
-            italic: self.options.links_style.italic.unwrap_or(self.style.italic),
-            strikeout: self
-                .options
-                .links_style
-                .strikeout
-                .unwrap_or(self.style.strikeout),
+        self.report.stats.links += 1;
+        // the text of autolinks (`<https://example.com>`) is already the URL
+        let link_mode = if is_autolink(children, url) {
+            LinkMode::TextOnly
+        } else {
+            self.options.link_mode()
+        };
+        let links_style = match self.options.autolink_style {
+            Some(autolink_style) if is_autolink(children, url) => autolink_style,
+            _ => self.options.links_style,
         };
+        let start = (self.state.lines.len(), self.state.line().len());
+        let mut new_style = links_style.apply(self.style);
+        if let Some(scheme_style) = self.options.scheme_style(url) {
+            new_style = scheme_style.apply(new_style);
+        }
         let old_style = mem::replace(&mut self.style, new_style);
-        for child in children {
-            self.node(child)?;
+        if link_mode == LinkMode::UrlOnly {
+            let url = self.styled(url);
+            self.state.line().push(url);
+        } else {
+            for child in children {
+                self.node(child)?;
+            }
         }
         self.style = old_style;
+        #[cfg(feature = "osc8")]
+        if self.options.hyperlinks && self.state.lines.len() == start.0 {
+            // make the text clickable, if it did not span multiple lines
+            let open = self.str(format!("\x1b]8;;{url}\x1b\\").into());
+            self.state.line().insert(start.1, Compound::raw_str(open));
+            self.state.line().push(Compound::raw_str("\x1b]8;;\x1b\\"));
+        }
+        #[cfg(feature = "osc8")]
+        let clickable = self.options.hyperlinks;
+        #[cfg(not(feature = "osc8"))]
+        let clickable = false;
+        if link_mode == LinkMode::TextOnly && !is_autolink(children, url) && !clickable {
+            self.record_loss(NodeKind::Link, position, Loss::DroppedUrl);
+        }
+        match link_mode {
+            LinkMode::TextOnly | LinkMode::UrlOnly => (),
+            LinkMode::Appendix => {
+                // Copy the link text, if it did not span multiple lines
+                let text = if self.state.lines.len() == start.0 {
+                    self.state.line()[start.1..].to_vec()
+                } else {
+                    vec![]
+                };
+                self.link_appendix.push((text, url));
+            }
+            LinkMode::TextWithUrl { open, close } => {
+                let style = self.style;
+                let (open, close) = (self.str(open), self.str(close));
+                self.state.line().extend(
+                    [
+                        Compound::raw_str(open),
+                        Compound::raw_str(url),
+                        Compound::raw_str(close),
+                    ]
+                    .map(|compound| Compound {
+                        bold: style.bold,
+                        italic: style.italic,
+                        strikeout: style.strikeout,
+                        ..compound
+                    }),
+                );
+            }
+            LinkMode::ReferenceFootnotes => {
+                let number = self.footnotes.link(url);
+                let marker = self.styled(self.footnote_marker(number).src);
+                self.state.line().push(marker);
+            }
+        }
         Ok(())
     }
 
@@ -461,87 +2394,217 @@ impl<'a> Emitter<'a> {
         &mut self,
         mdast::List {
             children,
-            position: _,
+            position,
             ordered,
-            start: _,
-            spread: _,
+            start,
+            spread,
         }: &'a mdast::List,
     ) -> Result<(), ToMinimadError> {
-        if *ordered {
-            return Err(ToMinimadError::UnsupportedNumberedLists);
+        if self.options.spacing.before_lists {
+            self.force_spacing();
         }
+        let loose = self.options.spacing.loose_items
+            && (*spread
+                || children
+                    .iter()
+                    .any(|item| matches!(item, mdast::Node::ListItem(item) if item.spread)));
         self.phrasing(CompositeStyle::Paragraph, true, |this| {
-            for item in children {
+            for (idx, item) in children.iter().enumerate() {
                 let item @ mdast::Node::ListItem(mdast::ListItem {
                     children,
                     position: _,
                     spread: _,
-                    checked: _,
+                    checked,
                 }) = item
                 else {
                     return Err(ToMinimadError::unsupported_child_node(item));
                 };
+                if loose && idx > 0 {
+                    this.state.emptyline();
+                }
+                // ordered lists can start from any number, as `4.`
+                let number = start.map_or(1, |start| start as usize) + idx;
                 // render the child as a text
-                let mut emitter = Emitter::new(this.options);
-                for child in children {
-                    emitter.node(child).while_emitting(item)?;
+                if *ordered {
+                    this.numbering.push(number);
+                }
+                let item = this
+                    .isolated(|emitter| {
+                        for child in children {
+                            emitter.node(child)?;
+                        }
+                        Ok(())
+                    })
+                    .while_emitting(item);
+                if *ordered {
+                    this.numbering.pop();
                 }
-                let mut item = emitter.finish();
-                // Transform the first line in a list item if is a paragraph,
-                // else leave a empty list item (minimad do not support item of different type)
+                let mut item = item?;
+                // The first line of the item is marked if it is a paragraph, else an empty item is
+                // added before it (minimad do not support item of different type)
+                let first_style = if *ordered {
+                    CompositeStyle::Paragraph
+                } else {
+                    CompositeStyle::ListItem(0)
+                };
                 if let Some(Line::Normal(Composite {
                     style: style @ CompositeStyle::Paragraph,
                     compounds: _,
                 })) = item.lines.first_mut()
                 {
-                    *style = CompositeStyle::ListItem(0)
+                    *style = first_style
                 } else {
                     item.lines.insert(
                         0,
                         Line::Normal(Composite {
-                            style: CompositeStyle::ListItem(0),
+                            style: first_style,
                             compounds: vec![],
                         }),
                     )
                 }
+                // Mark the items of task lists
+                if let (Some(checked), Some(Line::Normal(Composite { compounds, .. }))) =
+                    (checked, item.lines.first_mut())
+                {
+                    compounds.insert(0, Compound::raw_str(if *checked { "[x] " } else { "[ ] " }))
+                }
+                // Width of the bullet as rendered by termimad, if known
+                let mut marker_width = this.options.bullet_width.map(usize::from);
+                // Number the items of ordered lists
+                if let (true, Some(Line::Normal(Composite { compounds, .. }))) =
+                    (*ordered, item.lines.first_mut())
+                {
+                    let marker = this.options.numbering.marker(&this.numbering, number);
+                    marker_width = marker_width.map(|_| marker.chars().count());
+                    compounds.insert(0, Compound::raw_str(this.str((marker + " ").into())))
+                }
                 // For each child successive line, if its a list, indent it a bit more, else add some indentation as text
-                for line in item.lines.iter_mut().skip(1) {
-                    match line {
-                        Line::Normal(Composite { style, compounds }) => match style {
-                            CompositeStyle::ListItem(indent) => {
-                                *indent = indent
-                                    .checked_add(1)
-                                    .ok_or(ToMinimadError::ListTooMuchNested)?
-                            }
+                let (prefix, step) = match marker_width {
+                    // the lines are aligned under the text after the marker and its space
+                    Some(width) => (
+                        this.str(" ".repeat(width + 1).into()),
+                        u8::try_from(width + 1).unwrap_or(u8::MAX),
+                    ),
+                    None => (this.str(this.options.list_continuation.clone()), 1),
+                };
+                this.continue_lines(
+                    &mut item.lines[1..],
+                    prefix,
+                    step,
+                    this.options.indent_policy,
+                )
+                .map_err(|()| ToMinimadError::ListTooMuchNested {
+                    position: position.clone(),
+                })?;
+                // Append all the lines from the item
+                this.state.lines.append(&mut item.lines)
+            }
+            Ok(())
+        })
+    }
 
-                            CompositeStyle::Paragraph
-                            | CompositeStyle::Header(_)
-                            | CompositeStyle::Code
-                            | CompositeStyle::Quote => compounds.insert(
-                                0,
-                                Compound {
-                                    src: "  ",
-                                    bold: false,
-                                    italic: false,
-                                    code: false,
-                                    strikeout: false,
-                                },
-                            ),
+    /// Indent the lines continuing a list item or a footnote
+    ///
+    /// Lists are nested `step` levels deeper, the other lines are prefixed with `prefix`. Fails if
+    /// a list cannot be nested deeper and the policy is [`IndentPolicy::Error`].
+    fn continue_lines(
+        &self,
+        lines: &mut [Line<'a>],
+        prefix: &'a str,
+        step: u8,
+        indent_policy: IndentPolicy,
+    ) -> Result<(), ()> {
+        for line in lines {
+            match line {
+                Line::Normal(Composite { style, compounds }) => match style {
+                    CompositeStyle::ListItem(indent) => match indent.checked_add(step) {
+                        Some(new_indent) => *indent = new_indent,
+                        None => match indent_policy {
+                            IndentPolicy::Error => return Err(()),
+                            IndentPolicy::Clamp => *indent = u8::MAX,
+                            IndentPolicy::TextPrefix => {
+                                // continue the indentation with spaces, as the other lines
+                                let prefix = prefix.repeat(u8::MAX as usize + 1) + "• ";
+                                *style = CompositeStyle::Paragraph;
+                                compounds.insert(0, Compound::raw_str(self.str(prefix.into())))
+                            }
                         },
-                        Line::HorizontalRule => (),
-                        Line::TableRow(_) | Line::TableRule(_) => {
-                            unimplemented!("Tables are not implemented")
-                        }
-                        Line::CodeFence(_) => {
-                            unimplemented!("Code fences are still not implemented")
-                        }
+                    },
+
+                    CompositeStyle::Paragraph
+                    | CompositeStyle::Header(_)
+                    | CompositeStyle::Code
+                    | CompositeStyle::Quote => compounds.insert(0, Compound::raw_str(prefix)),
+                },
+                // Fences only mark the code, and carry the language as content.
+                // Rules and tables cannot be indented by `minimad`, and are kept whole
+                Line::HorizontalRule
+                | Line::CodeFence(_)
+                | Line::TableRow(_)
+                | Line::TableRule(_) => (),
+            }
+        }
+        Ok(())
+    }
+
+    /// emit a `Blockquote` node
+    ///
+    /// `minimad` quotes are a style of the lines: the content is rendered apart, and then its
+    /// lines are restyled.
+    fn blockquote(
+        &mut self,
+        mdast::Blockquote {
+            children,
+            position: _,
+        }: &'a mdast::Blockquote,
+    ) -> Result<(), ToMinimadError> {
+        let mut quote = self.isolated(|emitter| {
+            for child in children {
+                emitter.node(child)?;
+            }
+            Ok(())
+        })?;
+        // the quote ends with its content
+        while quote.lines.last().is_some_and(|line| {
+            matches!(line, Line::Normal(Composite { style: CompositeStyle::Paragraph, compounds })
+                if compounds.iter().all(|compound| compound.src.trim().is_empty()))
+        }) {
+            quote.lines.pop();
+        }
+        for line in &mut quote.lines {
+            // tables, rules and fences cannot be quoted
+            let Line::Normal(Composite { style, compounds }) = line else {
+                continue;
+            };
+            match style {
+                CompositeStyle::Paragraph | CompositeStyle::Quote => (),
+                CompositeStyle::Header(_) => {
+                    for compound in compounds.iter_mut() {
+                        compound.bold = true
                     }
                 }
-                // Append all the lines from the item
-                this.lines.append(&mut item.lines)
+                CompositeStyle::ListItem(depth) => {
+                    let bullet = " ".repeat(usize::from(*depth)) + "• ";
+                    compounds.insert(0, Compound::raw_str(self.str(bullet.into())))
+                }
+                CompositeStyle::Code => match self.options.quoted_code {
+                    QuotedCode::Quote => {
+                        for compound in compounds.iter_mut() {
+                            compound.code = true
+                        }
+                    }
+                    QuotedCode::Code => continue,
+                },
             }
+            *style = CompositeStyle::Quote
+        }
+        self.phrasing(CompositeStyle::Quote, true, |this| {
+            this.state.lines.append(&mut quote.lines);
             Ok(())
-        })
+        })?;
+        // the open line of the quote was left empty
+        self.state.lines.pop();
+        Ok(())
     }
 
     /// emit a `Table` node
@@ -549,7 +2612,7 @@ impl<'a> Emitter<'a> {
         &mut self,
         mdast::Table {
             children,
-            position: _,
+            position,
             align,
         }: &'a mdast::Table,
     ) -> Result<(), ToMinimadError> {
@@ -560,9 +2623,19 @@ impl<'a> Emitter<'a> {
             Ok(child)
         });
 
+        let Some(header) = rows.next() else {
+            // nothing to emit. Only in invalid ASTs
+            self.invalid("table without rows");
+            return Ok(());
+        };
+        if self.options.width_class == WidthClass::Narrow {
+            return self.table_key_values(iter::once(header).chain(rows));
+        }
+
+        let mut column_widths = vec![];
         self.phrasing(CompositeStyle::Paragraph, true, |this| {
-            this.table_row(rows.next().unwrap()?)?;
-            this.lines.push(Line::TableRule(TableRule {
+            this.table_row(header?, &mut column_widths)?;
+            this.state.lines.push(Line::TableRule(TableRule {
                 cells: align
                     .iter()
                     .map(|align| match align {
@@ -574,64 +2647,393 @@ impl<'a> Emitter<'a> {
                     .collect(),
             }));
             for row in rows {
-                this.table_row(row?)?;
+                this.table_row(row?, &mut column_widths)?;
             }
             Ok(())
-        })
+        })?;
+        self.report.tables.push(TableLayout {
+            column_widths,
+            position: position.clone(),
+        });
+        Ok(())
+    }
+
+    /// Emit a table as a list of key/value lines for each row
+    fn table_key_values(
+        &mut self,
+        mut rows: impl Iterator<Item = Result<&'a mdast::TableRow, ToMinimadError>>,
+    ) -> Result<(), ToMinimadError> {
+        let Some(header) = rows.next() else {
+            return Ok(());
+        };
+        let header = self.table_cells(header?)?;
+        for row in rows {
+            let cells = self.table_cells(row?)?;
+            self.phrasing(CompositeStyle::Paragraph, true, |this| {
+                for (idx, (key, value)) in header.iter().zip(cells).enumerate() {
+                    if idx > 0 {
+                        this.state.newline();
+                    }
+                    let line = this.state.line();
+                    line.extend(key.compounds.iter().cloned().map(Compound::bold));
+                    line.push(Compound::raw_str(": "));
+                    line.extend(value.compounds);
+                }
+                Ok(())
+            })?;
+        }
+        Ok(())
     }
 
-    /// Emit a `TableRow` node
+    /// Emit a `TableRow` node, updating the width of the columns
     fn table_row(
+        &mut self,
+        row: &'a mdast::TableRow,
+        column_widths: &mut Vec<usize>,
+    ) -> Result<(), ToMinimadError> {
+        let cells = self.table_cells(row)?;
+        if column_widths.len() < cells.len() {
+            column_widths.resize(cells.len(), 0);
+        }
+        for (width, cell) in column_widths.iter_mut().zip(&cells) {
+            let cell_width = cell
+                .compounds
+                .iter()
+                .map(|compound| compound.src.chars().count())
+                .sum();
+            *width = (*width).max(cell_width);
+        }
+        self.state.lines.push(Line::TableRow(TableRow { cells }));
+        Ok(())
+    }
+
+    /// Convert the cells of a `TableRow` node
+    fn table_cells(
         &mut self,
         mdast::TableRow {
             children,
             position: _,
         }: &'a mdast::TableRow,
-    ) -> Result<(), ToMinimadError> {
-        let cells = children.iter().map(|child| {
-            let mdast::Node::TableCell(mdast::TableCell {
-                children,
-                position: _,
-            }) = child
-            else {
+    ) -> Result<Vec<Composite<'a>>, ToMinimadError> {
+        let mut cells = Vec::with_capacity(children.len());
+        for child in children {
+            let mdast::Node::TableCell(mdast::TableCell { children, position }) = child else {
                 return Err(ToMinimadError::unsupported_child_node(child));
             };
             // render the cell as text
-            let mut emitter = Emitter::new(self.options);
-            for child in children {
-                emitter.node(child).while_emitting(child)?;
-            }
-            let Text { mut lines } = emitter.finish();
+            let Text { mut lines } = self
+                .isolated(|emitter| {
+                    for child in children {
+                        emitter.node(child)?;
+                    }
+                    Ok(())
+                })
+                .while_emitting(child)?;
             // fail if the cell has multiple lines
             if lines.len() > 1 {
-                return Err(ToMinimadError::MultilineTableCell);
+                return Err(ToMinimadError::MultilineTableCell {
+                    position: position.clone(),
+                });
             }
             // return the single line
-            let line = match lines.pop() {
+            cells.push(match lines.pop() {
                 Some(Line::Normal(composite)) => composite,
-                Some(_) => return Err(ToMinimadError::InvalidLineTypeInTableCell),
+                Some(_) => {
+                    return Err(ToMinimadError::InvalidLineTypeInTableCell {
+                        position: position.clone(),
+                    })
+                }
                 None => Composite {
                     style: CompositeStyle::Paragraph,
                     compounds: vec![],
                 },
-            };
-            Ok(line)
-        });
+            });
+        }
+        Ok(cells)
+    }
+
+    /// Emit a `Html` node
+    fn html(
+        &mut self,
+        mdast::Html { value, position }: &'a mdast::Html,
+    ) -> Result<(), ToMinimadError> {
+        if let Some(Directives { prefix, handler }) = &self.options.directives {
+            if let Some(directive) = comment_directive(value, prefix) {
+                match handler(
+                    directive,
+                    &mut LineSink::new(&mut self.state, self.interner),
+                ) {
+                    Directive::Done => return Ok(()),
+                    Directive::SkipNext => {
+                        self.skip_next = true;
+                        return Ok(());
+                    }
+                    Directive::PageBreak => {
+                        self.page_break();
+                        return Ok(());
+                    }
+                    Directive::Unknown => (),
+                }
+            }
+        }
+        // html is inline if we are in the middle of a line
+        let inline = self.state.is_phrasing();
+        if let (true, Some(kbd)) = (inline, self.options.kbd.clone()) {
+            if value.eq_ignore_ascii_case("<kbd>") {
+                self.html_tags.push(("kbd", self.style));
+                self.style.code = true;
+                let open = self.styled(self.str(kbd.open));
+                self.state.line().push(open);
+                return Ok(());
+            }
+            if value.eq_ignore_ascii_case("</kbd>") {
+                if self.close_html_tag("kbd") {
+                    let mut close = self.styled(self.str(kbd.close));
+                    close.code = true;
+                    self.state.line().push(close);
+                }
+                return Ok(());
+            }
+        }
+        match self.options.html_mode {
+            HtmlMode::Error => Err(ToMinimadError::UnsupportedNode {
+                node: NodeKind::Html,
+                position: position.clone(),
+            }),
+            HtmlMode::Strip => {
+                self.record_loss(NodeKind::Html, position, Loss::DroppedHtml);
+                Ok(())
+            }
+            HtmlMode::Code if inline => {
+                self.fmt_text(
+                    value,
+                    self.style.bold,
+                    self.style.italic,
+                    true,
+                    self.style.strikeout,
+                );
+                Ok(())
+            }
+            HtmlMode::Code => self.phrasing(CompositeStyle::Code, true, |this| {
+                this.fmt_text(value, false, false, false, false);
+                Ok(())
+            }),
+            HtmlMode::Translate => {
+                if !self.html_tag(value) {
+                    self.record_loss(NodeKind::Html, position, Loss::DroppedHtml);
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Translate a single HTML tag into a style change, returning `false` if it was dropped
+    fn html_tag(&mut self, tag: &str) -> bool {
+        let Some(tag) = tag
+            .trim()
+            .strip_prefix('<')
+            .and_then(|tag| tag.strip_suffix('>'))
+        else {
+            // Not a single tag (a block of HTML, or a comment). Dropping it.
+            return false;
+        };
+        let (closing, tag) = match tag.strip_prefix('/') {
+            Some(tag) => (true, tag),
+            None => (false, tag.trim_end_matches('/')),
+        };
+        let name = tag
+            .split(|ch: char| ch.is_ascii_whitespace())
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        let name = match &*name {
+            "b" | "strong" => "b",
+            "i" | "em" => "i",
+            "code" | "kbd" | "samp" | "tt" => "code",
+            "s" | "del" | "strike" => "s",
+            "br" => {
+                self.state.newline();
+                return true;
+            }
+            // Unknown tags are dropped
+            _ => return false,
+        };
+        if closing {
+            self.close_html_tag(name);
+            return true;
+        }
+        self.html_tags.push((name, self.style));
+        match name {
+            "b" => self.style.bold = true,
+            "i" => self.style.italic = true,
+            "code" => self.style.code = true,
+            "s" => self.style.strikeout = true,
+            _ => unreachable!(),
+        }
+        true
+    }
+
+    /// emit a `FootnoteReference` node
+    fn footnote_reference(
+        &mut self,
+        mdast::FootnoteReference {
+            identifier,
+            label,
+            position: _,
+        }: &'a mdast::FootnoteReference,
+    ) -> Result<(), ToMinimadError> {
+        // references to footnotes that are never defined are left as they are written
+        if self
+            .context
+            .as_ref()
+            .is_some_and(|context| context.footnote(identifier).is_none())
+        {
+            let reference = format!("[^{}]", label.as_deref().unwrap_or(identifier));
+            let reference = self.styled(self.str(reference.into()));
+            self.state.line().push(reference);
+            return Ok(());
+        }
+        let number = self.footnotes.reference(identifier);
+        let marker = self.styled(self.footnote_marker(number).src);
+        self.state.line().push(marker);
+        Ok(())
+    }
+
+    /// emit a `FootnoteDefinition` node
+    ///
+    /// The content is kept for the footnotes section, at the end of the document.
+    fn footnote_definition(
+        &mut self,
+        mdast::FootnoteDefinition {
+            children,
+            position: _,
+            identifier,
+            label: _,
+        }: &'a mdast::FootnoteDefinition,
+    ) -> Result<(), ToMinimadError> {
+        let text = self.isolated(|emitter| {
+            for child in children {
+                emitter.node(child)?;
+            }
+            Ok(())
+        })?;
+        self.footnotes.define(identifier, text.lines);
+        Ok(())
+    }
 
-        self.lines.push(Line::TableRow(TableRow {
-            cells: cells.collect::<Result<_, _>>()?,
-        }));
+    /// emit a `InlineMath` node
+    fn inline_math(
+        &mut self,
+        mdast::InlineMath { value, position: _ }: &'a mdast::InlineMath,
+    ) -> Result<(), ToMinimadError> {
+        // Terminals cannot render LaTeX: showing the source as code
+        self.fmt_text(
+            value,
+            self.style.bold,
+            self.style.italic,
+            true,
+            self.style.strikeout,
+        );
+        Ok(())
+    }
 
+    /// emit a `Image` node
+    fn image(
+        &mut self,
+        mdast::Image {
+            position,
+            alt,
+            url,
+            title,
+        }: &'a mdast::Image,
+    ) -> Result<(), ToMinimadError> {
+        self.record_loss(NodeKind::Image, position, Loss::DroppedUrl);
+        // Terminals cannot show images: showing a text in their place
+        let text = self
+            .options
+            .image_text
+            .iter()
+            .find_map(|source| {
+                match source {
+                    ImageText::Alt => Some(&**alt),
+                    ImageText::Title => title.as_deref(),
+                    ImageText::UrlBasename => url_basename(url),
+                    ImageText::Placeholder => Some(IMAGE_PLACEHOLDER),
+                }
+                .filter(|text| !text.trim().is_empty())
+            })
+            .unwrap_or(IMAGE_PLACEHOLDER);
+        let image = self.styled(text).italic();
+        self.state.line().push(image);
         Ok(())
     }
 
+    /// Emit the placeholder of a MDX expression, see [`to_mdx_template`]
+    fn placeholder(&mut self, expression: &str) {
+        let placeholders = self.placeholders.get_or_insert_with(Vec::new);
+        let expression = expression.trim();
+        let name = mdx::placeholder_name(expression, placeholders);
+        let marker = mdx::marker(&name);
+        if !placeholders
+            .iter()
+            .any(|placeholder| placeholder.name == name)
+        {
+            placeholders.push(Placeholder {
+                name,
+                expression: expression.to_owned(),
+            });
+        }
+        let marker = self.styled(self.str(Cow::Owned(marker)));
+        self.state.line().push(marker);
+    }
+
+    /// emit a `Math` node
+    fn math(
+        &mut self,
+        mdast::Math {
+            value,
+            position: _,
+            meta: _,
+        }: &'a mdast::Math,
+    ) -> Result<(), ToMinimadError> {
+        // Terminals cannot render LaTeX: showing the source as a code block
+        let Spacing {
+            before_code,
+            after_code,
+            ..
+        } = self.options.spacing;
+        if before_code {
+            self.force_spacing();
+        }
+        self.phrasing(minimad::CompositeStyle::Code, after_code, |this| {
+            if this.options.math_label {
+                this.fmt_text("math", false, true, false, false);
+                this.state.newline();
+            }
+            this.fmt_text(value, false, false, false, false);
+            Ok(())
+        })
+    }
+
+    /// Close a HTML tag, returning `false` if it was not open
+    ///
+    /// The style from before the matching opening tag is restored, closing any unclosed tag inside it
+    fn close_html_tag(&mut self, name: &str) -> bool {
+        let Some(idx) = self.html_tags.iter().rposition(|(open, _)| *open == name) else {
+            return false;
+        };
+        self.style = self.html_tags[idx].1;
+        self.html_tags.truncate(idx);
+        true
+    }
+
     /// Emit a `ThematicBreak` node
     fn thematic_break(
         &mut self,
         mdast::ThematicBreak { position: _ }: &'a mdast::ThematicBreak,
     ) -> Result<(), ToMinimadError> {
         self.phrasing(CompositeStyle::Paragraph, false, |this| {
-            this.lines.push(Line::HorizontalRule);
+            this.state.lines.push(Line::HorizontalRule);
             Ok(())
         })
     }
@@ -647,93 +3049,119 @@ impl<'a> Emitter<'a> {
         spacing: bool,
         fun: impl FnOnce(&mut Self) -> R,
     ) -> R {
-        // remove the old model, and if it was undefined set it to flow
-        let mut old_model = self
-            .model
-            .take()
-            .unwrap_or(ContentModel::Flow { spacing: false });
-        if let ContentModel::Phrasing { style, compounds } = &mut old_model {
-            // the old model was in the middle of a line. This can happen only in invalid ASTs, as the nodes that use `Phrasing`
-            // as inner content should be called only in `Flow` model. Anyway, let's not mix up the content emitting that line
-            self.lines.push(minimad::Line::Normal(Composite {
-                style: *style,
-                compounds: mem::take(compounds),
-            }));
-        }
-        // put a spacing newline between flows element
-        if old_model.need_spacing() {
-            self.emptyline()
-        }
-        // set the new model as phrasing with the given style
-        self.model = Some(ContentModel::Phrasing {
-            style,
-            compounds: vec![],
-        });
+        let outer = self.state.open_phrasing(style);
         // call the inner function
         let res = fun(self);
-        // put the old model back, settign spacing
-        old_model.set_spacing(spacing);
-        let residuals = mem::replace(&mut self.model, Some(old_model));
-        // if some compounds remains, emit them
-        if let Some(ContentModel::Phrasing { style, compounds }) = residuals {
-            self.lines
-                .push(minimad::Line::Normal(Composite { style, compounds }));
+        // close any HTML tag left open inside the phrasing content
+        if let Some((_, style)) = self.html_tags.first() {
+            self.style = *style;
+            self.html_tags.clear();
         }
+        self.state.close_phrasing(outer, spacing);
         // return the function result
         res
     }
 
-    /// Return the current line
-    fn line(&mut self) -> &mut Vec<Compound<'a>> {
-        match &mut self.model {
-            Some(ContentModel::Phrasing {
-                style: _,
-                compounds,
-            }) => compounds,
-            model @ (None | Some(ContentModel::Flow { .. })) => {
-                // If not phrasing (only in invalid ASTs), or if the model is undefined, assume we begin a new paragraph
-                *model = Some(ContentModel::Phrasing {
-                    style: minimad::CompositeStyle::Paragraph,
-                    compounds: vec![],
+    /// Emit the text of a paragraph, recognizing keyboard shortcuts
+    fn shortcuts_text(&mut self, mut value: &'a str) {
+        if let Some(kbd) = self.options.kbd.clone() {
+            let (open, close) = (self.str(kbd.open), self.str(kbd.close));
+            while let Some(range) = find_shortcut(value) {
+                if range.start > 0 {
+                    self.phrasing_text(&value[..range.start]);
+                }
+                let shortcut = [open, &value[range.clone()], close].map(|src| Compound {
+                    code: true,
+                    ..self.styled(src)
                 });
-                let Some(ContentModel::Phrasing {
-                    style: _,
-                    compounds,
-                }) = model
-                else {
-                    unreachable!()
-                };
-                compounds
+                self.state.line().extend(shortcut);
+                value = &value[range.end..];
+                if value.is_empty() {
+                    return;
+                }
             }
         }
+        self.phrasing_text(value)
     }
 
-    /// Start a new line
-    fn newline(&mut self) {
-        match &mut self.model {
-            Some(ContentModel::Phrasing { style, compounds }) => {
-                self.lines.push(minimad::Line::Normal(Composite {
-                    style: *style,
-                    compounds: mem::take(compounds),
-                }))
-            }
-            None | Some(ContentModel::Flow { .. }) => {
-                // In this models a newline has no meaning. The method should only be called when in phrasing contexts.
-                // Anyway ignoring to be lenient on malformed ASTs
+    /// A compound with the current style
+    fn styled(&self, src: &'a str) -> Compound<'a> {
+        Compound {
+            src,
+            bold: self.style.bold,
+            italic: self.style.italic,
+            code: self.style.code,
+            strikeout: self.style.strikeout,
+        }
+    }
+
+    /// Emit the text of a paragraph, with the current style, handling soft breaks
+    fn phrasing_text(&mut self, value: &'a str) {
+        let Style {
+            bold,
+            italic,
+            code,
+            strikeout,
+        } = self.style;
+        match self.options.soft_break {
+            SoftBreak::Newline => self.fmt_text(value, bold, italic, code, strikeout),
+            SoftBreak::Preserve => self.state.line().push(Compound {
+                src: value,
+                bold,
+                italic,
+                code,
+                strikeout,
+            }),
+            SoftBreak::Space => {
+                let style = |src| Compound {
+                    src,
+                    bold,
+                    italic,
+                    code,
+                    strikeout,
+                };
+                let mut lines = split_lines(value).peekable();
+                let mut first = true;
+                while let Some(mut line) = lines.next() {
+                    // spaces around a soft break are not rendered
+                    if !first {
+                        line = line.trim_start();
+                        self.state.line().push(style(" "));
+                    }
+                    if lines.peek().is_some() {
+                        line = line.trim_end();
+                    }
+                    self.state.line().push(style(line));
+                    first = false;
+                }
             }
         }
     }
 
-    /// Emit a empty line
-    fn emptyline(&mut self) {
-        self.lines.push(Line::new_paragraph(vec![]))
+    /// Get a string living as long as the AST, storing it if it was synthesized
+    fn str(&self, value: Cow<'a, str>) -> &'a str {
+        match value {
+            // Fast path: strings from the AST are used as they are
+            Cow::Borrowed(value) => value,
+            Cow::Owned(value) => self.interner.intern(value),
+        }
     }
 
     /// Emit formatted texts
-    fn fmt_text(&mut self, value: &'a str, bold: bool, italic: bool, code: bool, strikeout: bool) {
-        let mut lines = value.split("\r\n").flat_map(|l| l.split('\n'));
+    ///
+    /// The text can be borrowed from the AST or synthesized
+    fn fmt_text(
+        &mut self,
+        value: impl Into<Cow<'a, str>>,
+        bold: bool,
+        italic: bool,
+        code: bool,
+        strikeout: bool,
+    ) {
+        let value = self.str(value.into());
+        let mut lines = split_lines(value);
         if let Some(line) = lines.next() {
-            self.line().push(Compound {
+            self.state.line().push(Compound {
                 src: line,
                 bold,
                 italic,
@@ -742,8 +3170,8 @@ impl<'a> Emitter<'a> {
             });
         }
         for line in lines {
-            self.newline();
-            self.line().push(Compound {
+            self.state.newline();
+            self.state.line().push(Compound {
                 src: line,
                 bold,
                 italic,
@@ -754,44 +3182,236 @@ impl<'a> Emitter<'a> {
     }
 }
 
-/// Find a name for a node
+/// The directive in an HTML comment starting with `prefix`, trimmed
+fn comment_directive<'a>(html: &'a str, prefix: &str) -> Option<&'a str> {
+    let comment = html.trim().strip_prefix("<!--")?.strip_suffix("-->")?;
+    Some(comment.trim().strip_prefix(prefix)?.trim())
+}
+
+/// Merge the consecutive compounds with the same style, storing the merged text in the interner
+fn merge_compounds<'a>(lines: &mut [Line<'a>], interner: &'a Interner) {
+    for line in lines {
+        let cells = match line {
+            Line::Normal(composite) => std::slice::from_mut(composite),
+            Line::TableRow(TableRow { cells }) => &mut cells[..],
+            Line::CodeFence(_) | Line::TableRule(_) | Line::HorizontalRule => &mut [],
+        };
+        for Composite { compounds, .. } in cells {
+            let mut idx = 0;
+            while idx < compounds.len() {
+                let first = &compounds[idx];
+                let run = compounds[idx..]
+                    .iter()
+                    .take_while(|compound| {
+                        (
+                            compound.bold,
+                            compound.italic,
+                            compound.code,
+                            compound.strikeout,
+                        ) == (first.bold, first.italic, first.code, first.strikeout)
+                    })
+                    .count();
+                if run > 1 {
+                    let merged: String = compounds[idx..idx + run]
+                        .iter()
+                        .map(|compound| compound.src)
+                        .collect();
+                    compounds[idx].src = interner.intern(merged);
+                    compounds.drain(idx + 1..idx + run);
+                }
+                idx += 1;
+            }
+        }
+    }
+}
+
+/// Cut the lines so that they contain at most `max` bytes of text
 ///
-/// Used for error messages
-fn type_of(node: &mdast::Node) -> &'static str {
-    match node {
-        mdast::Node::Root(_) => "Root",
-        mdast::Node::Blockquote(_) => "Blockquote",
-        mdast::Node::FootnoteDefinition(_) => "FootnoteDefinition",
-        mdast::Node::MdxJsxFlowElement(_) => "MdxJsxFlowElement",
-        mdast::Node::List(_) => "List",
-        mdast::Node::MdxjsEsm(_) => "MdxjsEsm",
-        mdast::Node::Toml(_) => "Toml",
-        mdast::Node::Yaml(_) => "Yaml",
-        mdast::Node::Break(_) => "Break",
-        mdast::Node::InlineCode(_) => "InlineCode",
-        mdast::Node::InlineMath(_) => "InlineMath",
-        mdast::Node::Delete(_) => "Delete",
-        mdast::Node::Emphasis(_) => "Emphasis",
-        mdast::Node::MdxTextExpression(_) => "MdxTextExpression",
-        mdast::Node::FootnoteReference(_) => "FootnoteReference",
-        mdast::Node::Html(_) => "Html",
-        mdast::Node::Image(_) => "Image",
-        mdast::Node::ImageReference(_) => "ImageReference",
-        mdast::Node::MdxJsxTextElement(_) => "MdxJsxTextElement",
-        mdast::Node::Link(_) => "Link",
-        mdast::Node::LinkReference(_) => "LinkReference",
-        mdast::Node::Strong(_) => "Strong",
-        mdast::Node::Text(_) => "Text",
-        mdast::Node::Code(_) => "Code",
-        mdast::Node::Math(_) => "Math",
-        mdast::Node::MdxFlowExpression(_) => "MdxFlowExpression",
-        mdast::Node::Heading(_) => "Heading",
-        mdast::Node::Table(_) => "Table",
-        mdast::Node::ThematicBreak(_) => "ThematicBreak",
-        mdast::Node::TableRow(_) => "TableRow",
-        mdast::Node::TableCell(_) => "TableCell",
-        mdast::Node::ListItem(_) => "ListItem",
-        mdast::Node::Definition(_) => "Definition",
-        mdast::Node::Paragraph(_) => "Paragraph",
+/// Compounds are cut at a char boundary. Return `true` if some text was removed.
+fn truncate_lines(lines: &mut Vec<Line<'_>>, max: usize) -> bool {
+    let mut remaining = max;
+    for line_idx in 0..lines.len() {
+        let cells = match &mut lines[line_idx] {
+            Line::Normal(composite) | Line::CodeFence(composite) => std::slice::from_mut(composite),
+            Line::TableRow(TableRow { cells }) => &mut cells[..],
+            Line::TableRule(_) | Line::HorizontalRule => &mut [],
+        };
+        for cell_idx in 0..cells.len() {
+            let compounds = &mut cells[cell_idx].compounds;
+            for compound_idx in 0..compounds.len() {
+                let compound = &mut compounds[compound_idx];
+                if compound.src.len() <= remaining {
+                    remaining -= compound.src.len();
+                    continue;
+                }
+                let mut end = remaining;
+                while !compound.src.is_char_boundary(end) {
+                    end -= 1;
+                }
+                compound.src = &compound.src[..end];
+                compounds.truncate(compound_idx + 1);
+                if let Line::TableRow(TableRow { cells }) = &mut lines[line_idx] {
+                    cells.truncate(cell_idx + 1);
+                }
+                lines.truncate(line_idx + 1);
+                return true;
+            }
+        }
+    }
+    false
+}
+
+/// Split a text in lines
+fn split_lines(value: &str) -> impl Iterator<Item = &str> {
+    // `\r\n`, `\n` and the classic Mac `\r` are all line endings
+    let mut rest = Some(value);
+    std::iter::from_fn(move || {
+        let current = rest?;
+        let Some(end) = current.find(['\r', '\n']) else {
+            rest = None;
+            return Some(current);
+        };
+        let next = if current[end..].starts_with("\r\n") {
+            end + 2
+        } else {
+            end + 1
+        };
+        rest = Some(&current[next..]);
+        Some(&current[..end])
+    })
+}
+
+/// Bytes of text in a line
+fn line_bytes(line: &Line<'_>) -> usize {
+    owned::composites(line)
+        .iter()
+        .map(|composite| compounds_bytes(&composite.compounds))
+        .sum()
+}
+
+/// Bytes of text in some compounds
+fn compounds_bytes(compounds: &[Compound<'_>]) -> usize {
+    compounds.iter().map(|compound| compound.src.len()).sum()
+}
+
+/// Modifier keys starting a keyboard shortcut
+const SHORTCUT_MODIFIERS: &[&str] = &[
+    "ctrl", "control", "alt", "shift", "cmd", "command", "meta", "super", "option", "opt", "win",
+];
+
+/// Find the first keyboard shortcut (e.g. `Ctrl+Shift+T`) in a string
+fn find_shortcut(src: &str) -> Option<Range<usize>> {
+    let is_word = |ch: char| ch.is_alphanumeric() || ch == '_';
+    // Match a modifier followed by `+` at the given position, returning the end
+    let modifier = |at: usize| {
+        let word_end = src[at..]
+            .find(|ch: char| !is_word(ch))
+            .map_or(src.len(), |len| at + len);
+        (SHORTCUT_MODIFIERS
+            .iter()
+            .any(|modifier| src[at..word_end].eq_ignore_ascii_case(modifier))
+            && src[word_end..].starts_with('+'))
+        .then_some(word_end + 1)
+    };
+
+    let mut prev = None;
+    for (start, ch) in src.char_indices() {
+        let at_boundary = prev.is_none_or(|prev| !is_word(prev));
+        prev = Some(ch);
+        if !at_boundary {
+            continue;
+        }
+        let Some(mut end) = modifier(start) else {
+            continue;
+        };
+        // other modifiers
+        while let Some(next) = modifier(end) {
+            end = next
+        }
+        // the key
+        let key_len = match src[end..].chars().next() {
+            Some(ch) if is_word(ch) => src[end..]
+                .find(|ch: char| !is_word(ch))
+                .unwrap_or(src.len() - end),
+            Some(ch) if !ch.is_whitespace() && ch != '+' => ch.len_utf8(),
+            _ => continue,
+        };
+        end += key_len;
+        return Some(start..end);
+    }
+    None
+}
+
+/// A inline badge (`[[key: value]]`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Badge<'a> {
+    /// Key of the badge
+    pub key: &'a str,
+    /// Value of the badge
+    pub value: &'a str,
+    /// Position of the text containing the badge
+    pub position: Option<Position>,
+}
+
+/// Layout of an emitted table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableLayout {
+    /// Maximum width of the content of each column, in chars
+    pub column_widths: Vec<usize>,
+    /// Position of the table
+    pub position: Option<Position>,
+}
+
+/// Remove the frontmatter from the children of the root, if any
+fn without_frontmatter(children: &[mdast::Node]) -> &[mdast::Node] {
+    match children {
+        [mdast::Node::Toml(_) | mdast::Node::Yaml(_), rest @ ..] => rest,
+        children => children,
+    }
+}
+
+/// The last segment of the path of an URL
+fn url_basename(url: &str) -> Option<&str> {
+    let path = url.split(['?', '#']).next()?.trim_end_matches('/');
+    path.rsplit('/').next()
+}
+
+/// Check if a link is an autolink, whose text is the URL
+fn is_autolink(children: &[mdast::Node], url: &str) -> bool {
+    let [mdast::Node::Text(mdast::Text { value, .. })] = children else {
+        return false;
+    };
+    value == url || url.strip_prefix("mailto:") == Some(value)
+}
+
+/// Find the first badge in a string, returning its range, key and value
+fn find_badge(src: &str) -> Option<(Range<usize>, &str, &str)> {
+    let mut from = 0;
+    while let Some(start) = src[from..].find("[[").map(|idx| idx + from) {
+        let inner_start = start + 2;
+        let Some(len) = src[inner_start..].find("]]") else {
+            // no badge can be closed after this
+            return None;
+        };
+        let inner = &src[inner_start..inner_start + len];
+        if let Some((key, value)) = inner.split_once(':') {
+            let (key, value) = (key.trim(), value.trim());
+            if !key.is_empty() && !value.is_empty() && !inner.contains(['[', ']', '\n', '\r']) {
+                return Some((start..inner_start + len + 2, key, value));
+            }
+        }
+        from = start + 1;
     }
+    None
+}
+
+/// Find the first inline footnote (`^[text]`) in a string, returning its range and text
+fn find_inline_footnote(src: &str) -> Option<(Range<usize>, &str)> {
+    let start = src.find("^[")?;
+    let inner_start = start + 2;
+    let len = src[inner_start..].find(']')?;
+    Some((
+        start..inner_start + len + 1,
+        &src[inner_start..inner_start + len],
+    ))
 }
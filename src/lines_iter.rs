@@ -0,0 +1,159 @@
+//! Lazy conversion of a document
+
+use std::{collections::VecDeque, mem, slice, sync::Arc};
+
+use minimad::{Compound, Line};
+
+use crate::{
+    line_bytes, mdast, transform::is_blank, truncate_lines, DocumentContext, Emitter, Interner,
+    Options, ToMinimadError, WhileEmitting,
+};
+
+/// Iterator over the lines of a document, converted as they are requested
+///
+/// The top level nodes of the document are converted one at a time, so a pager can start drawing
+/// before the whole document is converted. The lines are the same as the ones of
+/// [`to_minimad_with_options`](crate::to_minimad_with_options). After an error the iterator ends.
+///
+/// The transforms in [`Options::post`](crate::Options::post) need the whole text: if any is set,
+/// the first line is returned only when the whole document is converted.
+///
+/// ```
+/// # use mdast2minimad::{md_parse_options, to_minimad, to_minimad_lines, Interner};
+/// let ast = markdown::to_mdast("# Title\n\nSome text", &md_parse_options()).unwrap();
+/// let interner = Interner::new();
+/// let mut lines = to_minimad_lines(&ast, Default::default(), &interner);
+/// let title = lines.next().unwrap().unwrap();
+/// assert_eq!(title, to_minimad(&ast, &interner).unwrap().lines[0]);
+/// ```
+pub struct LinesIter<'a> {
+    /// The whole document
+    ast: &'a mdast::Node,
+    /// Top level nodes still to convert
+    nodes: slice::Iter<'a, mdast::Node>,
+    /// The emitter, until the document is completed
+    emitter: Option<Emitter<'a>>,
+    /// Lines converted, but not yet returned
+    ready: VecDeque<Line<'a>>,
+    /// Bytes of text still allowed by `Options::max_output_bytes`
+    budget: Option<usize>,
+    /// If the last line returned is empty
+    last_blank: bool,
+}
+
+impl<'a> LinesIter<'a> {
+    pub(crate) fn new(ast: &'a mdast::Node, options: Options, interner: &'a Interner) -> Self {
+        let budget = options.max_output_bytes;
+        let mut emitter = Emitter::new(options, interner);
+        let nodes = match ast {
+            mdast::Node::Root(mdast::Root {
+                children,
+                position: _,
+            }) => {
+                // root contains flow content
+                emitter.state.flow();
+                // as in `Emitter::root`, the definitions are collected before emitting the document
+                emitter.context = Some(Arc::new(DocumentContext::scan_all(children)));
+                crate::without_frontmatter(children)
+            }
+            other => slice::from_ref(other),
+        };
+        Self {
+            ast,
+            nodes: nodes.iter(),
+            emitter: Some(emitter),
+            ready: VecDeque::new(),
+            budget,
+            last_blank: false,
+        }
+    }
+
+    /// Move the lines emitted up to now in the ready ones, enforcing the budget
+    ///
+    /// `complete` tells if the document is complete.
+    fn take_lines(&mut self, complete: bool) {
+        let Some(emitter) = &mut self.emitter else {
+            return;
+        };
+        if !complete && !emitter.options.post.is_empty() {
+            // the transforms need the whole text
+            return;
+        }
+        emitter.post_process();
+        // all the emitted lines were already counted by the emitter
+        emitter.counted_lines = 0;
+        let mut lines = mem::take(&mut emitter.state.lines);
+        if emitter.options.spacing.collapse_blank_lines {
+            // the runs of empty lines can span the lines emitted by different nodes
+            if self.last_blank && lines.first().is_some_and(is_blank) {
+                lines.remove(0);
+            }
+            if let Some(last) = lines.last() {
+                self.last_blank = is_blank(last);
+            }
+        }
+        // as in `Emitter::finish`, the dropped blocks are marked once the document is complete
+        let mut truncated = complete && emitter.cut;
+        if let Some(max) = emitter.options.max_lines {
+            let remaining = max.saturating_sub(emitter.taken_lines);
+            if lines.len() > remaining {
+                lines.truncate(remaining);
+                truncated = true;
+            }
+        }
+        emitter.taken_lines += lines.len();
+        for line in lines {
+            let Some(budget) = &mut self.budget else {
+                self.ready.push_back(line);
+                continue;
+            };
+            let mut lines = vec![line];
+            if truncate_lines(&mut lines, *budget) {
+                self.ready.extend(lines);
+                truncated = true;
+                break;
+            }
+            *budget -= line_bytes(&lines[0]);
+            self.ready.extend(lines);
+        }
+        if truncated {
+            self.ready
+                .push_back(Line::new_paragraph(vec![Compound::raw_str(
+                    emitter.str(emitter.options.truncation_mark.clone()),
+                )
+                .italic()]));
+            self.emitter = None;
+        }
+    }
+}
+
+impl<'a> Iterator for LinesIter<'a> {
+    type Item = Result<Line<'a>, ToMinimadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(line) = self.ready.pop_front() {
+                return Some(Ok(line));
+            }
+            let emitter = self.emitter.as_mut()?;
+            match self.nodes.next() {
+                Some(node) => {
+                    let res = emitter.node(node);
+                    if let Err(error) = match self.ast {
+                        mdast::Node::Root(_) => res.while_emitting(self.ast),
+                        _ => res,
+                    } {
+                        self.emitter = None;
+                        return Some(Err(error));
+                    }
+                    self.take_lines(false);
+                }
+                None => {
+                    emitter.complete();
+                    self.take_lines(true);
+                    self.emitter = None;
+                }
+            }
+        }
+    }
+}
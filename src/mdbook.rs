@@ -0,0 +1,184 @@
+//! Support for [mdBook](https://rust-lang.github.io/mdBook/) books
+//!
+//! The `SUMMARY.md` of a book is parsed into a [`Summary`], that lists the chapters and is used to
+//! rewrite the links between them, so that a terminal reader can follow them.
+//!
+//! ```
+//! # use mdast2minimad::{mdbook::Summary, md_parse_options};
+//! let summary = markdown::to_mdast(
+//!     "# Summary\n\n- [Intro](intro.md)\n- [Guide](guide/index.md)\n  - [Setup](guide/setup.md)",
+//!     &md_parse_options(),
+//! )
+//! .unwrap();
+//! let summary = Summary::parse(&summary);
+//! assert_eq!(
+//!     summary.resolve_link("guide/setup.md", "../intro.md#start").as_deref(),
+//!     Some("intro.md#start")
+//! );
+//! ```
+
+use minimad::Text;
+
+use crate::{mdast, Converter, Interner, ToMinimadError};
+
+/// The chapters of a book, as listed in its `SUMMARY.md`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Summary {
+    /// The top level chapters, prefix and suffix chapters included
+    pub chapters: Vec<Chapter>,
+}
+
+/// A chapter of a book
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Chapter {
+    /// Title of the chapter
+    pub title: String,
+    /// Path of the chapter source, relative to the book sources. `None` for draft chapters.
+    pub path: Option<String>,
+    /// The nested chapters
+    pub sub_chapters: Vec<Chapter>,
+}
+
+impl Summary {
+    /// Parse the AST of a `SUMMARY.md`
+    ///
+    /// Part titles and separators are ignored.
+    pub fn parse(ast: &mdast::Node) -> Self {
+        let children = match ast {
+            mdast::Node::Root(mdast::Root {
+                children,
+                position: _,
+            }) => &children[..],
+            other => std::slice::from_ref(other),
+        };
+        let mut chapters = vec![];
+        for child in children {
+            match child {
+                // prefix and suffix chapters
+                mdast::Node::Paragraph(mdast::Paragraph {
+                    children,
+                    position: _,
+                }) => chapters.extend(children.iter().filter_map(chapter_link)),
+                mdast::Node::List(list) => chapters.extend(list_chapters(list)),
+                _ => (),
+            }
+        }
+        Self { chapters }
+    }
+
+    /// All the chapters, in reading order
+    pub fn chapters(&self) -> impl Iterator<Item = &Chapter> + '_ {
+        let mut stack: Vec<_> = self.chapters.iter().rev().collect();
+        std::iter::from_fn(move || {
+            let chapter = stack.pop()?;
+            stack.extend(chapter.sub_chapters.iter().rev());
+            Some(chapter)
+        })
+    }
+
+    /// Find a chapter by the path of its source
+    pub fn chapter(&self, path: &str) -> Option<&Chapter> {
+        self.chapters()
+            .find(|chapter| chapter.path.as_deref() == Some(path))
+    }
+
+    /// Resolve a link found in the chapter at `from`
+    ///
+    /// If the link points to a chapter of the book, return the path of the chapter (relative to
+    /// the book sources) followed by the fragment, if any.
+    pub fn resolve_link(&self, from: &str, url: &str) -> Option<String> {
+        if url.starts_with(['/', '#']) || url.contains(':') {
+            // absolute, internal or external links
+            return None;
+        }
+        let (path, fragment) = match url.split_once('#') {
+            Some((path, fragment)) => (path, Some(fragment)),
+            None => (url, None),
+        };
+        let mut segments: Vec<_> = from.split('/').collect();
+        // remove the file name
+        segments.pop();
+        for segment in path.split('/') {
+            match segment {
+                "" | "." => (),
+                ".." => {
+                    segments.pop()?;
+                }
+                segment => segments.push(segment),
+            }
+        }
+        let path = segments.join("/");
+        self.chapter(&path)?;
+        Some(match fragment {
+            Some(fragment) => format!("{path}#{fragment}"),
+            None => path,
+        })
+    }
+
+    /// Rewrite the links to other chapters in the AST of the chapter at `from`
+    ///
+    /// The links are rewritten as resolved by [`Summary::resolve_link`].
+    pub fn rewrite_links(&self, from: &str, ast: &mut mdast::Node) {
+        if let mdast::Node::Link(mdast::Link { url, .. }) = ast {
+            if let Some(resolved) = self.resolve_link(from, url) {
+                *url = resolved
+            }
+        }
+        if let Some(children) = ast.children_mut() {
+            for child in children {
+                self.rewrite_links(from, child)
+            }
+        }
+    }
+
+    /// Convert the chapter at `from`, rewriting the links to the other chapters
+    pub fn convert_chapter<'a>(
+        &self,
+        from: &str,
+        ast: &'a mut mdast::Node,
+        converter: &Converter,
+        interner: &'a Interner,
+    ) -> Result<Text<'a>, ToMinimadError> {
+        self.rewrite_links(from, ast);
+        converter.convert(ast, interner)
+    }
+}
+
+/// Parse the chapters in a list
+fn list_chapters(list: &mdast::List) -> Vec<Chapter> {
+    let mut chapters = vec![];
+    for item in &list.children {
+        let mdast::Node::ListItem(mdast::ListItem { children, .. }) = item else {
+            continue;
+        };
+        let mut chapter = None;
+        let mut sub_chapters = vec![];
+        for child in children {
+            match child {
+                mdast::Node::Paragraph(mdast::Paragraph {
+                    children,
+                    position: _,
+                }) if chapter.is_none() => chapter = children.iter().find_map(chapter_link),
+                mdast::Node::List(list) => sub_chapters.extend(list_chapters(list)),
+                _ => (),
+            }
+        }
+        if let Some(mut chapter) = chapter {
+            chapter.sub_chapters = sub_chapters;
+            chapters.push(chapter)
+        }
+    }
+    chapters
+}
+
+/// Parse a chapter from a link
+fn chapter_link(node: &mdast::Node) -> Option<Chapter> {
+    let mdast::Node::Link(mdast::Link { url, .. }) = node else {
+        return None;
+    };
+    Some(Chapter {
+        title: node.to_string(),
+        path: (!url.is_empty()).then(|| url.trim_start_matches("./").to_owned()),
+        sub_chapters: vec![],
+    })
+}
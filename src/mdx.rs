@@ -0,0 +1,210 @@
+//! Conversion of MDX documents to `minimad` templates
+
+use minimad::{Compound, Line, TextTemplate};
+
+use crate::{mdast, Emitter, Interner, Options, OwnedText, ToMinimadError};
+
+/// A placeholder of an [`MdxTemplate`], standing for a MDX expression
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Placeholder {
+    /// Name of the placeholder, as given to the template expander
+    pub name: String,
+    /// The expression, without the braces
+    pub expression: String,
+}
+
+/// A converted MDX document, whose expressions are placeholders to fill
+///
+/// ```
+/// # use mdast2minimad::to_mdx_template;
+/// # use minimad::{Compound, Line};
+/// let ast = markdown::to_mdast("Hello **{user.name}**!", &markdown::ParseOptions::mdx()).unwrap();
+/// let template = to_mdx_template(&ast).unwrap();
+/// assert_eq!(template.placeholders()[0].name, "user-name");
+///
+/// let template = template.template();
+/// let mut expander = template.expander();
+/// expander.set("user-name", "Ada");
+/// assert_eq!(
+///     expander.expand().lines,
+///     [Line::new_paragraph(vec![
+///         Compound::raw_str("Hello "),
+///         Compound::raw_str("Ada").bold(),
+///         Compound::raw_str("!"),
+///     ])]
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct MdxTemplate {
+    /// The converted text, with a `${name}` compound for each placeholder
+    text: OwnedText,
+    /// Source of a template with the placeholders at the same places
+    skeleton: String,
+    placeholders: Vec<Placeholder>,
+}
+
+impl MdxTemplate {
+    /// The template, ready to be expanded
+    pub fn template(&self) -> TextTemplate<'_> {
+        // `TextTemplate` can only be parsed: the skeleton gives the places of the placeholders,
+        // and then the lines are replaced by the converted ones
+        let mut template = TextTemplate::from(&*self.skeleton);
+        template.text = self.text.text();
+        template
+    }
+
+    /// The placeholders, in order of appearance
+    ///
+    /// A placeholder appearing more than once is listed only the first time.
+    pub fn placeholders(&self) -> &[Placeholder] {
+        &self.placeholders
+    }
+
+    /// The converted text, where each placeholder is a `${name}` compound
+    pub fn text(&self) -> &OwnedText {
+        &self.text
+    }
+}
+
+/// Convert a MDX document to a template, whose placeholders are the expressions
+///
+/// The expressions (`{expression}`) become placeholders that can be filled with `minimad`'s
+/// [`TextTemplateExpander`](minimad::TextTemplateExpander). A placeholder inside a line keeps the
+/// style of the surrounding text, while a flow expression is a paragraph of its own.
+///
+/// The AST should be parsed with [`markdown::ParseOptions::mdx`]. The other MDX nodes, like JSX
+/// elements, are unsupported as in [`to_minimad`](crate::to_minimad).
+pub fn to_mdx_template(ast: &mdast::Node) -> Result<MdxTemplate, ToMinimadError> {
+    to_mdx_template_with_options(ast, Options::default())
+}
+
+/// Convert a MDX document to a template, with the given options
+///
+/// See [`to_mdx_template`].
+pub fn to_mdx_template_with_options(
+    ast: &mdast::Node,
+    options: Options,
+) -> Result<MdxTemplate, ToMinimadError> {
+    let interner = Interner::new();
+    let mut emitter = Emitter::new(options, &interner);
+    emitter.placeholders = Some(vec![]);
+    emitter.node(ast)?;
+    let placeholders = emitter.placeholders.take().unwrap_or_default();
+    let mut text = emitter.finish();
+
+    let markers: Vec<String> = placeholders
+        .iter()
+        .map(|placeholder| marker(&placeholder.name))
+        .collect();
+    let mut skeleton = String::new();
+    for line in &mut text.lines {
+        match line {
+            Line::Normal(composite) => {
+                skeleton += &composite_skeleton(&mut composite.compounds, &markers);
+            }
+            Line::TableRow(row) => {
+                skeleton.push('|');
+                for cell in &mut row.cells {
+                    skeleton += &composite_skeleton(&mut cell.compounds, &markers);
+                    skeleton.push('|');
+                }
+            }
+            Line::TableRule(_) | Line::HorizontalRule | Line::CodeFence(_) => skeleton.push('x'),
+        }
+        skeleton.push('\n');
+    }
+    Ok(MdxTemplate {
+        text: OwnedText::from(text),
+        skeleton,
+        placeholders,
+    })
+}
+
+/// The text of the placeholder with the given name
+pub(crate) fn marker(name: &str) -> String {
+    format!("${{{name}}}")
+}
+
+/// The name of the placeholder for an expression
+///
+/// The name is made of the characters that `minimad` accepts, and is unique between the
+/// different expressions.
+pub(crate) fn placeholder_name(expression: &str, placeholders: &[Placeholder]) -> String {
+    if let Some(placeholder) = placeholders
+        .iter()
+        .find(|placeholder| placeholder.expression == expression)
+    {
+        return placeholder.name.clone();
+    }
+    let mut base = String::new();
+    for ch in expression.chars().flat_map(char::to_lowercase) {
+        if ch.is_ascii_lowercase() || ch.is_ascii_digit() || ch == '_' {
+            base.push(ch);
+        } else if !base.is_empty() && !base.ends_with('-') {
+            base.push('-');
+        }
+    }
+    let base = match base.trim_end_matches('-') {
+        "" => "expression",
+        base => base,
+    };
+    let taken = |name: &str| {
+        placeholders
+            .iter()
+            .any(|placeholder| placeholder.name == name)
+    };
+    let mut name = base.to_owned();
+    let mut count = 1;
+    while taken(&name) {
+        count += 1;
+        name = format!("{base}-{count}");
+    }
+    name
+}
+
+/// Write a line that `minimad` parses with the placeholders at the same index as in `compounds`
+///
+/// The placeholders merged with the text around them by the post-processing are split first.
+fn composite_skeleton<'a>(compounds: &mut Vec<Compound<'a>>, markers: &[String]) -> String {
+    let mut split = Vec::with_capacity(compounds.len());
+    for mut compound in compounds.drain(..) {
+        while let Some((start, len)) = markers
+            .iter()
+            .filter_map(|marker| Some((compound.src.find(&**marker)?, marker.len())))
+            .min()
+        {
+            if start == 0 && len == compound.src.len() {
+                break;
+            }
+            if start > 0 {
+                split.push(compound.sub(0, start));
+            }
+            split.push(compound.sub(start, start + len));
+            compound = compound.tail(start + len);
+            if compound.src.is_empty() {
+                break;
+            }
+        }
+        if !compound.src.is_empty() {
+            split.push(compound);
+        }
+    }
+    *compounds = split;
+
+    let mut skeleton = String::new();
+    // consecutive fillers alternate their style, so that they are not merged
+    let mut italic = false;
+    for compound in compounds.iter() {
+        if markers.iter().any(|marker| marker == compound.src) {
+            skeleton.push_str(compound.src);
+        } else {
+            skeleton.push_str(if italic { "*x*" } else { "x" });
+            italic = !italic;
+        }
+    }
+    // `minimad` drops the blank lines at the start and at the end of the templates
+    if skeleton.is_empty() {
+        skeleton.push('x');
+    }
+    skeleton
+}
@@ -0,0 +1,383 @@
+//! A simple model of the converted text, independent from `minimad`
+//!
+//! The model owns its strings, and with the `serde` feature can be serialized. It is meant to test
+//! the output of the conversion, or to write other backends, without depending on the details of
+//! the `minimad` lines.
+//!
+//! When the exact lines matter, e.g. to snapshot a rendering pipeline or to cache the converted
+//! documents on disk, [`TextModel`] mirrors the `minimad` text line by line.
+//!
+//! ```
+//! # use mdast2minimad::{model::{to_simple_model, SimpleBlock, Span}, Options};
+//! let ast = markdown::to_mdast("# Title\n\nSome **text**", &mdast2minimad::md_parse_options()).unwrap();
+//! let model = to_simple_model(&ast, Options::default()).unwrap();
+//! assert_eq!(
+//!     model[1],
+//!     SimpleBlock::Paragraph {
+//!         lines: vec![vec![Span::plain("Some "), Span { bold: true, ..Span::plain("text") }]],
+//!     }
+//! );
+//! ```
+
+use minimad::{Alignment, Composite, CompositeStyle, Compound, Line, TableRow, TableRule, Text};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{mdast, to_minimad_with_options, Interner, Options, OwnedText, ToMinimadError};
+
+/// A block of the converted text
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum SimpleBlock {
+    /// A heading, with its level starting from 1
+    Heading { level: u8, spans: Vec<Span> },
+    /// Consecutive lines of text
+    Paragraph { lines: Vec<Vec<Span>> },
+    /// An item of a list, with its nesting depth starting from 0
+    ListItem { depth: u8, spans: Vec<Span> },
+    /// Consecutive lines of code
+    Code { lines: Vec<String> },
+    /// Consecutive lines of a quote
+    Quote { lines: Vec<Vec<Span>> },
+    /// A table, as rows of cells
+    Table { rows: Vec<Vec<Vec<Span>>> },
+    /// A horizontal rule
+    Rule,
+}
+
+/// A run of text with the same style
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Span {
+    pub text: String,
+    pub bold: bool,
+    pub italic: bool,
+    pub code: bool,
+    pub strikeout: bool,
+}
+
+impl Span {
+    /// A span of unstyled text
+    pub fn plain(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            ..Default::default()
+        }
+    }
+}
+
+impl Span {
+    /// The compound with the text and style of the span
+    pub fn compound(&self) -> Compound<'_> {
+        Compound {
+            src: &self.text,
+            bold: self.bold,
+            italic: self.italic,
+            code: self.code,
+            strikeout: self.strikeout,
+        }
+    }
+}
+
+impl From<&Compound<'_>> for Span {
+    fn from(compound: &Compound<'_>) -> Self {
+        Self {
+            text: compound.src.to_owned(),
+            bold: compound.bold,
+            italic: compound.italic,
+            code: compound.code,
+            strikeout: compound.strikeout,
+        }
+    }
+}
+
+/// Convert the markdown AST to a list of simple blocks
+///
+/// Blank lines only separate the blocks, and are not part of the model. Code fences are dropped.
+pub fn to_simple_model(
+    ast: &mdast::Node,
+    options: Options,
+) -> Result<Vec<SimpleBlock>, ToMinimadError> {
+    let interner = Interner::new();
+    let text = to_minimad_with_options(ast, options, &interner)?;
+
+    let mut blocks = vec![];
+    // if the last block is complete, and a new one must be started
+    let mut closed = true;
+    for line in &text.lines {
+        let last = if closed { None } else { blocks.last_mut() };
+        closed = false;
+        match (line, last) {
+            (Line::Normal(composite), _) if is_empty(composite) => closed = true,
+            (Line::Normal(Composite { style, compounds }), last) => {
+                let spans = spans(compounds);
+                match (style, last) {
+                    (CompositeStyle::Paragraph, Some(SimpleBlock::Paragraph { lines }))
+                    | (CompositeStyle::Quote, Some(SimpleBlock::Quote { lines })) => {
+                        lines.push(spans)
+                    }
+                    (CompositeStyle::Code, Some(SimpleBlock::Code { lines })) => {
+                        lines.push(plain(compounds))
+                    }
+                    (CompositeStyle::Paragraph, _) => {
+                        blocks.push(SimpleBlock::Paragraph { lines: vec![spans] })
+                    }
+                    (CompositeStyle::Quote, _) => {
+                        blocks.push(SimpleBlock::Quote { lines: vec![spans] })
+                    }
+                    (CompositeStyle::Code, _) => blocks.push(SimpleBlock::Code {
+                        lines: vec![plain(compounds)],
+                    }),
+                    (CompositeStyle::Header(level), _) => blocks.push(SimpleBlock::Heading {
+                        level: *level,
+                        spans,
+                    }),
+                    (CompositeStyle::ListItem(depth), _) => blocks.push(SimpleBlock::ListItem {
+                        depth: *depth,
+                        spans,
+                    }),
+                }
+            }
+            (Line::TableRow(row), last) => {
+                let row = row
+                    .cells
+                    .iter()
+                    .map(|cell| spans(&cell.compounds))
+                    .collect();
+                match last {
+                    Some(SimpleBlock::Table { rows }) => rows.push(row),
+                    _ => blocks.push(SimpleBlock::Table { rows: vec![row] }),
+                }
+            }
+            // The rule is part of the table
+            (Line::TableRule(_), _) => (),
+            (Line::HorizontalRule, _) => {
+                blocks.push(SimpleBlock::Rule);
+                closed = true
+            }
+            (Line::CodeFence(_), _) => closed = true,
+        }
+    }
+    Ok(blocks)
+}
+
+fn is_empty(composite: &Composite<'_>) -> bool {
+    composite.style == CompositeStyle::Paragraph
+        && composite
+            .compounds
+            .iter()
+            .all(|compound| compound.src.trim().is_empty())
+}
+
+fn spans(compounds: &[Compound<'_>]) -> Vec<Span> {
+    compounds.iter().map(Span::from).collect()
+}
+
+fn plain(compounds: &[Compound<'_>]) -> String {
+    compounds.iter().map(|compound| compound.src).collect()
+}
+
+/// A `minimad` text, line by line
+///
+/// Unlike [`SimpleBlock`], nothing is dropped: converting back with [`TextModel::text`] gives the
+/// same lines.
+/// ```
+/// # use mdast2minimad::{md_parse_options, model::TextModel, to_minimad, Interner};
+/// let interner = Interner::new();
+/// let ast = markdown::to_mdast("# Title\n\nSome **text**", &md_parse_options()).unwrap();
+/// let text = to_minimad(&ast, &interner).unwrap();
+/// let model = TextModel::from(&text);
+/// assert_eq!(model.text(), text);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TextModel {
+    pub lines: Vec<LineModel>,
+}
+
+/// A line of a [`TextModel`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum LineModel {
+    /// A line of text
+    Normal(CompositeModel),
+    /// A row of a table, with a composite for each cell
+    TableRow(Vec<CompositeModel>),
+    /// The rule under the header of a table, with the alignment of each column
+    TableRule(Vec<AlignmentModel>),
+    /// A horizontal rule
+    HorizontalRule,
+    /// The fence of a code block
+    CodeFence(CompositeModel),
+}
+
+/// A styled line, or a table cell, of a [`TextModel`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CompositeModel {
+    pub style: StyleModel,
+    pub spans: Vec<Span>,
+}
+
+/// The style of a line, as `minimad::CompositeStyle`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum StyleModel {
+    Paragraph,
+    Header(u8),
+    ListItem(u8),
+    Code,
+    Quote,
+}
+
+/// The alignment of a table column, as `minimad::Alignment`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum AlignmentModel {
+    Unspecified,
+    Left,
+    Center,
+    Right,
+}
+
+impl TextModel {
+    /// The `minimad` text, borrowing the strings of the model
+    pub fn text(&self) -> Text<'_> {
+        Text {
+            lines: self.lines.iter().map(LineModel::line).collect(),
+        }
+    }
+}
+
+impl From<&Text<'_>> for TextModel {
+    fn from(text: &Text<'_>) -> Self {
+        Self {
+            lines: text.lines.iter().map(LineModel::from).collect(),
+        }
+    }
+}
+
+impl From<&OwnedText> for TextModel {
+    fn from(text: &OwnedText) -> Self {
+        Self::from(&text.text())
+    }
+}
+
+impl LineModel {
+    /// The `minimad` line, borrowing the strings of the model
+    pub fn line(&self) -> Line<'_> {
+        match self {
+            LineModel::Normal(composite) => Line::Normal(composite.composite()),
+            LineModel::TableRow(cells) => Line::TableRow(TableRow {
+                cells: cells.iter().map(CompositeModel::composite).collect(),
+            }),
+            LineModel::TableRule(cells) => Line::TableRule(TableRule {
+                cells: cells.iter().map(|&alignment| alignment.into()).collect(),
+            }),
+            LineModel::HorizontalRule => Line::HorizontalRule,
+            LineModel::CodeFence(composite) => Line::CodeFence(composite.composite()),
+        }
+    }
+}
+
+impl From<&Line<'_>> for LineModel {
+    fn from(line: &Line<'_>) -> Self {
+        match line {
+            Line::Normal(composite) => LineModel::Normal(composite.into()),
+            Line::TableRow(TableRow { cells }) => {
+                LineModel::TableRow(cells.iter().map(CompositeModel::from).collect())
+            }
+            Line::TableRule(TableRule { cells }) => {
+                LineModel::TableRule(cells.iter().map(|&alignment| alignment.into()).collect())
+            }
+            Line::HorizontalRule => LineModel::HorizontalRule,
+            Line::CodeFence(composite) => LineModel::CodeFence(composite.into()),
+        }
+    }
+}
+
+impl CompositeModel {
+    /// The `minimad` composite, borrowing the strings of the model
+    pub fn composite(&self) -> Composite<'_> {
+        Composite {
+            style: self.style.into(),
+            compounds: self.spans.iter().map(Span::compound).collect(),
+        }
+    }
+}
+
+impl From<&Composite<'_>> for CompositeModel {
+    fn from(composite: &Composite<'_>) -> Self {
+        Self {
+            style: composite.style.into(),
+            spans: spans(&composite.compounds),
+        }
+    }
+}
+
+impl From<CompositeStyle> for StyleModel {
+    fn from(style: CompositeStyle) -> Self {
+        match style {
+            CompositeStyle::Paragraph => StyleModel::Paragraph,
+            CompositeStyle::Header(depth) => StyleModel::Header(depth),
+            CompositeStyle::ListItem(depth) => StyleModel::ListItem(depth),
+            CompositeStyle::Code => StyleModel::Code,
+            CompositeStyle::Quote => StyleModel::Quote,
+        }
+    }
+}
+
+impl From<StyleModel> for CompositeStyle {
+    fn from(style: StyleModel) -> Self {
+        match style {
+            StyleModel::Paragraph => CompositeStyle::Paragraph,
+            StyleModel::Header(depth) => CompositeStyle::Header(depth),
+            StyleModel::ListItem(depth) => CompositeStyle::ListItem(depth),
+            StyleModel::Code => CompositeStyle::Code,
+            StyleModel::Quote => CompositeStyle::Quote,
+        }
+    }
+}
+
+impl From<Alignment> for AlignmentModel {
+    fn from(alignment: Alignment) -> Self {
+        match alignment {
+            Alignment::Unspecified => AlignmentModel::Unspecified,
+            Alignment::Left => AlignmentModel::Left,
+            Alignment::Center => AlignmentModel::Center,
+            Alignment::Right => AlignmentModel::Right,
+        }
+    }
+}
+
+impl From<AlignmentModel> for Alignment {
+    fn from(alignment: AlignmentModel) -> Self {
+        match alignment {
+            AlignmentModel::Unspecified => Alignment::Unspecified,
+            AlignmentModel::Left => Alignment::Left,
+            AlignmentModel::Center => Alignment::Center,
+            AlignmentModel::Right => Alignment::Right,
+        }
+    }
+}
+
+/// Convert the markdown AST to a [`TextModel`]
+pub fn to_text_model(ast: &mdast::Node, options: Options) -> Result<TextModel, ToMinimadError> {
+    let interner = Interner::new();
+    to_minimad_with_options(ast, options, &interner).map(|text| TextModel::from(&text))
+}
+
+/// The owned text is serialized as its [`TextModel`]
+#[cfg(feature = "serde")]
+impl Serialize for OwnedText {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        TextModel::from(self).serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for OwnedText {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        TextModel::deserialize(deserializer).map(|model| OwnedText::new(&model.text()))
+    }
+}
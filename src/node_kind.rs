@@ -0,0 +1,251 @@
+//! Kinds of the mdast nodes
+
+use std::{fmt, str::FromStr};
+
+use derive_more::derive::{Display, Error};
+
+use crate::mdast;
+
+/// The kind of a [`mdast::Node`], without its content
+///
+/// This is the crate's own enum: matching on it does not depend on the version of the `markdown`
+/// crate. The variants are named as the node types of the mdast specification, and new ones can
+/// be added when `markdown` supports new nodes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum NodeKind {
+    /// The whole document
+    Root,
+    /// A quoted section, `> text`
+    Blockquote,
+    /// The content of a footnote, `[^label]: text`
+    FootnoteDefinition,
+    /// An MDX JSX element in flow content
+    MdxJsxFlowElement,
+    /// An ordered or unordered list
+    List,
+    /// An MDX `import` or `export` statement
+    MdxjsEsm,
+    /// A TOML frontmatter, between `+++`
+    Toml,
+    /// A YAML frontmatter, between `---`
+    Yaml,
+    /// A hard line break
+    Break,
+    /// A code span, `` `code` ``
+    InlineCode,
+    /// A math span, `$x$`
+    InlineMath,
+    /// A strikethrough text, `~~text~~`
+    Delete,
+    /// An emphasized text, `*text*`
+    Emphasis,
+    /// An MDX expression in phrasing content, `{value}`
+    MdxTextExpression,
+    /// A reference to a footnote, `[^label]`
+    FootnoteReference,
+    /// Raw HTML
+    Html,
+    /// An image, `![alt](url)`
+    Image,
+    /// An image through a definition, `![alt][label]`
+    ImageReference,
+    /// An MDX JSX element in phrasing content
+    MdxJsxTextElement,
+    /// A link, `[text](url)`
+    Link,
+    /// A link through a definition, `[text][label]`
+    LinkReference,
+    /// A strongly emphasized text, `**text**`
+    Strong,
+    /// Plain text
+    Text,
+    /// A fenced or indented code block
+    Code,
+    /// A math block, between `$$`
+    Math,
+    /// An MDX expression in flow content, `{value}`
+    MdxFlowExpression,
+    /// A heading, `# Title`
+    Heading,
+    /// A GFM table
+    Table,
+    /// A horizontal rule, `***`
+    ThematicBreak,
+    /// A row of a table
+    TableRow,
+    /// A cell of a table row
+    TableCell,
+    /// An item of a list
+    ListItem,
+    /// A link or image definition, `[label]: url`
+    Definition,
+    /// A paragraph
+    Paragraph,
+}
+
+impl NodeKind {
+    /// All the kinds of nodes
+    pub const ALL: &'static [NodeKind] = &[
+        Self::Root,
+        Self::Blockquote,
+        Self::FootnoteDefinition,
+        Self::MdxJsxFlowElement,
+        Self::List,
+        Self::MdxjsEsm,
+        Self::Toml,
+        Self::Yaml,
+        Self::Break,
+        Self::InlineCode,
+        Self::InlineMath,
+        Self::Delete,
+        Self::Emphasis,
+        Self::MdxTextExpression,
+        Self::FootnoteReference,
+        Self::Html,
+        Self::Image,
+        Self::ImageReference,
+        Self::MdxJsxTextElement,
+        Self::Link,
+        Self::LinkReference,
+        Self::Strong,
+        Self::Text,
+        Self::Code,
+        Self::Math,
+        Self::MdxFlowExpression,
+        Self::Heading,
+        Self::Table,
+        Self::ThematicBreak,
+        Self::TableRow,
+        Self::TableCell,
+        Self::ListItem,
+        Self::Definition,
+        Self::Paragraph,
+    ];
+
+    /// The kind of a node
+    pub fn of(node: &mdast::Node) -> Self {
+        match node {
+            mdast::Node::Root(_) => Self::Root,
+            mdast::Node::Blockquote(_) => Self::Blockquote,
+            mdast::Node::FootnoteDefinition(_) => Self::FootnoteDefinition,
+            mdast::Node::MdxJsxFlowElement(_) => Self::MdxJsxFlowElement,
+            mdast::Node::List(_) => Self::List,
+            mdast::Node::MdxjsEsm(_) => Self::MdxjsEsm,
+            mdast::Node::Toml(_) => Self::Toml,
+            mdast::Node::Yaml(_) => Self::Yaml,
+            mdast::Node::Break(_) => Self::Break,
+            mdast::Node::InlineCode(_) => Self::InlineCode,
+            mdast::Node::InlineMath(_) => Self::InlineMath,
+            mdast::Node::Delete(_) => Self::Delete,
+            mdast::Node::Emphasis(_) => Self::Emphasis,
+            mdast::Node::MdxTextExpression(_) => Self::MdxTextExpression,
+            mdast::Node::FootnoteReference(_) => Self::FootnoteReference,
+            mdast::Node::Html(_) => Self::Html,
+            mdast::Node::Image(_) => Self::Image,
+            mdast::Node::ImageReference(_) => Self::ImageReference,
+            mdast::Node::MdxJsxTextElement(_) => Self::MdxJsxTextElement,
+            mdast::Node::Link(_) => Self::Link,
+            mdast::Node::LinkReference(_) => Self::LinkReference,
+            mdast::Node::Strong(_) => Self::Strong,
+            mdast::Node::Text(_) => Self::Text,
+            mdast::Node::Code(_) => Self::Code,
+            mdast::Node::Math(_) => Self::Math,
+            mdast::Node::MdxFlowExpression(_) => Self::MdxFlowExpression,
+            mdast::Node::Heading(_) => Self::Heading,
+            mdast::Node::Table(_) => Self::Table,
+            mdast::Node::ThematicBreak(_) => Self::ThematicBreak,
+            mdast::Node::TableRow(_) => Self::TableRow,
+            mdast::Node::TableCell(_) => Self::TableCell,
+            mdast::Node::ListItem(_) => Self::ListItem,
+            mdast::Node::Definition(_) => Self::Definition,
+            mdast::Node::Paragraph(_) => Self::Paragraph,
+        }
+    }
+
+    /// The name of the node type, as in the mdast specification
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Root => "Root",
+            Self::Blockquote => "Blockquote",
+            Self::FootnoteDefinition => "FootnoteDefinition",
+            Self::MdxJsxFlowElement => "MdxJsxFlowElement",
+            Self::List => "List",
+            Self::MdxjsEsm => "MdxjsEsm",
+            Self::Toml => "Toml",
+            Self::Yaml => "Yaml",
+            Self::Break => "Break",
+            Self::InlineCode => "InlineCode",
+            Self::InlineMath => "InlineMath",
+            Self::Delete => "Delete",
+            Self::Emphasis => "Emphasis",
+            Self::MdxTextExpression => "MdxTextExpression",
+            Self::FootnoteReference => "FootnoteReference",
+            Self::Html => "Html",
+            Self::Image => "Image",
+            Self::ImageReference => "ImageReference",
+            Self::MdxJsxTextElement => "MdxJsxTextElement",
+            Self::Link => "Link",
+            Self::LinkReference => "LinkReference",
+            Self::Strong => "Strong",
+            Self::Text => "Text",
+            Self::Code => "Code",
+            Self::Math => "Math",
+            Self::MdxFlowExpression => "MdxFlowExpression",
+            Self::Heading => "Heading",
+            Self::Table => "Table",
+            Self::ThematicBreak => "ThematicBreak",
+            Self::TableRow => "TableRow",
+            Self::TableCell => "TableCell",
+            Self::ListItem => "ListItem",
+            Self::Definition => "Definition",
+            Self::Paragraph => "Paragraph",
+        }
+    }
+}
+
+impl fmt::Display for NodeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<&mdast::Node> for NodeKind {
+    fn from(node: &mdast::Node) -> Self {
+        Self::of(node)
+    }
+}
+
+/// Error parsing an unknown [`NodeKind`] name
+#[derive(Debug, Clone, PartialEq, Eq, Display, Error)]
+#[display("Unknown node kind `{_0}`")]
+pub struct UnknownNodeKind(#[error(not(source))] pub String);
+
+impl FromStr for NodeKind {
+    type Err = UnknownNodeKind;
+
+    /// Parse the name of a node type, as returned by [`NodeKind::as_str`]
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|kind| kind.as_str() == name)
+            .ok_or_else(|| UnknownNodeKind(name.to_owned()))
+    }
+}
+
+/// Node kinds are serialized by their name, as in [`NodeKind::as_str`]
+#[cfg(feature = "serde")]
+impl serde::Serialize for NodeKind {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for NodeKind {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+        name.parse().map_err(serde::de::Error::custom)
+    }
+}
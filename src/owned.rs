@@ -0,0 +1,152 @@
+//! Converted texts that do not borrow from the AST
+
+use std::ops::Range;
+
+use minimad::{Composite, CompositeStyle, Compound, Line, TableRow, TableRule, Text};
+
+/// A minimad text owning its strings
+///
+/// All the strings are stored in a single buffer, and [`OwnedText::text`] gives back a borrowed
+/// [`Text`] pointing into it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OwnedText {
+    /// All the strings of the text, concatenated
+    buffer: String,
+    /// The lines, with compounds pointing into the buffer
+    lines: Vec<OwnedLine>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum OwnedLine {
+    Normal(OwnedComposite),
+    TableRow(Vec<OwnedComposite>),
+    TableRule(TableRule),
+    HorizontalRule,
+    CodeFence(OwnedComposite),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct OwnedComposite {
+    style: CompositeStyle,
+    compounds: Vec<OwnedCompound>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct OwnedCompound {
+    /// Range of the string in the buffer
+    src: Range<usize>,
+    bold: bool,
+    italic: bool,
+    code: bool,
+    strikeout: bool,
+}
+
+impl OwnedText {
+    /// Copy a text into an owned one
+    pub fn new(text: &Text<'_>) -> Self {
+        let mut buffer = String::with_capacity(
+            text.lines
+                .iter()
+                .flat_map(composites)
+                .flat_map(|composite| &composite.compounds)
+                .map(|compound| compound.src.len())
+                .sum(),
+        );
+        let mut own = |composite: &Composite<'_>| OwnedComposite {
+            style: composite.style,
+            compounds: composite
+                .compounds
+                .iter()
+                .map(|compound| {
+                    let start = buffer.len();
+                    buffer.push_str(compound.src);
+                    OwnedCompound {
+                        src: start..buffer.len(),
+                        bold: compound.bold,
+                        italic: compound.italic,
+                        code: compound.code,
+                        strikeout: compound.strikeout,
+                    }
+                })
+                .collect(),
+        };
+        let lines = text
+            .lines
+            .iter()
+            .map(|line| match line {
+                Line::Normal(composite) => OwnedLine::Normal(own(composite)),
+                Line::TableRow(TableRow { cells }) => {
+                    OwnedLine::TableRow(cells.iter().map(&mut own).collect())
+                }
+                Line::TableRule(rule) => OwnedLine::TableRule(rule.clone()),
+                Line::HorizontalRule => OwnedLine::HorizontalRule,
+                Line::CodeFence(composite) => OwnedLine::CodeFence(own(composite)),
+            })
+            .collect();
+        Self { buffer, lines }
+    }
+
+    /// The text, borrowing from this one
+    pub fn text(&self) -> Text<'_> {
+        let borrow = |composite: &OwnedComposite| Composite {
+            style: composite.style,
+            compounds: composite
+                .compounds
+                .iter()
+                .map(|compound| Compound {
+                    src: &self.buffer[compound.src.clone()],
+                    bold: compound.bold,
+                    italic: compound.italic,
+                    code: compound.code,
+                    strikeout: compound.strikeout,
+                })
+                .collect(),
+        };
+        Text {
+            lines: self
+                .lines
+                .iter()
+                .map(|line| match line {
+                    OwnedLine::Normal(composite) => Line::Normal(borrow(composite)),
+                    OwnedLine::TableRow(cells) => Line::TableRow(TableRow {
+                        cells: cells.iter().map(borrow).collect(),
+                    }),
+                    OwnedLine::TableRule(rule) => Line::TableRule(rule.clone()),
+                    OwnedLine::HorizontalRule => Line::HorizontalRule,
+                    OwnedLine::CodeFence(composite) => Line::CodeFence(borrow(composite)),
+                })
+                .collect(),
+        }
+    }
+
+    /// Number of lines in the text
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// Check if the text has no lines
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+}
+
+impl From<&Text<'_>> for OwnedText {
+    fn from(text: &Text<'_>) -> Self {
+        Self::new(text)
+    }
+}
+
+impl From<Text<'_>> for OwnedText {
+    fn from(text: Text<'_>) -> Self {
+        Self::new(&text)
+    }
+}
+
+/// All the composites in a line
+pub(crate) fn composites<'l, 'a>(line: &'l Line<'a>) -> &'l [Composite<'a>] {
+    match line {
+        Line::Normal(composite) | Line::CodeFence(composite) => std::slice::from_ref(composite),
+        Line::TableRow(TableRow { cells }) => cells,
+        Line::TableRule(_) | Line::HorizontalRule => &[],
+    }
+}
@@ -0,0 +1,69 @@
+//! Splitting of a converted text in pages
+//!
+//! ```
+//! # use mdast2minimad::{pages::paginate, Converter, Directive, Interner};
+//! let interner = Interner::new();
+//! let ast = markdown::to_mdast(
+//!     "# First\n\nSlide\n\n<!-- slides: pause -->\n\n# Second",
+//!     &markdown::ParseOptions::default(),
+//! )
+//! .unwrap();
+//! let converter = Converter::builder()
+//!     .directives("slides:", |directive, _| match directive {
+//!         "pause" => Directive::PageBreak,
+//!         _ => Directive::Unknown,
+//!     })
+//!     .build();
+//! let (text, report) = converter.convert_reporting(&ast, &interner).unwrap();
+//! let pages = paginate(&text.lines, 24, &report.page_breaks);
+//! assert_eq!(pages.len(), 2);
+//! assert_eq!(pages[1][0], minimad::Line::new_header(1, vec![minimad::Compound::raw_str("Second")]));
+//! ```
+
+use minimad::{Composite, CompositeStyle, Line};
+
+/// Split the lines in pages of at most `height` lines, starting a new page at each page break
+///
+/// `page_breaks` are the indices of the lines that must start a new page, as in
+/// [`ConversionReport::page_breaks`](crate::ConversionReport::page_breaks). The empty lines at the
+/// start of a page are dropped, and so are the empty pages. Each line is counted as one row: if
+/// the lines are wrapped when displayed, `height` must leave room for it.
+pub fn paginate<'l, 'a>(
+    lines: &'l [Line<'a>],
+    height: usize,
+    page_breaks: &[usize],
+) -> Vec<&'l [Line<'a>]> {
+    let height = height.max(1);
+    let mut pages = vec![];
+    let mut start = 0;
+    let ends = page_breaks
+        .iter()
+        .copied()
+        .filter(|&end| end < lines.len())
+        .chain([lines.len()]);
+    for end in ends {
+        if end < start {
+            continue;
+        }
+        let mut section = &lines[start..end];
+        start = end;
+        loop {
+            while section.first().is_some_and(is_blank) {
+                section = &section[1..];
+            }
+            if section.is_empty() {
+                break;
+            }
+            let (page, rest) = section.split_at(height.min(section.len()));
+            pages.push(page);
+            section = rest;
+        }
+    }
+    pages
+}
+
+/// Check if a line is an empty paragraph line
+fn is_blank(line: &Line<'_>) -> bool {
+    matches!(line, Line::Normal(Composite { style: CompositeStyle::Paragraph, compounds })
+        if compounds.iter().all(|compound| compound.src.trim().is_empty()))
+}
@@ -0,0 +1,90 @@
+//! Conversion to unstyled plain text
+
+use minimad::{Composite, CompositeStyle, Line, TableRow, Text};
+
+use crate::{mdast, to_minimad_with_options, Interner, Options, ToMinimadError};
+
+/// Convert the markdown AST to plain text, without any style
+///
+/// The AST is converted as by [`to_minimad`](crate::to_minimad), and the lines are written
+/// without markup: list items get a `-` bullet, quotes a `> ` prefix, table cells are separated
+/// by ` | ` and code is kept verbatim. Blank lines are left empty, and the ones at the end are
+/// removed.
+///
+/// ```
+/// # use mdast2minimad::{md_parse_options, to_plain_text};
+/// let ast = markdown::to_mdast(
+///     "# Title\n\nSome **bold** [link](https://example.com)\n\n- one\n- two",
+///     &md_parse_options(),
+/// )
+/// .unwrap();
+/// assert_eq!(
+///     to_plain_text(&ast).unwrap(),
+///     "Title\n\nSome bold link\n\n- one\n- two"
+/// );
+/// ```
+pub fn to_plain_text(ast: &mdast::Node) -> Result<String, ToMinimadError> {
+    to_plain_text_with_options(ast, Options::default())
+}
+
+/// Convert the markdown AST to plain text, with the given options
+///
+/// See [`to_plain_text`].
+pub fn to_plain_text_with_options(
+    ast: &mdast::Node,
+    options: Options,
+) -> Result<String, ToMinimadError> {
+    let interner = Interner::new();
+    Ok(plain_text(&to_minimad_with_options(
+        ast, options, &interner,
+    )?))
+}
+
+/// Write a converted text as plain text
+///
+/// See [`to_plain_text`].
+pub fn plain_text(text: &Text<'_>) -> String {
+    let mut lines: Vec<String> = text.lines.iter().filter_map(plain_line).collect();
+    while lines.last().is_some_and(String::is_empty) {
+        lines.pop();
+    }
+    lines.join("\n")
+}
+
+/// Write a line as plain text, or `None` if it has no text
+fn plain_line(line: &Line<'_>) -> Option<String> {
+    Some(match line {
+        Line::Normal(composite) => {
+            let prefix = match composite.style {
+                CompositeStyle::ListItem(depth) => "  ".repeat(depth.into()) + "- ",
+                CompositeStyle::Quote => "> ".to_owned(),
+                CompositeStyle::Paragraph | CompositeStyle::Header(_) | CompositeStyle::Code => {
+                    String::new()
+                }
+            };
+            let line = prefix + &plain_composite(composite);
+            // blank lines inside list items are indented
+            if line.trim().is_empty() {
+                String::new()
+            } else {
+                line
+            }
+        }
+        Line::TableRow(TableRow { cells }) => cells
+            .iter()
+            .map(plain_composite)
+            .collect::<Vec<_>>()
+            .join(" | "),
+        Line::HorizontalRule => "---".to_owned(),
+        Line::TableRule(_) | Line::CodeFence(_) => return None,
+    })
+}
+
+/// The text of a composite
+fn plain_composite(composite: &Composite<'_>) -> String {
+    composite
+        .compounds
+        .iter()
+        .map(|compound| compound.src)
+        .collect()
+}
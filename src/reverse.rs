@@ -0,0 +1,313 @@
+//! Conversion of a minimad text back to a markdown AST
+
+use minimad::{Alignment, Composite, CompositeStyle, Compound, Line, TableRow, Text};
+
+use crate::mdast;
+
+/// Convert a minimad text back to a markdown AST
+///
+/// This is the reverse of [`to_minimad`](crate::to_minimad), for the texts parsed by `minimad` or
+/// edited after the conversion. The structure is rebuilt from the lines:
+/// - consecutive list items, even if separated by blank lines, form a list nested by their depth;
+/// - consecutive quote lines form a quote, with a paragraph for each line;
+/// - consecutive code lines form a code block, split by the code fences;
+/// - consecutive table lines form a table, whose header is before the table rule.
+///
+/// Each paragraph line is a paragraph, and empty lines are dropped. The nodes have no position.
+/// ```
+/// # use mdast2minimad::{from_minimad, mdast, to_minimad, Interner};
+/// let interner = Interner::new();
+/// let text = minimad::parse_text("# Title\n\nSome **bold** text", minimad::Options::default());
+/// let ast = from_minimad(&text);
+/// assert_eq!(to_minimad(&ast, &interner).unwrap(), text);
+/// ```
+pub fn from_minimad(text: &Text<'_>) -> mdast::Node {
+    let mut children = vec![];
+    let mut lines = &text.lines[..];
+    while let Some(line) = lines.first() {
+        let len = match line {
+            Line::Normal(Composite {
+                style: CompositeStyle::ListItem(_),
+                ..
+            }) => {
+                let is_item = |line: &Line<'_>| {
+                    matches!(
+                        line,
+                        Line::Normal(Composite {
+                            style: CompositeStyle::ListItem(_),
+                            ..
+                        })
+                    )
+                };
+                // the items of loose lists are separated by blank lines
+                let len = run(lines, |line| is_item(line) || is_blank(line));
+                let len = lines[..len]
+                    .iter()
+                    .rposition(is_item)
+                    .map_or(len, |last| last + 1);
+                let items: Vec<_> = lines[..len]
+                    .iter()
+                    .filter_map(|line| match line {
+                        Line::Normal(Composite {
+                            style: CompositeStyle::ListItem(depth),
+                            compounds,
+                        }) => Some((*depth, &compounds[..])),
+                        _ => None,
+                    })
+                    .collect();
+                children.push(list(&items));
+                len
+            }
+            Line::Normal(Composite {
+                style: CompositeStyle::Quote,
+                ..
+            }) => {
+                let len = run(lines, |line| {
+                    matches!(
+                        line,
+                        Line::Normal(Composite {
+                            style: CompositeStyle::Quote,
+                            ..
+                        })
+                    )
+                });
+                children.push(mdast::Node::Blockquote(mdast::Blockquote {
+                    children: lines[..len]
+                        .iter()
+                        .filter_map(|line| match line {
+                            Line::Normal(composite) => paragraph(&composite.compounds),
+                            _ => None,
+                        })
+                        .collect(),
+                    position: None,
+                }));
+                len
+            }
+            Line::Normal(Composite {
+                style: CompositeStyle::Code,
+                ..
+            }) => {
+                let len = run(lines, |line| {
+                    matches!(
+                        line,
+                        Line::Normal(Composite {
+                            style: CompositeStyle::Code,
+                            ..
+                        })
+                    )
+                });
+                let value: Vec<String> = lines[..len]
+                    .iter()
+                    .filter_map(|line| match line {
+                        Line::Normal(composite) => Some(source(composite)),
+                        _ => None,
+                    })
+                    .collect();
+                children.push(mdast::Node::Code(mdast::Code {
+                    value: value.join("\n"),
+                    position: None,
+                    lang: None,
+                    meta: None,
+                }));
+                len
+            }
+            Line::Normal(Composite {
+                style: CompositeStyle::Header(depth),
+                compounds,
+            }) => {
+                children.push(mdast::Node::Heading(mdast::Heading {
+                    children: phrasing(compounds),
+                    position: None,
+                    depth: (*depth).clamp(1, 6),
+                }));
+                1
+            }
+            Line::Normal(Composite {
+                style: CompositeStyle::Paragraph,
+                compounds,
+            }) => {
+                children.extend(paragraph(compounds));
+                1
+            }
+            Line::TableRow(_) | Line::TableRule(_) => {
+                let len = run(lines, |line| {
+                    matches!(line, Line::TableRow(_) | Line::TableRule(_))
+                });
+                children.push(table(&lines[..len]));
+                len
+            }
+            Line::HorizontalRule => {
+                children.push(mdast::Node::ThematicBreak(mdast::ThematicBreak {
+                    position: None,
+                }));
+                1
+            }
+            Line::CodeFence(_) => 1,
+        };
+        lines = &lines[len..];
+    }
+    mdast::Node::Root(mdast::Root {
+        children,
+        position: None,
+    })
+}
+
+/// Length of the run of lines at the start matching the predicate
+fn run(lines: &[Line<'_>], pred: impl Fn(&Line<'_>) -> bool) -> usize {
+    lines
+        .iter()
+        .position(|line| !pred(line))
+        .unwrap_or(lines.len())
+}
+
+/// Check if a line is a paragraph line without text
+fn is_blank(line: &Line<'_>) -> bool {
+    matches!(line, Line::Normal(Composite { style: CompositeStyle::Paragraph, compounds })
+        if compounds.iter().all(|compound| compound.src.trim().is_empty()))
+}
+
+/// A list of the given items, nested by their depth
+fn list(items: &[(u8, &[Compound<'_>])]) -> mdast::Node {
+    let base = items.iter().map(|(depth, _)| *depth).min().unwrap_or(0);
+    let mut children: Vec<mdast::Node> = vec![];
+    let mut items = items;
+    while let Some(&(depth, compounds)) = items.first() {
+        if depth == base {
+            children.push(mdast::Node::ListItem(mdast::ListItem {
+                children: paragraph(compounds).into_iter().collect(),
+                position: None,
+                spread: false,
+                checked: None,
+            }));
+            items = &items[1..];
+            continue;
+        }
+        // the deeper items are a list nested in the previous one
+        let len = items
+            .iter()
+            .position(|(depth, _)| *depth == base)
+            .unwrap_or(items.len());
+        let sublist = list(&items[..len]);
+        match children.last_mut() {
+            Some(mdast::Node::ListItem(item)) => item.children.push(sublist),
+            _ => children.push(mdast::Node::ListItem(mdast::ListItem {
+                children: vec![sublist],
+                position: None,
+                spread: false,
+                checked: None,
+            })),
+        }
+        items = &items[len..];
+    }
+    mdast::Node::List(mdast::List {
+        children,
+        position: None,
+        ordered: false,
+        start: None,
+        spread: false,
+    })
+}
+
+/// A table from its rows and rules
+///
+/// The rows before the first rule are the header. If there is no rule, the first row is.
+fn table(lines: &[Line<'_>]) -> mdast::Node {
+    let align = lines
+        .iter()
+        .find_map(|line| match line {
+            Line::TableRule(rule) => Some(
+                rule.cells
+                    .iter()
+                    .map(|alignment| match alignment {
+                        Alignment::Left => mdast::AlignKind::Left,
+                        Alignment::Center => mdast::AlignKind::Center,
+                        Alignment::Right => mdast::AlignKind::Right,
+                        Alignment::Unspecified => mdast::AlignKind::None,
+                    })
+                    .collect(),
+            ),
+            _ => None,
+        })
+        .unwrap_or_default();
+    let children = lines
+        .iter()
+        .filter_map(|line| match line {
+            Line::TableRow(TableRow { cells }) => Some(mdast::Node::TableRow(mdast::TableRow {
+                children: cells
+                    .iter()
+                    .map(|cell| {
+                        mdast::Node::TableCell(mdast::TableCell {
+                            children: phrasing(&cell.compounds),
+                            position: None,
+                        })
+                    })
+                    .collect(),
+                position: None,
+            })),
+            _ => None,
+        })
+        .collect();
+    mdast::Node::Table(mdast::Table {
+        children,
+        position: None,
+        align,
+    })
+}
+
+/// A paragraph with the given compounds, or `None` if it is empty
+fn paragraph(compounds: &[Compound<'_>]) -> Option<mdast::Node> {
+    let children = phrasing(compounds);
+    (!children.is_empty()).then_some(mdast::Node::Paragraph(mdast::Paragraph {
+        children,
+        position: None,
+    }))
+}
+
+/// The phrasing nodes of the compounds, wrapped in the nodes giving their style
+fn phrasing(compounds: &[Compound<'_>]) -> Vec<mdast::Node> {
+    compounds
+        .iter()
+        .filter(|compound| !compound.src.is_empty())
+        .map(|compound| {
+            let mut node = if compound.code {
+                mdast::Node::InlineCode(mdast::InlineCode {
+                    value: compound.src.to_owned(),
+                    position: None,
+                })
+            } else {
+                mdast::Node::Text(mdast::Text {
+                    value: compound.src.to_owned(),
+                    position: None,
+                })
+            };
+            if compound.strikeout {
+                node = mdast::Node::Delete(mdast::Delete {
+                    children: vec![node],
+                    position: None,
+                });
+            }
+            if compound.italic {
+                node = mdast::Node::Emphasis(mdast::Emphasis {
+                    children: vec![node],
+                    position: None,
+                });
+            }
+            if compound.bold {
+                node = mdast::Node::Strong(mdast::Strong {
+                    children: vec![node],
+                    position: None,
+                });
+            }
+            node
+        })
+        .collect()
+}
+
+/// The text of a composite, ignoring the style
+fn source(composite: &Composite<'_>) -> String {
+    composite
+        .compounds
+        .iter()
+        .map(|compound| compound.src)
+        .collect()
+}
@@ -0,0 +1,488 @@
+//! Splitting of a document in sections, and caching of their conversion
+
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    hash::{Hash, Hasher},
+    sync::Arc,
+};
+
+use minimad::Text;
+
+use crate::{
+    mdast, Converter, DocumentContext, Emitter, Interner, NodeKind, Options, OwnedText,
+    ToMinimadError,
+};
+
+/// A section of a document: a heading and all the content up to the next one
+#[derive(Debug, Clone, Copy)]
+pub struct Section<'a> {
+    /// The heading of the section. `None` for the content before the first heading.
+    pub heading: Option<&'a mdast::Heading>,
+    /// The nodes of the section, heading included
+    pub nodes: &'a [mdast::Node],
+}
+
+impl<'a> Section<'a> {
+    /// Convert the section with the given converter
+    pub fn convert(
+        &self,
+        converter: &Converter,
+        interner: &'a Interner,
+    ) -> Result<Text<'a>, ToMinimadError> {
+        let mut emitter = Emitter::new(converter.options().clone(), interner);
+        emitter.context = Some(Arc::new(DocumentContext::scan_all(self.nodes)));
+        for node in self.nodes {
+            emitter.node(node)?;
+        }
+        Ok(emitter.finish())
+    }
+
+    /// Hash of the content of the section
+    ///
+    /// Positions are ignored, so the hash does not change if the section is moved in the document.
+    pub fn content_hash(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        for node in self.nodes {
+            hash_node(node, &mut hasher);
+        }
+        hasher.finish()
+    }
+}
+
+/// A section, with the slug of its heading
+#[derive(Debug, Clone)]
+pub struct SlugSection<'a> {
+    /// The section
+    pub section: Section<'a>,
+    /// The slug of the heading, unique in the document. Empty for the content before the first heading.
+    pub slug: String,
+}
+
+/// Split a document at each heading
+///
+/// If the node is not a `Root` the whole node is a single section.
+pub fn sections(ast: &mdast::Node) -> Vec<SlugSection<'_>> {
+    sections_with(ast, &SlugOptions::default())
+}
+
+/// Split a document at each heading, computing the slugs with the given options
+pub fn sections_with<'a>(ast: &'a mdast::Node, options: &SlugOptions) -> Vec<SlugSection<'a>> {
+    let children = match ast {
+        mdast::Node::Root(mdast::Root {
+            children,
+            position: _,
+        }) => &children[..],
+        other => std::slice::from_ref(other),
+    };
+
+    let mut sections = vec![];
+    let mut slugs = HashMap::new();
+    let mut start = 0;
+    for (idx, node) in children.iter().enumerate().skip(1) {
+        if matches!(node, mdast::Node::Heading(_)) {
+            sections.push(slug_section(&children[start..idx], &mut slugs, options));
+            start = idx;
+        }
+    }
+    if start < children.len() {
+        sections.push(slug_section(&children[start..], &mut slugs, options));
+    }
+    sections
+}
+
+fn slug_section<'a>(
+    nodes: &'a [mdast::Node],
+    slugs: &mut HashMap<String, usize>,
+    options: &SlugOptions,
+) -> SlugSection<'a> {
+    let heading = match nodes.first() {
+        Some(mdast::Node::Heading(heading)) => Some(heading),
+        _ => None,
+    };
+    let slug = heading.map_or_else(String::new, |heading| {
+        unique_slug(slug_with(&title(heading), options), slugs)
+    });
+    SlugSection {
+        section: Section { heading, nodes },
+        slug,
+    }
+}
+
+/// The text of a heading
+pub(crate) fn title(heading: &mdast::Heading) -> String {
+    heading.children.iter().map(ToString::to_string).collect()
+}
+
+/// Make a slug unique in the document, GitHub style
+pub(crate) fn unique_slug(slug: String, slugs: &mut HashMap<String, usize>) -> String {
+    let count = slugs.entry(slug.clone()).or_insert(0);
+    *count += 1;
+    if *count > 1 {
+        format!("{slug}-{}", *count - 1)
+    } else {
+        slug
+    }
+}
+
+/// How to find a section, see [`extract_section`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SectionSelector<'s> {
+    /// The heading with the given slug, as computed by [`sections`]
+    Slug(&'s str),
+    /// The first heading with the given text, ignoring case and surrounding spaces
+    Title(&'s str),
+}
+
+/// Find the section under a heading, up to the next heading of the same or lower depth
+///
+/// The returned nodes start with the heading. Subsections are included.
+/// ```
+/// # use mdast2minimad::{md_parse_options, sections::{extract_section, SectionSelector}};
+/// let ast = markdown::to_mdast(
+///     "# Help\n\n## Install\n\nRun it\n\n### On Linux\n\n## Usage",
+///     &md_parse_options(),
+/// )
+/// .unwrap();
+/// let install = extract_section(&ast, SectionSelector::Title("install")).unwrap();
+/// assert_eq!(install.len(), 3);
+/// ```
+pub fn extract_section<'a>(
+    ast: &'a mdast::Node,
+    selector: SectionSelector<'_>,
+) -> Option<&'a [mdast::Node]> {
+    let children = match ast {
+        mdast::Node::Root(mdast::Root {
+            children,
+            position: _,
+        }) => &children[..],
+        other => std::slice::from_ref(other),
+    };
+    let mut slugs = HashMap::new();
+    let (start, depth) = children.iter().enumerate().find_map(|(idx, node)| {
+        let mdast::Node::Heading(heading) = node else {
+            return None;
+        };
+        let title = title(heading);
+        let found = match selector {
+            SectionSelector::Slug(slug) => {
+                unique_slug(slug_with(&title, &SlugOptions::default()), &mut slugs) == slug
+            }
+            SectionSelector::Title(wanted) => {
+                title.trim().to_lowercase() == wanted.trim().to_lowercase()
+            }
+        };
+        found.then_some((idx, heading.depth))
+    })?;
+    let end = children[start + 1..]
+        .iter()
+        .position(|node| matches!(node, mdast::Node::Heading(heading) if heading.depth <= depth))
+        .map_or(children.len(), |len| start + 1 + len);
+    Some(&children[start..end])
+}
+
+/// Convert only the section under a heading, see [`extract_section`]
+///
+/// Returns `None` if the section is not found.
+pub fn to_minimad_section<'a>(
+    ast: &'a mdast::Node,
+    selector: SectionSelector<'_>,
+    options: Options,
+    interner: &'a Interner,
+) -> Result<Option<Text<'a>>, ToMinimadError> {
+    extract_section(ast, selector)
+        .map(|nodes| convert_nodes(nodes, options, interner))
+        .transpose()
+}
+
+/// The heading of a section converted by [`to_minimad_sections`]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct HeadingInfo {
+    /// Depth of the heading. `0` for the content before the first heading.
+    pub depth: u8,
+    /// Text of the heading
+    pub title: String,
+    /// The slug of the heading, unique in the document
+    pub slug: String,
+}
+
+/// Split a document at its top-level headings, converting each section on its own
+///
+/// The top-level headings are the shallowest in the document: deeper headings stay in the section
+/// that contains them. The content before the first heading, if any, is returned first with an
+/// empty [`HeadingInfo`].
+/// ```
+/// # use mdast2minimad::{md_parse_options, sections::to_minimad_sections, Interner};
+/// let ast = markdown::to_mdast(
+///     "Intro\n\n## Install\n\n### On Linux\n\n## Usage",
+///     &md_parse_options(),
+/// )
+/// .unwrap();
+/// let interner = Interner::new();
+/// let sections = to_minimad_sections(&ast, &interner).unwrap();
+/// let slugs: Vec<_> = sections.iter().map(|(info, _)| &*info.slug).collect();
+/// assert_eq!(slugs, ["", "install", "usage"]);
+/// ```
+pub fn to_minimad_sections<'a>(
+    ast: &'a mdast::Node,
+    interner: &'a Interner,
+) -> Result<Vec<(HeadingInfo, Text<'a>)>, ToMinimadError> {
+    to_minimad_sections_with_options(ast, Options::default(), interner)
+}
+
+/// Split a document at its top-level headings, converting each section with the given options
+///
+/// See [`to_minimad_sections`].
+pub fn to_minimad_sections_with_options<'a>(
+    ast: &'a mdast::Node,
+    options: Options,
+    interner: &'a Interner,
+) -> Result<Vec<(HeadingInfo, Text<'a>)>, ToMinimadError> {
+    let children = match ast {
+        mdast::Node::Root(mdast::Root {
+            children,
+            position: _,
+        }) => &children[..],
+        other => std::slice::from_ref(other),
+    };
+    let top = children
+        .iter()
+        .filter_map(|node| match node {
+            mdast::Node::Heading(heading) => Some(heading.depth),
+            _ => None,
+        })
+        .min();
+
+    let mut sections = vec![];
+    let mut slugs = HashMap::new();
+    let mut info = HeadingInfo::default();
+    let mut start = 0;
+    for (idx, node) in children.iter().enumerate() {
+        let mdast::Node::Heading(heading) = node else {
+            continue;
+        };
+        // every heading takes its slug, so the slugs match the anchors of the whole document
+        let title = title(heading);
+        let slug = unique_slug(slug_with(&title, &SlugOptions::default()), &mut slugs);
+        if Some(heading.depth) != top {
+            continue;
+        }
+        if idx > start {
+            let text = convert_nodes(&children[start..idx], options.clone(), interner)?;
+            sections.push((info, text));
+        }
+        info = HeadingInfo {
+            depth: heading.depth,
+            title,
+            slug,
+        };
+        start = idx;
+    }
+    if start < children.len() {
+        sections.push((info, convert_nodes(&children[start..], options, interner)?));
+    }
+    Ok(sections)
+}
+
+/// Convert a sequence of nodes as a single text
+fn convert_nodes<'a>(
+    nodes: &'a [mdast::Node],
+    options: Options,
+    interner: &'a Interner,
+) -> Result<Text<'a>, ToMinimadError> {
+    let mut emitter = Emitter::new(options, interner);
+    emitter.context = Some(Arc::new(DocumentContext::scan_all(nodes)));
+    for node in nodes {
+        emitter.node(node)?;
+    }
+    Ok(emitter.finish())
+}
+
+/// Normalization applied when computing a slug
+///
+/// The default matches the anchors generated by GitHub and mdBook.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SlugOptions {
+    /// If emoji should be kept instead of removed
+    pub keep_emoji: bool,
+    /// If runs of separators (`-`) should be collapsed into one, and removed at the ends
+    pub collapse_separators: bool,
+    /// If accented and non latin letters should be replaced by their ASCII transliteration
+    #[cfg(feature = "transliterate")]
+    pub transliterate: bool,
+}
+
+/// Compute the GitHub style slug of a heading text
+pub fn slug(title: &str) -> String {
+    slug_with(title, &SlugOptions::default())
+}
+
+/// Compute the slug of a heading text with the given normalization
+pub fn slug_with(title: &str, options: &SlugOptions) -> String {
+    let title: String = title
+        .trim()
+        .chars()
+        .filter(|&ch| options.keep_emoji || !is_emoji(ch))
+        .collect();
+    #[cfg(feature = "transliterate")]
+    let title = if options.transliterate {
+        deunicode::deunicode(&title)
+    } else {
+        title
+    };
+    let slug: String = title
+        .chars()
+        .filter_map(|ch| match ch {
+            ' ' => Some('-'),
+            '-' | '_' => Some(ch),
+            ch if ch.is_alphanumeric() => Some(ch),
+            ch if options.keep_emoji && is_emoji(ch) => Some(ch),
+            _ => None,
+        })
+        .flat_map(char::to_lowercase)
+        .collect();
+    if !options.collapse_separators {
+        return slug;
+    }
+    slug.split('-')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Check if a char is part of an emoji
+pub(crate) fn is_emoji(ch: char) -> bool {
+    matches!(ch as u32,
+        0x1F000..=0x1FAFF // pictographs, emoticons, symbols, flags
+        | 0x2600..=0x27BF // miscellaneous symbols and dingbats
+        | 0x2B00..=0x2BFF // arrows and stars
+        | 0xFE0F // emoji presentation selector
+        | 0x200D // zero width joiner
+    )
+}
+
+/// Hash the content of a node, ignoring positions
+///
+/// The tree is walked with an explicit stack, so deep documents do not overflow the call stack.
+pub(crate) fn hash_node(node: &mdast::Node, hasher: &mut impl Hasher) {
+    let mut stack = vec![node];
+    while let Some(node) = stack.pop() {
+        hash_fields(node, hasher);
+        match node.children() {
+            Some(children) => {
+                children.len().hash(hasher);
+                stack.extend(children.iter().rev());
+            }
+            // Literal nodes
+            None => node.to_string().hash(hasher),
+        }
+    }
+}
+
+/// Hash the kind and the attributes of a single node
+fn hash_fields(node: &mdast::Node, hasher: &mut impl Hasher) {
+    NodeKind::of(node).hash(hasher);
+    match node {
+        mdast::Node::Heading(mdast::Heading { depth, .. }) => depth.hash(hasher),
+        mdast::Node::List(mdast::List {
+            ordered,
+            start,
+            spread,
+            ..
+        }) => (ordered, start, spread).hash(hasher),
+        mdast::Node::ListItem(mdast::ListItem {
+            spread, checked, ..
+        }) => (spread, checked).hash(hasher),
+        mdast::Node::Code(mdast::Code { lang, meta, .. }) => (lang, meta).hash(hasher),
+        mdast::Node::Link(mdast::Link { url, title, .. }) => (url, title).hash(hasher),
+        mdast::Node::Image(mdast::Image {
+            url, title, alt, ..
+        }) => (url, title, alt).hash(hasher),
+        mdast::Node::Table(mdast::Table { align, .. }) => {
+            for align in align {
+                (*align as u8).hash(hasher)
+            }
+        }
+        // references and definitions have no children: their content is in their fields
+        mdast::Node::FootnoteReference(mdast::FootnoteReference {
+            identifier, label, ..
+        })
+        | mdast::Node::FootnoteDefinition(mdast::FootnoteDefinition {
+            identifier, label, ..
+        }) => (identifier, label).hash(hasher),
+        mdast::Node::LinkReference(mdast::LinkReference {
+            identifier,
+            label,
+            reference_kind,
+            ..
+        }) => (identifier, label, *reference_kind as u8).hash(hasher),
+        mdast::Node::ImageReference(mdast::ImageReference {
+            identifier,
+            label,
+            reference_kind,
+            alt,
+            ..
+        }) => (identifier, label, *reference_kind as u8, alt).hash(hasher),
+        mdast::Node::Definition(mdast::Definition {
+            identifier,
+            label,
+            url,
+            title,
+            ..
+        }) => (identifier, label, url, title).hash(hasher),
+        _ => (),
+    }
+}
+
+/// Cache of the conversion of sections
+///
+/// Each section is cached by its slug and content hash, so a section is converted again only if
+/// its content changed. The cached texts are owned, so the cache outlives the documents.
+#[derive(Debug, Clone, Default)]
+pub struct SectionCache {
+    converter: Converter,
+    entries: HashMap<String, (u64, OwnedText)>,
+}
+
+impl SectionCache {
+    /// Create an empty cache, converting with the given converter
+    pub fn new(converter: Converter) -> Self {
+        Self {
+            converter,
+            entries: HashMap::new(),
+        }
+    }
+
+    /// Get the conversion of a section, converting it if it is not cached or if it changed
+    pub fn get(&mut self, section: &SlugSection<'_>) -> Result<&OwnedText, ToMinimadError> {
+        let hash = section.section.content_hash();
+        if self
+            .entries
+            .get(&section.slug)
+            .is_none_or(|(cached, _)| *cached != hash)
+        {
+            let interner = Interner::new();
+            let text = OwnedText::new(&section.section.convert(&self.converter, &interner)?);
+            self.entries.insert(section.slug.clone(), (hash, text));
+        }
+        Ok(&self.entries[&section.slug].1)
+    }
+
+    /// Check if a section is cached and up to date
+    pub fn contains(&self, section: &SlugSection<'_>) -> bool {
+        self.entries
+            .get(&section.slug)
+            .is_some_and(|(cached, _)| *cached == section.section.content_hash())
+    }
+
+    /// Drop the cached conversion of the section with the given slug
+    ///
+    /// Return `true` if the section was cached.
+    pub fn invalidate(&mut self, slug: &str) -> bool {
+        self.entries.remove(slug).is_some()
+    }
+
+    /// Drop all the cached conversions
+    pub fn clear(&mut self) {
+        self.entries.clear()
+    }
+}
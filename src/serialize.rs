@@ -0,0 +1,300 @@
+//! Conversion of a minimad text to a markdown source
+
+use minimad::{Alignment, Composite, CompositeStyle, Compound, Line, TableRow, Text};
+
+/// Write a minimad text as a markdown source
+///
+/// This lets the applications that edit the converted text, e.g. injecting lines, save it back.
+/// Each line keeps its style: headers become ATX headings, list items `-` items and quotes `>`
+/// lines. The runs of code lines are fenced, and tables are written with pipes. Blocks that would
+/// merge in markdown, like two paragraph lines, are separated by an empty line.
+///
+/// Parsing the result with [`md_parse_options`](crate::md_parse_options) and converting it gives
+/// back the same lines, but for the empty ones.
+/// ```
+/// # use mdast2minimad::to_markdown;
+/// # use minimad::{Compound, Line, Text};
+/// let text = Text {
+///     lines: vec![
+///         Line::new_header(1, vec![Compound::raw_str("Title")]),
+///         Line::new_paragraph(vec![
+///             Compound::raw_str("Some "),
+///             Compound::raw_str("bold").bold(),
+///             Compound::raw_str(" text*"),
+///         ]),
+///     ],
+/// };
+/// assert_eq!(to_markdown(&text), "# Title\n\nSome **bold** text\\*\n");
+/// ```
+pub fn to_markdown(text: &Text<'_>) -> String {
+    let mut markdown = String::new();
+    let mut previous: Option<Block> = None;
+    let mut lines = text.lines.iter().peekable();
+    while let Some(line) = lines.next() {
+        let Some(block) = Block::of(line) else {
+            continue;
+        };
+        match previous {
+            // quote lines would be joined in a single paragraph
+            Some(Block::Quote) if block == Block::Quote => markdown.push_str(">\n"),
+            Some(previous)
+                if previous != Block::Blank
+                    && block != Block::Blank
+                    && block.separated_from(previous) =>
+            {
+                markdown.push('\n')
+            }
+            _ => (),
+        }
+        previous = Some(block);
+        match line {
+            Line::Normal(Composite {
+                style: CompositeStyle::Code,
+                ..
+            }) => {
+                // the whole run of code lines is fenced at once
+                let mut code = vec![line];
+                while let Some(line) = lines.next_if(|line| Block::of(line) == Some(Block::Code)) {
+                    code.push(line);
+                }
+                let code: Vec<String> = code
+                    .into_iter()
+                    .filter_map(|line| match line {
+                        Line::Normal(composite) => Some(source(composite)),
+                        _ => None,
+                    })
+                    .collect();
+                let fence = "`".repeat(longest_run(&code.concat(), '`').max(2) + 1);
+                markdown.push_str(&fence);
+                markdown.push('\n');
+                for line in code {
+                    markdown.push_str(&line);
+                    markdown.push('\n');
+                }
+                markdown.push_str(&fence);
+            }
+            Line::Normal(Composite { style, compounds }) => {
+                match style {
+                    CompositeStyle::Header(depth) => {
+                        markdown.push_str(&"#".repeat((*depth).clamp(1, 6).into()));
+                        markdown.push(' ');
+                    }
+                    CompositeStyle::ListItem(depth) => {
+                        markdown.push_str(&"  ".repeat((*depth).into()));
+                        markdown.push_str("- ");
+                    }
+                    CompositeStyle::Quote => markdown.push_str("> "),
+                    CompositeStyle::Paragraph | CompositeStyle::Code => (),
+                }
+                markdown.push_str(&escape_line_start(inline(compounds)));
+            }
+            Line::TableRow(TableRow { cells }) => {
+                markdown.push('|');
+                for cell in cells {
+                    markdown.push(' ');
+                    markdown.push_str(&inline(&cell.compounds));
+                    markdown.push_str(" |");
+                }
+            }
+            Line::TableRule(rule) => {
+                markdown.push('|');
+                for alignment in &rule.cells {
+                    markdown.push_str(match alignment {
+                        Alignment::Left => ":--",
+                        Alignment::Center => ":-:",
+                        Alignment::Right => "--:",
+                        Alignment::Unspecified => "---",
+                    });
+                    markdown.push('|');
+                }
+            }
+            Line::HorizontalRule => markdown.push_str("***"),
+            Line::CodeFence(_) => (),
+        }
+        markdown.push('\n');
+    }
+    markdown
+}
+
+/// The kind of block a line is part of
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Block {
+    Blank,
+    Paragraph,
+    Header,
+    ListItem,
+    Quote,
+    Code,
+    Table,
+    Rule,
+}
+impl Block {
+    /// The block of a line, or `None` for the lines that are not written
+    fn of(line: &Line<'_>) -> Option<Self> {
+        Some(match line {
+            Line::Normal(Composite {
+                style: CompositeStyle::Paragraph,
+                compounds,
+            }) if compounds
+                .iter()
+                .all(|compound| compound.src.trim().is_empty()) =>
+            {
+                Self::Blank
+            }
+            Line::Normal(Composite { style, .. }) => match style {
+                CompositeStyle::Paragraph => Self::Paragraph,
+                CompositeStyle::Header(_) => Self::Header,
+                CompositeStyle::ListItem(_) => Self::ListItem,
+                CompositeStyle::Quote => Self::Quote,
+                CompositeStyle::Code => Self::Code,
+            },
+            Line::TableRow(_) | Line::TableRule(_) => Self::Table,
+            Line::HorizontalRule => Self::Rule,
+            Line::CodeFence(_) => return None,
+        })
+    }
+
+    /// If an empty line is needed between a line of `previous` and one of `self`
+    fn separated_from(self, previous: Self) -> bool {
+        self != previous || self == Self::Paragraph
+    }
+}
+
+/// A marker of inline style
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Marker {
+    Bold,
+    Italic,
+    Strikeout,
+}
+impl Marker {
+    fn as_str(self) -> &'static str {
+        match self {
+            Marker::Bold => "**",
+            Marker::Italic => "*",
+            Marker::Strikeout => "~~",
+        }
+    }
+
+    fn is_set(self, compound: &Compound<'_>) -> bool {
+        match self {
+            Marker::Bold => compound.bold,
+            Marker::Italic => compound.italic,
+            Marker::Strikeout => compound.strikeout,
+        }
+    }
+}
+
+/// Write compounds as markdown inline content
+///
+/// The markers are kept next to the text: markdown does not recognize them next to spaces.
+fn inline(compounds: &[Compound<'_>]) -> String {
+    let mut markdown = String::new();
+    // markers open, in order
+    let mut open: Vec<Marker> = vec![];
+    // spaces not written yet, as they must go outside of the closing markers
+    let mut spaces = String::new();
+    for compound in compounds {
+        if compound.src.trim().is_empty() && !compound.code {
+            spaces.push_str(compound.src);
+            continue;
+        }
+        let (lead, core, trail) = if compound.code {
+            ("", code_span(compound.src), "")
+        } else {
+            let start = compound.src.len() - compound.src.trim_start().len();
+            let end = compound.src.trim_end().len();
+            (
+                &compound.src[..start],
+                escape(&compound.src[start..end]),
+                &compound.src[end..],
+            )
+        };
+        // close the markers that are not set anymore, and all the ones opened after them
+        if let Some(idx) = open.iter().position(|marker| !marker.is_set(compound)) {
+            for marker in open.drain(idx..).rev() {
+                markdown.push_str(marker.as_str());
+            }
+        }
+        markdown.push_str(&spaces);
+        markdown.push_str(lead);
+        spaces.clear();
+        for marker in [Marker::Strikeout, Marker::Bold, Marker::Italic] {
+            if marker.is_set(compound) && !open.contains(&marker) {
+                markdown.push_str(marker.as_str());
+                open.push(marker);
+            }
+        }
+        markdown.push_str(&core);
+        spaces.push_str(trail);
+    }
+    for marker in open.into_iter().rev() {
+        markdown.push_str(marker.as_str());
+    }
+    markdown.push_str(&spaces);
+    markdown
+}
+
+/// Escape the characters that markdown would parse as markup
+fn escape(text: &str) -> String {
+    const SPECIAL: [char; 12] = ['\\', '`', '*', '_', '[', ']', '<', '>', '~', '|', '#', '&'];
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        if SPECIAL.contains(&ch) {
+            escaped.push('\\');
+        }
+        escaped.push(ch);
+    }
+    escaped
+}
+
+/// Escape the start of a line that markdown would parse as a block marker
+///
+/// The first char of the indentation is written as a char reference, so the line does not become
+/// a code block, and a leading list, rule or setext marker is escaped.
+fn escape_line_start(line: String) -> String {
+    if line.trim().is_empty() {
+        return line;
+    }
+    if let Some(indent) = line.chars().next().filter(|&ch| ch == ' ' || ch == '\t') {
+        return format!("&#{};{}", u32::from(indent), &line[1..]);
+    }
+    let digits = line.len()
+        - line
+            .trim_start_matches(|ch: char| ch.is_ascii_digit())
+            .len();
+    let marker = if line.starts_with(['-', '+', '=']) {
+        0
+    } else if digits > 0 && line[digits..].starts_with(['.', ')']) {
+        digits
+    } else {
+        return line;
+    };
+    format!("{}\\{}", &line[..marker], &line[marker..])
+}
+
+/// A code span with the given content
+fn code_span(code: &str) -> String {
+    let fence = "`".repeat(longest_run(code, '`') + 1);
+    // the content is padded if it would merge with the fence, and markdown removes the padding
+    let pad = if code.starts_with('`') || code.ends_with('`') {
+        " "
+    } else {
+        ""
+    };
+    format!("{fence}{pad}{code}{pad}{fence}")
+}
+
+/// Length of the longest run of a char in a text
+fn longest_run(text: &str, ch: char) -> usize {
+    text.split(|c| c != ch).map(str::len).max().unwrap_or(0)
+}
+
+/// The text of a composite, ignoring the style
+fn source(composite: &Composite<'_>) -> String {
+    composite
+        .compounds
+        .iter()
+        .map(|compound| compound.src)
+        .collect()
+}
@@ -0,0 +1,92 @@
+//! Overview of a document, made only of its headings
+//!
+//! The skeleton is cheap to produce even for very large documents, and can be shown while the full
+//! conversion is running.
+
+use minimad::Line;
+
+use crate::{mdast, Emitter, Interner, Options, ToMinimadError, WhileEmitting};
+
+/// Options for the rendering of a skeleton
+#[derive(Debug, Clone, Default)]
+pub struct SkeletonOptions {
+    /// Options for the conversion
+    pub options: Options,
+    /// If the first sentence of each section should be kept
+    ///
+    /// The sentence is taken from the paragraph right after the heading, if any.
+    pub first_sentence: bool,
+}
+
+/// Convert the headings and horizontal rules of a document to a minimad Text
+pub fn to_minimad<'a>(
+    ast: &'a mdast::Node,
+    options: &SkeletonOptions,
+    interner: &'a Interner,
+) -> Result<minimad::Text<'a>, ToMinimadError> {
+    let children = match ast {
+        mdast::Node::Root(mdast::Root {
+            children,
+            position: _,
+        }) => &children[..],
+        other => std::slice::from_ref(other),
+    };
+
+    let mut emitter = Emitter::new(options.options.clone(), interner);
+    // if the next node starts a section
+    let mut section_start = true;
+    for child in children {
+        match child {
+            mdast::Node::Heading(_) | mdast::Node::ThematicBreak(_) => {
+                emitter.node(child).while_emitting(ast)?;
+            }
+            mdast::Node::Paragraph(_) if section_start && options.first_sentence => {
+                let start = emitter.state.lines.len();
+                emitter.node(child).while_emitting(ast)?;
+                first_sentence(&mut emitter.state.lines, start);
+            }
+            _ => (),
+        }
+        section_start = matches!(child, mdast::Node::Heading(_));
+    }
+    Ok(emitter.finish())
+}
+
+/// Cut the paragraph emitted from `start` after its first sentence
+///
+/// If no sentence end is found, the first line is kept.
+fn first_sentence(lines: &mut Vec<Line<'_>>, start: usize) {
+    for line_idx in start..lines.len() {
+        let Line::Normal(composite) = &mut lines[line_idx] else {
+            continue;
+        };
+        // skip the spacing before the paragraph
+        if composite.compounds.is_empty() {
+            continue;
+        }
+        let compounds = &mut composite.compounds;
+        let end = compounds
+            .iter()
+            .enumerate()
+            .find_map(|(idx, compound)| sentence_end(compound.src).map(|end| (idx, end)));
+        if let Some((idx, end)) = end {
+            compounds[idx].src = &compounds[idx].src[..end];
+            compounds.truncate(idx + 1);
+        }
+        lines.truncate(line_idx + 1);
+        return;
+    }
+}
+
+/// Find the end of the first sentence in a string, including the punctuation
+fn sentence_end(src: &str) -> Option<usize> {
+    let mut chars = src.char_indices().peekable();
+    while let Some((idx, ch)) = chars.next() {
+        if matches!(ch, '.' | '!' | '?')
+            && chars.peek().is_none_or(|(_, next)| next.is_whitespace())
+        {
+            return Some(idx + ch.len_utf8());
+        }
+    }
+    None
+}
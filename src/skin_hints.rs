@@ -0,0 +1,86 @@
+//! Hints for the styling of a document
+
+use crate::mdast;
+
+/// The constructs appearing in a document, to adjust the skin used to render it
+///
+/// E.g. an application can give the headings distinguishable styles only if the document uses
+/// many levels, or skip the setup of the table borders for documents without tables.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SkinHints {
+    /// If some text is bold
+    pub bold: bool,
+    /// If some text is italic
+    pub italic: bool,
+    /// If some text is struck out
+    pub strikeout: bool,
+    /// If there is inline code, math included
+    pub inline_code: bool,
+    /// If there are code blocks, math included
+    pub code_blocks: bool,
+    /// If there are tables
+    pub tables: bool,
+    /// If there are quotes
+    pub quotes: bool,
+    /// If there are lists
+    pub lists: bool,
+    /// If there are horizontal rules
+    pub rules: bool,
+    /// The depths of the headings used, `heading_depths[0]` being `#`
+    pub heading_depths: [bool; 6],
+}
+
+impl SkinHints {
+    /// The depth of the deepest heading, if any
+    ///
+    /// This is the depth in the source, before [`Options::heading_offset`](crate::Options) and
+    /// the other options changing the headings.
+    pub fn deepest_heading(&self) -> Option<u8> {
+        self.heading_depths
+            .iter()
+            .rposition(|&used| used)
+            .map(|idx| idx as u8 + 1)
+    }
+
+    /// The number of heading levels used
+    pub fn heading_levels(&self) -> usize {
+        self.heading_depths.iter().filter(|&&used| used).count()
+    }
+}
+
+/// Find which constructs appear in a document
+///
+/// ```
+/// # use mdast2minimad::{md_parse_options, skin_hints};
+/// let ast = markdown::to_mdast("# Title\n\n#### Deep\n\nSome `code`", &md_parse_options()).unwrap();
+/// let hints = skin_hints(&ast);
+/// assert_eq!(hints.deepest_heading(), Some(4));
+/// assert!(hints.inline_code && !hints.tables);
+/// ```
+pub fn skin_hints(ast: &mdast::Node) -> SkinHints {
+    let mut hints = SkinHints::default();
+    // the AST is walked without recursion, so deep documents cannot overflow the stack
+    let mut stack = vec![ast];
+    while let Some(node) = stack.pop() {
+        match node {
+            mdast::Node::Strong(_) => hints.bold = true,
+            mdast::Node::Emphasis(_) => hints.italic = true,
+            mdast::Node::Delete(_) => hints.strikeout = true,
+            mdast::Node::InlineCode(_) | mdast::Node::InlineMath(_) => hints.inline_code = true,
+            mdast::Node::Code(_) | mdast::Node::Math(_) => hints.code_blocks = true,
+            mdast::Node::Table(_) => hints.tables = true,
+            mdast::Node::Blockquote(_) => hints.quotes = true,
+            mdast::Node::List(_) => hints.lists = true,
+            mdast::Node::ThematicBreak(_) => hints.rules = true,
+            mdast::Node::Heading(heading) => {
+                let depth = usize::from(heading.depth.clamp(1, 6));
+                hints.heading_depths[depth - 1] = true;
+            }
+            _ => (),
+        }
+        if let Some(children) = node.children() {
+            stack.extend(children.iter().rev());
+        }
+    }
+    hints
+}
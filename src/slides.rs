@@ -0,0 +1,77 @@
+//! Presentation of a document as slides
+//!
+//! As in terminal slideshow tools, the slides are separated by horizontal rules (`---`). The
+//! first heading of each slide is its title, and can be styled differently from the other
+//! headings.
+//!
+//! ```
+//! # use mdast2minimad::{md_parse_options, slides::{slides, SlideOptions}, Interner};
+//! let interner = Interner::new();
+//! let ast = markdown::to_mdast(
+//!     "# Welcome\n\nHello\n\n---\n\n# Agenda\n\n- one\n- two",
+//!     &md_parse_options(),
+//! )
+//! .unwrap();
+//! let slides = slides(&ast, &SlideOptions::default(), &interner).unwrap();
+//! assert_eq!(slides.len(), 2);
+//! ```
+
+use minimad::Text;
+
+use crate::{
+    mdast, without_frontmatter, Emitter, Interner, Options, Styling, ToMinimadError, WhileEmitting,
+};
+
+/// Options for the rendering of slides
+#[derive(Debug, Clone, Default)]
+pub struct SlideOptions {
+    /// Options for the conversion of each slide
+    pub options: Options,
+    /// Styling of the title of each slide, its first heading
+    ///
+    /// If `None` the title is styled as the other headings of its depth.
+    pub title_style: Option<Styling>,
+}
+
+/// Convert a document to slides, splitting it at the horizontal rules
+///
+/// Each slide is converted on its own: footnotes and link appendixes are at the end of the slide
+/// that references them. Empty slides are dropped.
+pub fn slides<'a>(
+    ast: &'a mdast::Node,
+    options: &SlideOptions,
+    interner: &'a Interner,
+) -> Result<Vec<Text<'a>>, ToMinimadError> {
+    let children = match ast {
+        mdast::Node::Root(mdast::Root {
+            children,
+            position: _,
+        }) => without_frontmatter(children),
+        other => std::slice::from_ref(other),
+    };
+    children
+        .split(|child| matches!(child, mdast::Node::ThematicBreak(_)))
+        .filter(|slide| !slide.is_empty())
+        .map(|slide| {
+            let mut emitter = Emitter::new(options.options.clone(), interner);
+            emitter.state.flow();
+            let mut title = options.title_style;
+            for child in slide {
+                match (child, title) {
+                    (mdast::Node::Heading(heading), Some(title_style)) => {
+                        let slot = usize::from(heading.depth.clamp(1, 6)) - 1;
+                        let heading_style = emitter.options.heading_style[slot];
+                        emitter.options.heading_style[slot] = title_style;
+                        let res = emitter.node(child);
+                        emitter.options.heading_style[slot] = heading_style;
+                        title = None;
+                        res
+                    }
+                    _ => emitter.node(child),
+                }
+                .while_emitting(ast)?;
+            }
+            Ok(emitter.finish())
+        })
+        .collect()
+}
@@ -0,0 +1,34 @@
+//! Rendering on the terminal with `termimad`
+
+use termimad::{FmtText, MadSkin};
+
+use crate::{mdast, to_minimad, Interner, ToMinimadError};
+
+/// Convert the markdown AST and format it with the given skin
+///
+/// The text is wrapped at `width` columns, or not wrapped if `None`. The result contains the
+/// escape sequences of the skin, and can be printed directly.
+/// ```
+/// # use mdast2minimad::{md_parse_options, render_to_string};
+/// let ast = markdown::to_mdast("Some *text*", &md_parse_options()).unwrap();
+/// let rendered = render_to_string(&ast, &termimad::MadSkin::no_style(), Some(80)).unwrap();
+/// assert_eq!(rendered.trim_end(), "Some text");
+/// ```
+pub fn render_to_string(
+    ast: &mdast::Node,
+    skin: &MadSkin,
+    width: Option<usize>,
+) -> Result<String, ToMinimadError> {
+    let interner = Interner::new();
+    let text = to_minimad(ast, &interner)?;
+    Ok(FmtText::from_text(skin, text, width).to_string())
+}
+
+/// Convert the markdown AST and print it on the standard output with the given skin
+///
+/// The text is wrapped at the width of the terminal.
+pub fn print(ast: &mdast::Node, skin: &MadSkin) -> Result<(), ToMinimadError> {
+    let width = termimad::terminal_size().0 as usize;
+    print!("{}", render_to_string(ast, skin, Some(width))?);
+    Ok(())
+}
@@ -0,0 +1,114 @@
+//! Table of contents of a document
+//!
+//! ```
+//! # use mdast2minimad::{md_parse_options, toc::{toc, TocOptions}, Interner};
+//! # use minimad::{Compound, Line};
+//! let interner = Interner::new();
+//! let ast = markdown::to_mdast(
+//!     "# Guide\n\n## Install\n\n## Usage\n\n### Flags",
+//!     &md_parse_options(),
+//! )
+//! .unwrap();
+//! let text = toc(&ast, &TocOptions::default(), &interner).unwrap();
+//! assert_eq!(
+//!     text.lines,
+//!     [
+//!         Line::new_list_item(0, vec![Compound::raw_str("Guide")]),
+//!         Line::new_list_item(1, vec![Compound::raw_str("Install")]),
+//!         Line::new_list_item(1, vec![Compound::raw_str("Usage")]),
+//!         Line::new_list_item(2, vec![Compound::raw_str("Flags")]),
+//!     ]
+//! );
+//! ```
+
+use minimad::{CompositeStyle, Compound, Text};
+
+use crate::{mdast, Emitter, Interner, Numbering, Options, ToMinimadError, WhileEmitting};
+
+/// Options for the rendering of a table of contents
+#[derive(Debug, Clone)]
+pub struct TocOptions {
+    /// Options for the conversion of the titles
+    pub options: Options,
+    /// Depth of the deepest headings listed
+    pub max_depth: u8,
+    /// If set, the titles are numbered instead of bulleted
+    ///
+    /// The numbering is prefixed with [`Options::list_continuation`] once for each level.
+    pub numbering: Option<Numbering>,
+}
+
+impl Default for TocOptions {
+    fn default() -> Self {
+        Self {
+            options: Options::default(),
+            max_depth: 6,
+            numbering: None,
+        }
+    }
+}
+
+/// Convert the headings of a document to a nested list of titles
+///
+/// Only the headings at the top level of the document are listed, not the ones inside lists or
+/// quotes. The shallowest heading is at the first level of the list.
+pub fn toc<'a>(
+    ast: &'a mdast::Node,
+    options: &TocOptions,
+    interner: &'a Interner,
+) -> Result<Text<'a>, ToMinimadError> {
+    let children = match ast {
+        mdast::Node::Root(mdast::Root {
+            children,
+            position: _,
+        }) => &children[..],
+        other => std::slice::from_ref(other),
+    };
+    let headings: Vec<_> = children
+        .iter()
+        .filter_map(|child| match child {
+            mdast::Node::Heading(heading) if heading.depth <= options.max_depth => {
+                Some((child, heading))
+            }
+            _ => None,
+        })
+        .collect();
+    let top = headings
+        .iter()
+        .map(|(_, heading)| heading.depth)
+        .min()
+        .unwrap_or(1);
+
+    let mut emitter = Emitter::new(options.options.clone(), interner);
+    emitter.state.flow();
+    // the number of the last heading at each level
+    let mut numbers: Vec<usize> = vec![];
+    for (node, heading) in headings {
+        let level = usize::from(heading.depth - top);
+        numbers.resize(level + 1, 0);
+        numbers[level] += 1;
+        let style = match options.numbering {
+            Some(_) => CompositeStyle::Paragraph,
+            None => CompositeStyle::ListItem(level as u8),
+        };
+        let res = emitter
+            .phrasing(style, false, |emitter| {
+                if let Some(numbering) = options.numbering {
+                    let marker = numbering.marker(&numbers[..level], numbers[level]);
+                    let prefix = emitter.options.list_continuation.repeat(level) + &marker + " ";
+                    let prefix = emitter.str(prefix.into());
+                    emitter.state.line().push(Compound::raw_str(prefix));
+                }
+                for child in &heading.children {
+                    emitter.node(child)?;
+                }
+                Ok(())
+            })
+            .while_emitting(node);
+        match ast {
+            mdast::Node::Root(_) => res.while_emitting(ast)?,
+            _ => res?,
+        }
+    }
+    Ok(emitter.finish())
+}
@@ -0,0 +1,405 @@
+//! Post-processing of the converted text
+//!
+//! The transforms in [`Options::post`](crate::Options::post) are applied in order to the whole
+//! text, once the conversion is complete:
+//! ```
+//! # use mdast2minimad::{md_parse_options, transform::{Indent, TrimTrailingBlankLines}, Converter, Interner};
+//! # use minimad::{Compound, Line};
+//! let interner = Interner::new();
+//! let ast = markdown::to_mdast("# Title\n\nText", &md_parse_options()).unwrap();
+//! let converter = Converter::builder()
+//!     .post(Indent("  "))
+//!     .post(TrimTrailingBlankLines)
+//!     .build();
+//! assert_eq!(
+//!     converter.convert(&ast, &interner).unwrap().lines.last(),
+//!     Some(&Line::new_paragraph(vec![Compound::raw_str("  "), Compound::raw_str("Text")]))
+//! );
+//! ```
+//!
+//! The passes rewriting the text of the compounds (smart punctuation, emoji, transliteration...)
+//! are applied with [`Pass`], that leaves the code alone:
+//! ```
+//! # use mdast2minimad::{md_parse_options, transform::{Pass, SmartPunctuation}, Converter, Interner};
+//! # use minimad::{Compound, Line};
+//! let interner = Interner::new();
+//! let ast = markdown::to_mdast("Wait... `a--b`", &md_parse_options()).unwrap();
+//! let converter = Converter::builder().post(Pass::new(SmartPunctuation)).build();
+//! assert_eq!(
+//!     converter.convert(&ast, &interner).unwrap().lines[0],
+//!     Line::new_paragraph(vec![Compound::raw_str("Wait… "), Compound::raw_str("a--b").code()])
+//! );
+//! ```
+
+use std::{borrow::Cow, mem, ops::Range};
+
+use minimad::{Composite, CompositeStyle, Compound, Line, Text};
+
+use crate::{merge_compounds, sections::is_emoji, Interner, Styling};
+
+/// A transformation of the converted text
+pub trait TextTransform: Send + Sync {
+    /// Transform the text, storing the synthesized strings in the interner
+    fn apply<'a>(&self, text: &mut Text<'a>, interner: &'a Interner);
+}
+
+/// Merge the consecutive compounds with the same style
+///
+/// This is the same as [`Options::merge_compounds`](crate::Options::merge_compounds).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MergeCompounds;
+
+impl TextTransform for MergeCompounds {
+    fn apply<'a>(&self, text: &mut Text<'a>, interner: &'a Interner) {
+        merge_compounds(&mut text.lines, interner)
+    }
+}
+
+/// Indent the lines of text with a prefix
+///
+/// Empty lines, tables and rules are left as they are.
+#[derive(Debug, Clone, Copy)]
+pub struct Indent(pub &'static str);
+
+impl TextTransform for Indent {
+    fn apply<'a>(&self, text: &mut Text<'a>, _: &'a Interner) {
+        for line in &mut text.lines {
+            if let Line::Normal(Composite { compounds, .. }) = line {
+                if !compounds.is_empty() {
+                    compounds.insert(0, Compound::raw_str(self.0));
+                }
+            }
+        }
+    }
+}
+
+/// Remove the empty lines at the end of the text
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TrimTrailingBlankLines;
+
+impl TextTransform for TrimTrailingBlankLines {
+    fn apply<'a>(&self, text: &mut Text<'a>, _: &'a Interner) {
+        while text.lines.last().is_some_and(is_blank) {
+            text.lines.pop();
+        }
+    }
+}
+
+/// Collapse the runs of empty lines longer than the given number
+#[derive(Debug, Clone, Copy)]
+pub struct LimitBlankRuns(pub usize);
+
+impl TextTransform for LimitBlankRuns {
+    fn apply<'a>(&self, text: &mut Text<'a>, _: &'a Interner) {
+        let mut run = 0;
+        text.lines.retain(|line| {
+            run = if is_blank(line) { run + 1 } else { 0 };
+            run <= self.0
+        });
+    }
+}
+
+/// Check if a line is an empty paragraph line
+pub(crate) fn is_blank(line: &Line<'_>) -> bool {
+    matches!(line, Line::Normal(Composite { style: CompositeStyle::Paragraph, compounds })
+        if compounds.iter().all(|compound| compound.src.is_empty()))
+}
+
+/// A rewrite of the text of each compound
+///
+/// Passes are applied to a text by wrapping them in a [`Pass`].
+pub trait TextPass: Send + Sync {
+    /// Rewrite the text of a compound, borrowing it if it is unchanged
+    fn rewrite<'s>(&self, text: &'s str) -> Cow<'s, str>;
+}
+
+/// Apply a [`TextPass`] to all the compounds of the text
+///
+/// If `protect_code` is set, as by [`Pass::new`], inline code, code blocks and the compounds
+/// marked as code are left untouched.
+#[derive(Debug, Clone, Copy)]
+pub struct Pass<P> {
+    /// The pass to apply
+    pub pass: P,
+    /// If the code must be left untouched
+    pub protect_code: bool,
+}
+
+impl<P> Pass<P> {
+    /// Apply the pass to everything but the code
+    pub fn new(pass: P) -> Self {
+        Self {
+            pass,
+            protect_code: true,
+        }
+    }
+
+    /// Apply the pass to the code too
+    pub fn unprotected(self) -> Self {
+        Self {
+            protect_code: false,
+            ..self
+        }
+    }
+}
+
+impl<P: TextPass> TextTransform for Pass<P> {
+    fn apply<'a>(&self, text: &mut Text<'a>, interner: &'a Interner) {
+        let rewrite = |compounds: &mut Vec<Compound<'a>>| {
+            for compound in compounds {
+                if self.protect_code && compound.code {
+                    continue;
+                }
+                if let Cow::Owned(rewritten) = self.pass.rewrite(compound.src) {
+                    compound.src = interner.intern(rewritten);
+                }
+            }
+        };
+        for line in &mut text.lines {
+            match line {
+                Line::Normal(Composite {
+                    style: CompositeStyle::Code,
+                    ..
+                }) if self.protect_code => (),
+                Line::Normal(Composite { compounds, .. }) => rewrite(compounds),
+                Line::TableRow(row) => {
+                    for cell in &mut row.cells {
+                        rewrite(&mut cell.compounds)
+                    }
+                }
+                Line::TableRule(_) | Line::HorizontalRule | Line::CodeFence(_) => (),
+            }
+        }
+    }
+}
+
+/// Typographic punctuation: curly quotes, dashes and ellipses
+///
+/// `---` becomes an em dash, `--` an en dash and `...` an ellipsis. Quotes open after a space or
+/// at the start of a compound, and close otherwise.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SmartPunctuation;
+
+impl TextPass for SmartPunctuation {
+    fn rewrite<'s>(&self, text: &'s str) -> Cow<'s, str> {
+        if !text.contains(['"', '\'', '-', '.']) {
+            return Cow::Borrowed(text);
+        }
+        let mut rewritten = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(ch) = rest.chars().next() {
+            let opening = rewritten
+                .chars()
+                .next_back()
+                .is_none_or(|prev| prev.is_whitespace() || "([{".contains(prev));
+            let (replacement, len) = match ch {
+                '-' if rest.starts_with("---") => ('—', 3),
+                '-' if rest.starts_with("--") => ('–', 2),
+                '.' if rest.starts_with("...") => ('…', 3),
+                '"' if opening => ('“', 1),
+                '"' => ('”', 1),
+                '\'' if opening => ('‘', 1),
+                '\'' => ('’', 1),
+                ch => (ch, ch.len_utf8()),
+            };
+            rewritten.push(replacement);
+            rest = &rest[len..];
+        }
+        if rewritten == text {
+            Cow::Borrowed(text)
+        } else {
+            Cow::Owned(rewritten)
+        }
+    }
+}
+
+/// Remove the emoji
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StripEmoji;
+
+impl TextPass for StripEmoji {
+    fn rewrite<'s>(&self, text: &'s str) -> Cow<'s, str> {
+        if text.chars().any(is_emoji) {
+            Cow::Owned(text.chars().filter(|&ch| !is_emoji(ch)).collect())
+        } else {
+            Cow::Borrowed(text)
+        }
+    }
+}
+
+/// Replace accented and non latin letters by their ASCII transliteration
+#[cfg(feature = "transliterate")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Transliterate;
+
+#[cfg(feature = "transliterate")]
+impl TextPass for Transliterate {
+    fn rewrite<'s>(&self, text: &'s str) -> Cow<'s, str> {
+        if text.is_ascii() {
+            Cow::Borrowed(text)
+        } else {
+            Cow::Owned(deunicode::deunicode(text))
+        }
+    }
+}
+
+/// Highlight the occurrences of a search query
+///
+/// The compounds are split at the start and end of each match, and the parts matching get the
+/// highlight styling. Matches can span compounds with different styles, but not lines or table
+/// cells.
+/// ```
+/// # use mdast2minimad::{transform::Highlight, Styling};
+/// # use minimad::{Compound, Line, Text};
+/// let mut text = Text::from("Find the *needle* here");
+/// let highlight = Highlight::new("the NEEDLE", Styling { bold: Some(true), ..Default::default() });
+/// assert_eq!(highlight.highlight(&mut text), 1);
+/// assert_eq!(
+///     text.lines[0],
+///     Line::new_paragraph(vec![
+///         Compound::raw_str("Find "),
+///         Compound::raw_str("the ").bold(),
+///         Compound::raw_str("needle").bold().italic(),
+///         Compound::raw_str(" here"),
+///     ])
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct Highlight {
+    /// The text to search
+    pub query: String,
+    /// The styling of the matches
+    pub styling: Styling,
+    /// If the case of the letters must match
+    pub case_sensitive: bool,
+}
+
+impl Highlight {
+    /// Highlight the query, ignoring case
+    pub fn new(query: impl Into<String>, styling: Styling) -> Self {
+        Self {
+            query: query.into(),
+            styling,
+            case_sensitive: false,
+        }
+    }
+
+    /// Match the case of the letters too
+    pub fn case_sensitive(self) -> Self {
+        Self {
+            case_sensitive: true,
+            ..self
+        }
+    }
+
+    /// Highlight the matches in the text, returning how many were found
+    pub fn highlight(&self, text: &mut Text<'_>) -> usize {
+        let mut found = 0;
+        for line in &mut text.lines {
+            match line {
+                Line::Normal(Composite { compounds, .. }) => found += self.composite(compounds),
+                Line::TableRow(row) => {
+                    for cell in &mut row.cells {
+                        found += self.composite(&mut cell.compounds)
+                    }
+                }
+                Line::TableRule(_) | Line::HorizontalRule | Line::CodeFence(_) => (),
+            }
+        }
+        found
+    }
+
+    /// Highlight the matches in a composite, returning how many were found
+    fn composite(&self, compounds: &mut Vec<Compound<'_>>) -> usize {
+        let text: String = compounds.iter().map(|compound| compound.src).collect();
+        let matches = self.find(&text);
+        if matches.is_empty() {
+            return 0;
+        }
+        let bounds: Vec<usize> = matches
+            .iter()
+            .flat_map(|found| [found.start, found.end])
+            .collect();
+        let mut start = 0;
+        for compound in mem::take(compounds) {
+            let end = start + compound.src.len();
+            let cuts = bounds
+                .iter()
+                .copied()
+                .filter(|&bound| start < bound && bound < end);
+            let mut from = start;
+            for to in cuts.chain([end]) {
+                let mut part = Compound {
+                    src: &compound.src[from - start..to - start],
+                    ..compound
+                };
+                if matches
+                    .iter()
+                    .any(|found| found.start <= from && to <= found.end && from < to)
+                {
+                    self.style(&mut part);
+                }
+                compounds.push(part);
+                from = to;
+            }
+            start = end;
+        }
+        matches.len()
+    }
+
+    /// Apply the highlight styling to a compound
+    fn style(&self, compound: &mut Compound<'_>) {
+        let Styling {
+            bold,
+            italic,
+            code,
+            strikeout,
+        } = self.styling;
+        compound.bold = bold.unwrap_or(compound.bold);
+        compound.italic = italic.unwrap_or(compound.italic);
+        compound.code = code.unwrap_or(compound.code);
+        compound.strikeout = strikeout.unwrap_or(compound.strikeout);
+    }
+
+    /// The byte ranges of the matches in a text, leftmost first and not overlapping
+    fn find(&self, text: &str) -> Vec<Range<usize>> {
+        let mut matches = vec![];
+        if self.query.is_empty() {
+            return matches;
+        }
+        let mut start = 0;
+        while let Some(ch) = text[start..].chars().next() {
+            match self.match_at(&text[start..]) {
+                Some(len) => {
+                    matches.push(start..start + len);
+                    start += len;
+                }
+                None => start += ch.len_utf8(),
+            }
+        }
+        matches
+    }
+
+    /// Length of the match at the start of the text, if any
+    fn match_at(&self, text: &str) -> Option<usize> {
+        let mut chars = text.char_indices();
+        for wanted in self.query.chars() {
+            let (_, ch) = chars.next()?;
+            let same = if self.case_sensitive {
+                ch == wanted
+            } else {
+                ch.to_lowercase().eq(wanted.to_lowercase())
+            };
+            if !same {
+                return None;
+            }
+        }
+        Some(chars.next().map_or(text.len(), |(idx, _)| idx))
+    }
+}
+
+impl TextTransform for Highlight {
+    fn apply<'a>(&self, text: &mut Text<'a>, _: &'a Interner) {
+        self.highlight(text);
+    }
+}
@@ -0,0 +1,89 @@
+use mdast2minimad::{md_parse_options, to_minimad_owned, ConversionCache, Options};
+
+/// Convert a source with the cache, checking that the result does not change
+fn check(cache: &mut ConversionCache, source: &str, options: &Options) {
+    check_with(cache, source, &md_parse_options(), options)
+}
+
+/// Parse and convert a source with the cache, checking that the result does not change
+fn check_with(
+    cache: &mut ConversionCache,
+    source: &str,
+    parse_options: &markdown::ParseOptions,
+    options: &Options,
+) {
+    let ast = markdown::to_mdast(source, parse_options).unwrap();
+    let expected = to_minimad_owned(&ast, options.clone()).unwrap();
+    assert_eq!(cache.convert(&ast).unwrap(), expected);
+}
+
+/// Main test implementation, called for every test source in `sources`
+///
+/// The cached conversion must give the same text as the direct one.
+fn test_source(source: &'static str) {
+    let mut cache = ConversionCache::new(Options::default());
+    check(&mut cache, source, &Options::default());
+    check(&mut cache, source, &Options::default());
+}
+
+#[test]
+fn reuses_unchanged_blocks() {
+    let options = Options::default();
+    let mut cache = ConversionCache::new(options.clone());
+    check(
+        &mut cache,
+        "# Title\n\nFirst\n\n- a\n- b\n\n```\ncode\n```\n\n> quoted",
+        &options,
+    );
+    assert_eq!(cache.reused(), 0);
+    assert_eq!(cache.len(), 5);
+
+    // only the changed paragraph is converted again
+    check(
+        &mut cache,
+        "# Title\n\nChanged\n\n- a\n- b\n\n```\ncode\n```\n\n> quoted",
+        &options,
+    );
+    assert_eq!(cache.reused(), 4);
+    assert_eq!(cache.len(), 5);
+
+    // moved blocks are reused, with the spacing of their new place
+    check(
+        &mut cache,
+        "```\ncode\n```\n\n# Title\n\n- a\n- b\n\nChanged",
+        &options,
+    );
+    check(
+        &mut cache,
+        "```\ncode\n```\n\n# Title\n\n- a\n- b\n\nChanged",
+        &options,
+    );
+    assert_eq!(cache.reused(), 4);
+    // the removed quote was dropped
+    assert_eq!(cache.len(), 4);
+}
+
+#[test]
+fn converts_blocks_depending_on_the_document() {
+    let options = Options::builder().number_headings(true).build();
+    let mut cache = ConversionCache::new(options.clone());
+    let gfm = markdown::ParseOptions::gfm();
+    check_with(
+        &mut cache,
+        "# One\n\nA note[^n]\n\n# Two\n\n[^n]: The note",
+        &gfm,
+        &options,
+    );
+    check_with(
+        &mut cache,
+        "# Zero\n\n# One\n\nA note[^n]\n\n# Two\n\n[^n]: The note",
+        &gfm,
+        &options,
+    );
+    assert_eq!(cache.reused(), 0);
+
+    cache.clear();
+    assert!(cache.is_empty());
+}
+
+include! {env!("TEST_SOURCES_RS")}
@@ -0,0 +1,67 @@
+use mdast2minimad::{
+    changelog::{self, ChangeKind, ChangelogOptions},
+    md_parse_options, Interner,
+};
+use minimad::{CompositeStyle, Line};
+
+const CHANGELOG: &str = "# Changelog
+
+## [Unreleased]
+
+### Added
+
+- Something new
+
+## [1.0.0] - 2017-06-20
+
+### Added
+
+- A feature
+- Another feature
+
+### Fixed
+
+- A bug
+";
+
+#[test]
+fn tags_change_groups() {
+    let interner = Interner::new();
+    let ast = markdown::to_mdast(CHANGELOG, &md_parse_options()).unwrap();
+    let changelog = changelog::to_minimad(&ast, &ChangelogOptions::default(), &interner).unwrap();
+
+    let kinds: Vec<_> = changelog.tags.iter().map(|tag| tag.kind).collect();
+    assert_eq!(
+        kinds,
+        [ChangeKind::Added, ChangeKind::Added, ChangeKind::Fixed]
+    );
+    for tag in &changelog.tags {
+        // every group starts with its bold prefix
+        let Line::Normal(prefix) = &changelog.text.lines[tag.lines.start] else {
+            panic!("Expected a normal line")
+        };
+        assert_eq!(prefix.style, CompositeStyle::Paragraph);
+        assert!(prefix.compounds.iter().all(|c| c.bold));
+    }
+}
+
+#[test]
+fn filters_unreleased() {
+    let interner = Interner::new();
+    let ast = markdown::to_mdast(CHANGELOG, &md_parse_options()).unwrap();
+    let changelog = changelog::to_minimad(
+        &ast,
+        &ChangelogOptions {
+            unreleased: false,
+            ..Default::default()
+        },
+        &interner,
+    )
+    .unwrap();
+
+    assert_eq!(changelog.tags.len(), 2);
+    assert!(!changelog.text.lines.iter().any(|line| matches!(
+        line,
+        Line::Normal(composite) if composite.compounds.iter().any(|c| c.src.contains("Unreleased") || c.src.contains("Something new"))
+    )));
+}
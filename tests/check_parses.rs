@@ -1,14 +1,15 @@
 use std::error::Error;
 
-use mdast2minimad::{md_parse_options, to_minimad};
+use mdast2minimad::{md_parse_options, to_minimad, Interner};
 
 /// Main test implementation, called for every test source in `sources`
 fn test_source(source: &'static str) {
+    let interner = Interner::new();
     // parsing the test with markdown
     let ast =
-        markdown::to_mdast(&source, &md_parse_options()).expect("Markdown has no syntax errors");
+        markdown::to_mdast(source, &md_parse_options()).expect("Markdown has no syntax errors");
     // convertint it
-    if let Err(error) = to_minimad(&ast) {
+    if let Err(error) = to_minimad(&ast, &interner) {
         eprintln!("{error}");
         if let Some(mut source) = error.source() {
             eprintln!();
@@ -0,0 +1,60 @@
+use mdast2minimad::{commit, md_parse_options, Interner, Options};
+use minimad::{CompositeStyle, Line};
+
+#[test]
+fn subject_body_and_trailers() {
+    let interner = Interner::new();
+    let message = "Fix the frobnicator
+
+The frobnicator was broken
+when the moon was full.
+
+Signed-off-by: Jane Doe
+Reviewed-by: John Doe
+";
+    let ast = markdown::to_mdast(message, &md_parse_options()).unwrap();
+    let text = commit::to_minimad(&ast, Options::default(), &interner).unwrap();
+
+    let styles: Vec<_> = text
+        .lines
+        .iter()
+        .map(|line| match line {
+            Line::Normal(composite) => composite.style,
+            _ => panic!("Expected only normal lines"),
+        })
+        .collect();
+    assert_eq!(
+        styles,
+        [
+            CompositeStyle::Header(1),
+            CompositeStyle::Paragraph,
+            CompositeStyle::Paragraph,
+            CompositeStyle::Paragraph,
+            CompositeStyle::Paragraph,
+            CompositeStyle::Quote,
+            CompositeStyle::Quote,
+        ]
+    );
+}
+
+#[test]
+fn body_keeps_its_lines() {
+    use mdast2minimad::SoftBreak;
+
+    let interner = Interner::new();
+    let message = "Fix the frobnicator
+
+The frobnicator was broken
+when the moon was full.
+";
+    let ast = markdown::to_mdast(message, &md_parse_options()).unwrap();
+    let expected = commit::to_minimad(&ast, Options::default(), &interner).unwrap();
+    assert_eq!(expected.lines.len(), 4);
+    for soft_break in [SoftBreak::Space, SoftBreak::Preserve] {
+        let options = Options::builder().soft_break(soft_break).build();
+        assert_eq!(
+            commit::to_minimad(&ast, options, &interner).unwrap(),
+            expected
+        );
+    }
+}
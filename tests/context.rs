@@ -0,0 +1,95 @@
+use mdast2minimad::{mdast, skin_hints, to_minimad, DocumentContext, Interner, SkinHints};
+use minimad::{Compound, Line};
+
+#[test]
+fn scan_collects_definitions() {
+    let ast = markdown::to_mdast(
+        "# Title\n\n- ## Nested\n\n[link]: https://example.com\n[LINK]: https://example.org\n",
+        &markdown::ParseOptions::default(),
+    )
+    .unwrap();
+    let context = DocumentContext::scan(&ast);
+
+    let titles: Vec<_> = context
+        .headings()
+        .iter()
+        .map(|heading| heading.depth)
+        .collect();
+    assert_eq!(titles, [1, 2]);
+    // the first definition wins
+    assert_eq!(
+        context
+            .definition("link")
+            .map(|definition| &*definition.url),
+        Some("https://example.com")
+    );
+    assert_eq!(context.definitions().count(), 1);
+    assert!(context.footnote("link").is_none());
+}
+
+#[cfg(feature = "gfm")]
+#[test]
+fn scan_collects_footnotes() {
+    let ast =
+        markdown::to_mdast("Text[^a]\n\n[^a]: Note", &mdast2minimad::md_parse_options()).unwrap();
+    let context = DocumentContext::scan(&ast);
+    assert!(context.footnote("a").is_some());
+    assert_eq!(context.footnotes().count(), 1);
+}
+
+#[test]
+fn undefined_footnotes_are_left_as_written() {
+    let interner = Interner::new();
+    let ast = mdast::Node::Root(mdast::Root {
+        children: vec![mdast::Node::Paragraph(mdast::Paragraph {
+            children: vec![
+                mdast::Node::Text(mdast::Text {
+                    value: "Text".to_owned(),
+                    position: None,
+                }),
+                mdast::Node::FootnoteReference(mdast::FootnoteReference {
+                    identifier: "missing".to_owned(),
+                    label: Some("Missing".to_owned()),
+                    position: None,
+                }),
+            ],
+            position: None,
+        })],
+        position: None,
+    });
+    assert_eq!(
+        to_minimad(&ast, &interner).unwrap().lines,
+        [Line::new_paragraph(vec![
+            Compound::raw_str("Text"),
+            Compound::raw_str("[^Missing]"),
+        ])]
+    );
+}
+
+#[test]
+fn skin_hints_of_a_document() {
+    let ast = markdown::to_mdast(
+        "# Title\n\n> ~~old~~ *new*\n\n| a |\n|---|\n| 1 |\n\n###### Deep\n\n```\ncode\n```",
+        &markdown::ParseOptions::gfm(),
+    )
+    .unwrap();
+    let hints = skin_hints(&ast);
+    assert_eq!(
+        hints,
+        SkinHints {
+            italic: true,
+            strikeout: true,
+            code_blocks: true,
+            tables: true,
+            quotes: true,
+            heading_depths: [true, false, false, false, false, true],
+            ..Default::default()
+        }
+    );
+    assert_eq!(hints.deepest_heading(), Some(6));
+    assert_eq!(hints.heading_levels(), 2);
+
+    let ast = markdown::to_mdast("Plain text", &Default::default()).unwrap();
+    assert_eq!(skin_hints(&ast), SkinHints::default());
+    assert_eq!(skin_hints(&ast).deepest_heading(), None);
+}
@@ -0,0 +1,375 @@
+use mdast2minimad::{
+    convert, from_minimad, md_parse_options, to_markdown, to_minimad, to_minimad_reporting,
+    to_minimad_with_options, to_plain_text, DocumentStats, Interner, Options,
+};
+
+#[test]
+fn convert_matches_ast_conversion() {
+    let interner = Interner::new();
+    let source = "# Title\n\nSome *styled* `text`\n\n| a | b |\n|---|---|\n| 1 | 2 |\n";
+    let owned = convert(source).unwrap();
+
+    let ast = markdown::to_mdast(source, &md_parse_options()).unwrap();
+    assert_eq!(owned.text(), to_minimad(&ast, &interner).unwrap());
+}
+
+#[test]
+fn owned_text_outlives_ast() {
+    let source = "Some *styled* text";
+    let owned = {
+        let ast = markdown::to_mdast(source, &md_parse_options()).unwrap();
+        mdast2minimad::to_minimad_owned(&ast, Default::default()).unwrap()
+    };
+    assert_eq!(
+        owned.text(),
+        minimad::parse_text(source, Default::default())
+    );
+}
+
+#[test]
+fn shared_interner() {
+    let interner = Interner::new();
+    let first = interner.intern(String::from("shared"));
+    let second = interner.intern("shared");
+    assert!(std::ptr::eq(first, second));
+    assert_eq!(interner.len(), 1);
+
+    let source = "Some *styled* text";
+    let ast = markdown::to_mdast(source, &md_parse_options()).unwrap();
+    assert_eq!(
+        to_minimad_with_options(&ast, Default::default(), &interner).unwrap(),
+        to_minimad(&ast, &interner).unwrap()
+    );
+
+    // the synthesized strings are stored in the given interner, and nowhere else
+    let ast = markdown::to_mdast("1. one\n2. two", &md_parse_options()).unwrap();
+    let own = Interner::new();
+    to_minimad(&ast, &own).unwrap();
+    assert!(!own.is_empty());
+    assert_eq!(interner.len(), 1);
+}
+
+#[test]
+fn all_line_endings() {
+    let interner = Interner::new();
+    for ending in ["\n", "\r\n", "\r"] {
+        let source = ["one", "two", "", "```", "let x;", "let y;", "```", ""].join(ending);
+        let ast = markdown::to_mdast(&source, &md_parse_options()).unwrap();
+        assert_eq!(
+            to_minimad(&ast, &interner).unwrap(),
+            minimad::parse_text("one\ntwo\n\n```\nlet x;\nlet y;\n```", Default::default()),
+            "with {ending:?} line endings"
+        );
+    }
+}
+
+#[test]
+fn frontmatter_only_at_start() {
+    use mdast2minimad::{frontmatter, mdast, Frontmatter};
+    use minimad::{Compound, Line};
+
+    let interner = Interner::new();
+
+    let yaml = |value: &str| {
+        mdast::Node::Yaml(mdast::Yaml {
+            value: value.to_owned(),
+            position: None,
+        })
+    };
+    let ast = mdast::Node::Root(mdast::Root {
+        children: vec![
+            yaml("title: Doc"),
+            mdast::Node::Paragraph(mdast::Paragraph {
+                children: vec![mdast::Node::Text(mdast::Text {
+                    value: "Text".to_owned(),
+                    position: None,
+                })],
+                position: None,
+            }),
+            yaml("stray: true"),
+        ],
+        position: None,
+    });
+    assert_eq!(frontmatter(&ast), Some(Frontmatter::Yaml("title: Doc")));
+    assert_eq!(
+        to_minimad(&ast, &interner).unwrap().lines,
+        [
+            Line::new_paragraph(vec![Compound::raw_str("Text")]),
+            Line::new_paragraph(vec![]),
+            Line::new_code(Compound::raw_str("stray: true")),
+        ]
+    );
+}
+
+#[test]
+fn single_composite() {
+    use mdast2minimad::{to_composite, ToMinimadError};
+    use minimad::{Composite, CompositeStyle, Compound};
+
+    let interner = Interner::new();
+
+    let ast = markdown::to_mdast("Done: **3** of `5`", &md_parse_options()).unwrap();
+    assert_eq!(
+        to_composite(&ast, &interner).unwrap(),
+        Composite {
+            style: CompositeStyle::Paragraph,
+            compounds: vec![
+                Compound::raw_str("Done: "),
+                Compound::raw_str("3").bold(),
+                Compound::raw_str(" of "),
+                Compound::raw_str("5").code(),
+            ]
+        }
+    );
+
+    let ast = markdown::to_mdast("One\n\nTwo", &md_parse_options()).unwrap();
+    assert!(matches!(
+        to_composite(&ast, &interner).unwrap_err().root_cause(),
+        ToMinimadError::MultilineComposite { .. }
+    ));
+}
+
+#[test]
+fn lines_iter_matches_conversion() {
+    use mdast2minimad::{to_minimad_lines, to_minimad_with_options, Options};
+
+    let interner = Interner::new();
+
+    let source = "# Title\n\nSome text[^1] and more text\n\n- a\n- b\n\n[^1]: A note";
+    let ast = markdown::to_mdast(source, &md_parse_options()).unwrap();
+    for max_output_bytes in [None, Some(12), Some(1000)] {
+        let options = Options::builder()
+            .max_output_bytes(max_output_bytes)
+            .build();
+        let lines: Result<Vec<_>, _> = to_minimad_lines(&ast, options.clone(), &interner).collect();
+        assert_eq!(
+            lines.unwrap(),
+            to_minimad_with_options(&ast, options, &interner)
+                .unwrap()
+                .lines,
+            "with max_output_bytes: {max_output_bytes:?}"
+        );
+    }
+
+    let ast = markdown::to_mdast(
+        "Text\n\n[cat]: cat.png\n\nMore",
+        &markdown::ParseOptions::default(),
+    )
+    .unwrap();
+    let mut lines = to_minimad_lines(&ast, Options::default(), &interner);
+    assert!(lines.next().unwrap().is_ok());
+    assert_eq!(
+        lines.next().unwrap().unwrap_err().path(),
+        to_minimad(&ast, &interner).unwrap_err().path()
+    );
+    assert!(lines.next().is_none());
+}
+
+#[test]
+fn lines_iter_sees_the_whole_document() {
+    use mdast2minimad::{to_minimad_lines, Options};
+
+    let interner = Interner::new();
+    let source = "## A\n\nText[^1] and[^missing]\n\n### B\n\n[^1]: A note";
+    let ast = markdown::to_mdast(source, &md_parse_options()).unwrap();
+    let options = Options::builder().number_headings(true).build();
+    let expected = to_minimad_with_options(&ast, options.clone(), &interner).unwrap();
+    let lines: Result<Vec<_>, _> = to_minimad_lines(&ast, options, &interner).collect();
+    assert_eq!(lines.unwrap(), expected.lines);
+}
+
+#[test]
+fn inline_template_round_trip() {
+    use mdast2minimad::{to_composite, to_inline_template};
+    use minimad::Composite;
+
+    let interner = Interner::new();
+
+    for source in [
+        "plain text",
+        "**bold** and *italic* and ~~struck~~",
+        "***both*** then `code` and **`bold code`**",
+        "stars \\* tildes \\~ pipes | and \\\\ backslashes",
+        "*\\*starred\\**",
+        "**bold** *italic*",
+    ] {
+        let ast = markdown::to_mdast(source, &md_parse_options()).unwrap();
+        let template = to_inline_template(&ast).unwrap();
+        let composite = to_composite(&ast, &interner).unwrap();
+        let parsed = Composite::from_inline(&template);
+        let text = |composite: &Composite<'_>| -> Vec<_> {
+            composite
+                .compounds
+                .iter()
+                .flat_map(|c| {
+                    c.src
+                        .chars()
+                        .map(move |ch| (ch, c.bold, c.italic, c.code, c.strikeout))
+                })
+                .collect()
+        };
+        assert_eq!(
+            text(&parsed),
+            text(&composite),
+            "{source:?} rendered as {template:?}"
+        );
+    }
+
+    // backticks cannot be part of code
+    let ast = markdown::to_mdast("`` a ` b ``", &md_parse_options()).unwrap();
+    assert_eq!(to_inline_template(&ast).unwrap(), "`a `\\`` b`");
+}
+
+#[test]
+fn batch_conversion() {
+    use mdast2minimad::{convert_many, Options, Parallelism};
+    use std::num::NonZeroUsize;
+
+    let interner = Interner::new();
+
+    let asts: Vec<_> = (0..10)
+        .map(|idx| {
+            let source = match idx % 3 {
+                0 => format!("# Page {idx}\n\nSome *text*"),
+                1 => format!("- item {idx}\n- another"),
+                _ => format!("> not supported {idx}"),
+            };
+            markdown::to_mdast(&source, &md_parse_options()).unwrap()
+        })
+        .collect();
+    let expected: Vec<_> = asts.iter().map(|ast| to_minimad(ast, &interner)).collect();
+    for parallelism in [
+        Parallelism::Sequential,
+        Parallelism::Threads(NonZeroUsize::new(3).unwrap()),
+        Parallelism::Available,
+    ] {
+        assert_eq!(
+            convert_many(&asts, Options::default(), parallelism, &interner)
+                .into_iter()
+                .map(|res| res.map_err(|err| err.path()))
+                .collect::<Vec<_>>(),
+            expected
+                .iter()
+                .map(|res| res.clone().map_err(|err| err.path()))
+                .collect::<Vec<_>>(),
+            "with {parallelism:?}"
+        );
+    }
+}
+
+#[test]
+fn compiled_features() {
+    let features = mdast2minimad::features();
+    assert_eq!(features.contains(&"gfm"), cfg!(feature = "gfm"));
+    assert_eq!(features.contains(&"osc8"), cfg!(feature = "osc8"));
+    assert_eq!(
+        features.contains(&"transliterate"),
+        cfg!(feature = "transliterate")
+    );
+}
+
+#[test]
+fn plain_text() {
+    let source =
+        "> *Quoted* text\n\n```\nlet x = 1;\n```\n\n***\n\n| a | b |\n|---|---|\n| 1 | 2 |\n";
+    let ast = markdown::to_mdast(source, &markdown::ParseOptions::gfm()).unwrap();
+    assert_eq!(
+        to_plain_text(&ast).unwrap(),
+        "> Quoted text\n\nlet x = 1;\n\n---\n\na | b\n1 | 2"
+    );
+}
+
+/// The lines of a text, without the empty ones
+fn non_empty(text: minimad::Text<'_>) -> Vec<minimad::Line<'_>> {
+    text.lines
+        .into_iter()
+        .filter(|line| {
+            !matches!(line, minimad::Line::Normal(composite) if composite.compounds.is_empty())
+        })
+        .collect()
+}
+
+#[test]
+fn from_minimad_round_trip() {
+    let interner = Interner::new();
+    let source = "# Title\n\nSome *styled* `text`\n\n- one\n  - nested\n- two\n\n> quoted\n\n```\nlet x = 1;\nlet y = 2;\n```\n\n| a | b |\n|:--|--:|\n| 1 | 2 |\n\n***\n";
+    let ast = markdown::to_mdast(source, &markdown::ParseOptions::gfm()).unwrap();
+    let text = to_minimad(&ast, &interner).unwrap();
+
+    let rebuilt = from_minimad(&text);
+    assert_eq!(
+        non_empty(to_minimad(&rebuilt, &interner).unwrap()),
+        non_empty(text)
+    );
+}
+
+#[test]
+fn markdown_round_trip() {
+    let interner = Interner::new();
+    let source = "# Title\n\nSome *styled* `text` with **bold *and italic* ** and a ~~strike~~\n\nSpecial chars: a\\*b [c] \\<d> `` ` ``\n\n- one\n  - nested\n- two\n\n> quoted\n>\n> twice\n\n```\nlet x = 1;\n```\n\n| a | b |\n|:--|--:|\n| 1 | `|` |\n\n***\n";
+    let ast = markdown::to_mdast(source, &markdown::ParseOptions::gfm()).unwrap();
+    let text = to_minimad(&ast, &interner).unwrap();
+
+    let written = to_markdown(&text);
+    let reparsed = markdown::to_mdast(&written, &markdown::ParseOptions::gfm()).unwrap();
+    assert_eq!(
+        non_empty(to_minimad(&reparsed, &interner).unwrap()),
+        non_empty(text),
+        "{written}"
+    );
+}
+
+#[test]
+fn markdown_round_trip_of_block_markers() {
+    use minimad::{Compound, Line, Text};
+
+    let interner = Interner::new();
+    for src in [
+        "- not a list",
+        "+ nor this",
+        "1. nor an ordered list",
+        "12) nor this",
+        "---",
+        "= not a setext underline",
+        "> not a quote",
+        "# not a heading",
+        "    not code",
+        "\tnor code",
+        " one space",
+    ] {
+        let text = Text {
+            lines: vec![
+                Line::new_paragraph(vec![Compound::raw_str(src)]),
+                Line::new_list_item(0, vec![Compound::raw_str(src)]),
+                Line::new_quote(vec![Compound::raw_str(src)]),
+            ],
+        };
+        let written = to_markdown(&text);
+        let reparsed = markdown::to_mdast(&written, &md_parse_options()).unwrap();
+        assert_eq!(
+            non_empty(to_minimad(&reparsed, &interner).unwrap()),
+            non_empty(text),
+            "{written}"
+        );
+    }
+}
+
+#[test]
+fn document_stats() {
+    let interner = Interner::new();
+    let source = "# Title\n\n## Install\n\nRun *the* in**stall**er from [the site](https://example.com)\n\n```\ncargo install\n```\n\n## Usage\n";
+    let ast = markdown::to_mdast(source, &md_parse_options()).unwrap();
+    let (text, report) = to_minimad_reporting(&ast, Options::default(), &interner).unwrap();
+    assert_eq!(
+        report.stats,
+        DocumentStats {
+            // the title, the headings, the 6 words of the paragraph and the code
+            words: 11,
+            headings: [1, 2, 0, 0, 0, 0],
+            code_blocks: 1,
+            links: 1,
+            lines: text.lines.len(),
+        }
+    );
+    assert_eq!(report.stats.reading_minutes(200), 1);
+}
@@ -0,0 +1,32 @@
+use mdast2minimad::{compare_with_minimad, convert, render_markdown, Options};
+
+/// Main test implementation, called for every test source in `sources`
+///
+/// Checks that the library functions behind the examples agree with the conversion.
+fn test_source(source: &'static str) {
+    let rendering = render_markdown(source, Options::default()).expect("The source should render");
+    assert_eq!(rendering.text, convert(source).unwrap());
+
+    let comparison = compare_with_minimad(source).expect("The source should be compared");
+    assert_eq!(comparison.converted, rendering.text);
+    let differences = comparison.differences();
+    assert_eq!(differences.is_empty(), comparison.is_identical());
+    for difference in differences {
+        assert_ne!(difference.minimad, difference.converted);
+    }
+}
+
+#[test]
+fn differences_are_reported() {
+    let comparison = compare_with_minimad("A paragraph\n\n- a list").unwrap();
+    let differences = comparison.differences();
+    assert!(!differences.is_empty());
+    assert!(differences.iter().all(|difference| difference.index
+        < comparison
+            .minimad
+            .lines
+            .len()
+            .max(comparison.converted.len())));
+}
+
+include! {env!("TEST_SOURCES_RS")}
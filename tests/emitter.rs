@@ -0,0 +1,60 @@
+use mdast2minimad::{emitter::Emitter, md_parse_options, to_minimad, Interner, Options};
+use minimad::{Compound, Line};
+
+#[test]
+fn interleaves_generated_lines() {
+    let interner = Interner::new();
+    let ast = markdown::to_mdast("Some *markdown*", &md_parse_options()).unwrap();
+    let bindings = minimad::parse_text("|key|action|\n|-|-|\n|q|quit|", Default::default());
+
+    let mut emitter = Emitter::new(Options::default(), &interner);
+    emitter.push_paragraph(vec![Compound::raw_str("Status: ok")]);
+    emitter.push_node(&ast).unwrap();
+    emitter.push_lines(bindings.lines.clone());
+    let text = emitter.finish();
+
+    let mut expected = vec![
+        Line::new_paragraph(vec![Compound::raw_str("Status: ok")]),
+        Line::new_paragraph(vec![]),
+    ];
+    expected.extend(to_minimad(&ast, &interner).unwrap().lines);
+    expected.push(Line::new_paragraph(vec![]));
+    expected.extend(bindings.lines);
+    assert_eq!(text.lines, expected);
+}
+
+#[test]
+fn recycled_allocations() {
+    use mdast2minimad::{emitter::buffers::Buffers, Converter};
+
+    let interner = Interner::new();
+
+    let converter = Converter::default();
+    let mut buffers = Buffers::new();
+    let mut capacity = 0;
+    for source in [
+        "# Title\n\nSome *text*",
+        "| a | b |\n|---|---|\n| 1 | 2 |",
+        "A\nB",
+    ] {
+        let ast = markdown::to_mdast(source, &md_parse_options()).unwrap();
+        let text = converter
+            .convert_recycling(&ast, &mut buffers, &interner)
+            .unwrap();
+        assert_eq!(text, to_minimad(&ast, &interner).unwrap());
+        // the vector of lines is reused as long as it is large enough
+        assert!(text.lines.capacity() >= capacity);
+        capacity = text.lines.capacity();
+        buffers.recycle(text);
+    }
+
+    let first = markdown::to_mdast("First *document*", &md_parse_options()).unwrap();
+    let second = markdown::to_mdast("- Second\n- document", &md_parse_options()).unwrap();
+    let mut emitter = Emitter::new(Options::default(), &interner);
+    emitter.push_node(&first).unwrap();
+    emitter.reset();
+    emitter.push_node(&second).unwrap();
+    let mut fresh = Emitter::new(Options::default(), &interner);
+    fresh.push_node(&second).unwrap();
+    assert_eq!(emitter.finish(), fresh.finish());
+}
@@ -0,0 +1,170 @@
+use mdast2minimad::{to_minimad, Interner, NodeKind, ToMinimadError};
+
+#[test]
+fn errors_point_at_the_failing_node() {
+    let interner = Interner::new();
+    let ast = markdown::to_mdast(
+        "# Title\n\nSome text\n\n- an item\n- an ![image][cat]\n\n[cat]: cat.png\n",
+        &markdown::ParseOptions::default(),
+    )
+    .unwrap();
+    let error = to_minimad(&ast, &interner).unwrap_err();
+
+    assert_eq!(
+        error.path(),
+        [
+            NodeKind::Root,
+            NodeKind::List,
+            NodeKind::ListItem,
+            NodeKind::Paragraph,
+            NodeKind::ImageReference
+        ]
+    );
+    assert!(matches!(
+        error.root_cause(),
+        ToMinimadError::UnsupportedNode {
+            node: NodeKind::ImageReference,
+            ..
+        }
+    ));
+    let position = error.position().unwrap();
+    assert_eq!((position.start.line, position.start.column), (6, 6));
+    assert_eq!((position.end.line, position.end.column), (6, 19));
+}
+
+#[test]
+fn strict_rejects_invalid_asts() {
+    use mdast2minimad::{mdast, to_minimad_with_options, Options};
+
+    let interner = Interner::new();
+
+    // a heading inside a paragraph
+    let ast = mdast::Node::Root(mdast::Root {
+        children: vec![mdast::Node::Paragraph(mdast::Paragraph {
+            children: vec![mdast::Node::Heading(mdast::Heading {
+                children: vec![mdast::Node::Text(mdast::Text {
+                    value: "Title".to_owned(),
+                    position: None,
+                })],
+                position: None,
+                depth: 1,
+            })],
+            position: None,
+        })],
+        position: None,
+    });
+
+    assert!(to_minimad(&ast, &interner).is_ok());
+    let error = to_minimad_with_options(&ast, Options::builder().strict(true).build(), &interner)
+        .unwrap_err();
+    assert!(matches!(
+        error.root_cause(),
+        ToMinimadError::InvalidAst { .. }
+    ));
+    assert_eq!(
+        error.path(),
+        [NodeKind::Root, NodeKind::Paragraph, NodeKind::Heading]
+    );
+}
+
+#[test]
+fn invalid_asts_do_not_panic() {
+    use mdast2minimad::{mdast, to_minimad_with_options, Options, WidthClass};
+
+    let interner = Interner::new();
+
+    let heading = mdast::Node::Heading(mdast::Heading {
+        children: vec![mdast::Node::Text(mdast::Text {
+            value: "Title".to_owned(),
+            position: None,
+        })],
+        position: None,
+        depth: 0,
+    });
+    let table = mdast::Node::Table(mdast::Table {
+        children: vec![],
+        position: None,
+        align: vec![],
+    });
+    for (node, kind) in [(heading, NodeKind::Heading), (table, NodeKind::Table)] {
+        let ast = mdast::Node::Root(mdast::Root {
+            children: vec![node],
+            position: None,
+        });
+        for width_class in [WidthClass::Narrow, WidthClass::Normal] {
+            let options = Options::builder().width_class(width_class).build();
+            assert!(to_minimad_with_options(&ast, options.clone(), &interner).is_ok());
+            let error = to_minimad_with_options(
+                &ast,
+                Options::builder().options(options).strict(true).build(),
+                &interner,
+            )
+            .unwrap_err();
+            assert!(matches!(
+                error.root_cause(),
+                ToMinimadError::InvalidAst { .. }
+            ));
+            assert_eq!(error.path(), [NodeKind::Root, kind]);
+        }
+    }
+}
+
+#[test]
+fn deep_asts_are_rejected() {
+    use mdast2minimad::{mdast, to_minimad_with_options, Options, DEFAULT_MAX_DEPTH};
+
+    let interner = Interner::new();
+
+    let nested = |depth: usize| {
+        let mut node = mdast::Node::Text(mdast::Text {
+            value: "deep".to_owned(),
+            position: None,
+        });
+        for _ in 0..depth {
+            node = mdast::Node::Emphasis(mdast::Emphasis {
+                children: vec![node],
+                position: None,
+            });
+        }
+        mdast::Node::Paragraph(mdast::Paragraph {
+            children: vec![node],
+            position: None,
+        })
+    };
+
+    let error = to_minimad(&nested(10_000), &interner).unwrap_err();
+    // the path ends with the node that is too deep
+    assert_eq!(error.path().len(), DEFAULT_MAX_DEPTH + 1);
+    assert!(matches!(
+        error.root_cause(),
+        ToMinimadError::TooDeep {
+            max_depth: DEFAULT_MAX_DEPTH,
+            ..
+        }
+    ));
+
+    let options = Options::builder().max_depth(10).build();
+    assert!(to_minimad_with_options(&nested(8), options.clone(), &interner).is_ok());
+    assert!(to_minimad_with_options(&nested(9), options, &interner).is_err());
+}
+
+#[test]
+fn custom_emitters_build_crate_errors() {
+    use mdast2minimad::WhileEmitting;
+
+    let ast = markdown::to_mdast("Text", &markdown::ParseOptions::default()).unwrap();
+    let paragraph = &ast.children().unwrap()[0];
+    let error = ToMinimadError::unsupported_child_node(paragraph).while_emitting(&ast);
+    assert_eq!(error.path(), [NodeKind::Root]);
+    assert!(matches!(
+        error.root_cause(),
+        ToMinimadError::UnsupportedChildNode {
+            child: NodeKind::Paragraph,
+            ..
+        }
+    ));
+    assert_eq!(error.position(), paragraph.position());
+
+    let error = ToMinimadError::invalid_ast("a custom reason", paragraph);
+    assert_eq!(error.to_string(), "Invalid AST: a custom reason");
+}
@@ -0,0 +1,91 @@
+//! Conversion of documents using the GitHub Flavored Markdown extensions
+
+use mdast2minimad::{md_parse_options, to_minimad, Interner};
+use minimad::{Composite, CompositeStyle, Line};
+
+const SAMPLE: &str = include_str!("sources/gfm.md");
+
+/// The text of the lines with the given style
+fn lines_with_style(text: &minimad::Text<'_>, style: CompositeStyle) -> Vec<String> {
+    text.lines
+        .iter()
+        .filter_map(|line| match line {
+            Line::Normal(Composite {
+                style: line_style,
+                compounds,
+            }) if *line_style == style => Some(compounds.iter().map(|c| c.src).collect()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn sample_document() {
+    let interner = Interner::new();
+    let ast = markdown::to_mdast(SAMPLE, &md_parse_options()).unwrap();
+    let text = to_minimad(&ast, &interner).unwrap();
+
+    assert_eq!(
+        lines_with_style(&text, CompositeStyle::ListItem(0)),
+        [
+            "[x] Write the press release",
+            "[ ] Update the website",
+            "[ ] Contact the media",
+        ]
+    );
+    assert_eq!(
+        text.lines
+            .iter()
+            .filter(|line| matches!(line, Line::TableRow(_)))
+            .count(),
+        3
+    );
+    let paragraphs = lines_with_style(&text, CompositeStyle::Paragraph);
+    assert!(paragraphs.iter().any(|line| line == "[1] My reference."));
+    assert!(text.lines.iter().any(|line| matches!(
+        line,
+        Line::Normal(Composite { compounds, .. })
+            if compounds.iter().any(|c| c.strikeout && c.src == "The world is flat.")
+    )));
+}
+
+#[test]
+fn tables_in_list_items() {
+    let interner = Interner::new();
+    let ast = markdown::to_mdast(
+        include_str!("sources/lists/with_table.md"),
+        &md_parse_options(),
+    )
+    .unwrap();
+    let text = to_minimad(&ast, &interner).unwrap();
+
+    assert_eq!(
+        lines_with_style(&text, CompositeStyle::ListItem(0)),
+        ["Item with a table:", "Item after the table"]
+    );
+    assert_eq!(
+        text.lines
+            .iter()
+            .filter(|line| matches!(line, Line::TableRow(_)))
+            .count(),
+        3
+    );
+    assert!(text
+        .lines
+        .iter()
+        .any(|line| matches!(line, Line::TableRule(_))));
+}
+
+#[test]
+fn parse_options_enable_gfm() {
+    let constructs = md_parse_options().constructs;
+    assert!(constructs.gfm_table);
+    assert!(constructs.gfm_strikethrough);
+    assert!(constructs.gfm_task_list_item);
+    assert!(constructs.gfm_footnote_definition);
+    assert!(constructs.gfm_label_start_footnote);
+    assert!(constructs.gfm_autolink_literal);
+    // the emitter does not render these
+    assert!(!constructs.html_flow && !constructs.html_text);
+    assert!(!constructs.definition);
+}
@@ -0,0 +1,109 @@
+use mdast2minimad::{from_mdast_json, mdast, to_minimad, Interner};
+
+/// The AST of "# Title\n\nSome **bold** text\n\n- [x] done", as serialized by remark
+const REMARK_JSON: &str = r#"{
+  "type": "root",
+  "children": [
+    {
+      "type": "heading",
+      "depth": 1,
+      "children": [
+        {
+          "type": "text",
+          "value": "Title",
+          "position": {
+            "start": { "line": 1, "column": 3, "offset": 2 },
+            "end": { "line": 1, "column": 8, "offset": 7 }
+          }
+        }
+      ],
+      "position": {
+        "start": { "line": 1, "column": 1, "offset": 0 },
+        "end": { "line": 1, "column": 8, "offset": 7 }
+      }
+    },
+    {
+      "type": "paragraph",
+      "children": [
+        { "type": "text", "value": "Some " },
+        { "type": "strong", "children": [{ "type": "text", "value": "bold" }] },
+        { "type": "text", "value": " text" }
+      ]
+    },
+    {
+      "type": "list",
+      "ordered": false,
+      "start": null,
+      "spread": false,
+      "children": [
+        {
+          "type": "listItem",
+          "spread": false,
+          "checked": true,
+          "children": [
+            { "type": "paragraph", "children": [{ "type": "text", "value": "done" }] }
+          ]
+        }
+      ]
+    }
+  ]
+}"#;
+
+#[test]
+fn converts_remark_json() {
+    let interner = Interner::new();
+    let ast = from_mdast_json(REMARK_JSON).unwrap();
+    let parsed = markdown::to_mdast(
+        "# Title\n\nSome **bold** text\n\n- [x] done",
+        &markdown::ParseOptions::gfm(),
+    )
+    .unwrap();
+    assert_eq!(
+        to_minimad(&ast, &interner).unwrap(),
+        to_minimad(&parsed, &interner).unwrap()
+    );
+
+    let mdast::Node::Root(root) = &ast else {
+        panic!("Expected a root")
+    };
+    assert_eq!(
+        root.children[0].children().unwrap()[0]
+            .position()
+            .map(|position| position.start.offset),
+        Some(2)
+    );
+}
+
+#[test]
+fn reads_escapes() {
+    let ast = from_mdast_json(
+        r#"{"type": "root", "children": [{"type": "inlineCode", "value": "a\"b\\c\u00e8\ud83d\ude00\n"}]}"#,
+    )
+    .unwrap();
+    let mdast::Node::Root(root) = &ast else {
+        panic!("Expected a root")
+    };
+    assert_eq!(root.children[0].to_string(), "a\"b\\cè😀\n");
+}
+
+#[test]
+fn rejects_invalid_json() {
+    assert!(from_mdast_json(r#"{"type": "root", "children": []"#).is_err());
+    assert!(from_mdast_json(r#"{"type": "root", "children": []} x"#).is_err());
+    assert!(from_mdast_json(r#"{"type": "nope"}"#).is_err());
+}
+
+#[test]
+fn rejects_deep_nesting() {
+    assert!(from_mdast_json(&"[".repeat(200_000)).is_err());
+
+    let quotes = |depth: usize| {
+        format!(
+            r#"{}{{"type": "text", "value": "deep"}}{}"#,
+            r#"{"type": "blockquote", "children": ["#.repeat(depth),
+            "]}".repeat(depth)
+        )
+    };
+    assert!(from_mdast_json(&quotes(50)).is_ok());
+    assert!(from_mdast_json(&quotes(10_000)).is_err());
+}
@@ -0,0 +1,26 @@
+//! The converted text must borrow only from the AST
+
+use mdast2minimad::{md_parse_options, Converter, Interner, Options};
+
+fn assert_owned<T: Clone + Send + Sync + 'static>() {}
+
+#[test]
+fn options_do_not_borrow() {
+    assert_owned::<Options>();
+    assert_owned::<Converter>();
+}
+
+#[test]
+fn text_outlives_options() {
+    let interner = Interner::new();
+    let ast = markdown::to_mdast("Some *styled* text", &md_parse_options()).unwrap();
+    let text = {
+        let converter = Converter::new(Options::default());
+        converter.convert(&ast, &interner).unwrap()
+    };
+    let lossy = {
+        let converter = Converter::new(Options::default());
+        converter.convert_lossy(&ast, &interner).0
+    };
+    assert_eq!(text, lossy);
+}
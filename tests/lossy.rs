@@ -0,0 +1,66 @@
+use mdast2minimad::{
+    to_minimad_lossy, to_minimad_lossy_into, ConversionWarning, Interner, NodeKind, Options,
+};
+use minimad::Line;
+
+#[test]
+fn skips_unsupported_nodes() {
+    let interner = Interner::new();
+    let ast = markdown::to_mdast(
+        "Look at ![a cat][cat]!\n\n[dog]: dog.png\n\nThe end.\n\n[cat]: cat.png",
+        &markdown::ParseOptions::default(),
+    )
+    .unwrap();
+    let (text, warnings) = to_minimad_lossy(&ast, Options::default(), &interner);
+
+    let skipped: Vec<_> = warnings.iter().map(|warning| warning.node).collect();
+    assert_eq!(
+        skipped,
+        [
+            NodeKind::ImageReference,
+            NodeKind::Definition,
+            NodeKind::Definition
+        ]
+    );
+    assert!(warnings.iter().all(|warning| warning.position.is_some()));
+
+    // the image is replaced by a placeholder, the definitions are dropped
+    let Line::Normal(first) = &text.lines[0] else {
+        panic!("Expected a normal line")
+    };
+    let first: String = first.compounds.iter().map(|c| c.src).collect();
+    assert_eq!(first, "Look at [ImageReference]!");
+    assert!(!text.lines.iter().any(|line| matches!(
+        line,
+        Line::Normal(composite) if composite.compounds.iter().any(|c| c.src.contains(".png"))
+    )));
+}
+
+#[test]
+fn streams_warnings_to_a_sink() {
+    let interner = Interner::new();
+    let ast = markdown::to_mdast(
+        "Look at ![a cat][cat]!\n\n[dog]: dog.png\n\nThe end.\n\n[cat]: cat.png",
+        &markdown::ParseOptions::default(),
+    )
+    .unwrap();
+    let mut skipped = vec![];
+    let text = to_minimad_lossy_into(
+        &ast,
+        Options::default(),
+        &interner,
+        &mut |warning: ConversionWarning| skipped.push(warning.node),
+    );
+    assert_eq!(
+        skipped,
+        [
+            NodeKind::ImageReference,
+            NodeKind::Definition,
+            NodeKind::Definition
+        ]
+    );
+    assert_eq!(
+        text,
+        to_minimad_lossy(&ast, Options::default(), &interner).0
+    );
+}
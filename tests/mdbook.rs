@@ -0,0 +1,86 @@
+use mdast2minimad::{
+    md_parse_options,
+    mdbook::{Chapter, Summary},
+    Converter, Interner, LinkMode,
+};
+
+const SUMMARY: &str = "# Summary
+
+[Introduction](./README.md)
+
+# User guide
+
+- [Installation](guide/installation.md)
+- [Reading books](guide/reading.md)
+    - [Advanced](guide/advanced.md)
+- [Draft]()
+
+---
+
+[Contributors](misc/contributors.md)
+";
+
+#[test]
+fn parses_summary() {
+    let ast = markdown::to_mdast(SUMMARY, &md_parse_options()).unwrap();
+    let summary = Summary::parse(&ast);
+
+    let chapter = |title: &str, path: Option<&str>, sub_chapters| Chapter {
+        title: title.to_owned(),
+        path: path.map(ToOwned::to_owned),
+        sub_chapters,
+    };
+    assert_eq!(
+        summary.chapters,
+        [
+            chapter("Introduction", Some("README.md"), vec![]),
+            chapter("Installation", Some("guide/installation.md"), vec![]),
+            chapter(
+                "Reading books",
+                Some("guide/reading.md"),
+                vec![chapter("Advanced", Some("guide/advanced.md"), vec![])]
+            ),
+            chapter("Draft", None, vec![]),
+            chapter("Contributors", Some("misc/contributors.md"), vec![]),
+        ]
+    );
+    let titles: Vec<_> = summary.chapters().map(|c| &*c.title).collect();
+    assert_eq!(
+        titles,
+        [
+            "Introduction",
+            "Installation",
+            "Reading books",
+            "Advanced",
+            "Draft",
+            "Contributors"
+        ]
+    );
+}
+
+#[test]
+fn rewrites_chapter_links() {
+    let interner = Interner::new();
+    let ast = markdown::to_mdast(SUMMARY, &md_parse_options()).unwrap();
+    let summary = Summary::parse(&ast);
+
+    let mut chapter = markdown::to_mdast(
+        "See [advanced](advanced.md#tips), [the intro](../README.md) and [docs](https://docs.rs).",
+        &md_parse_options(),
+    )
+    .unwrap();
+    let converter = Converter::builder()
+        .link_mode(Some(LinkMode::UrlOnly))
+        .build();
+    let text = summary
+        .convert_chapter("guide/reading.md", &mut chapter, &converter, &interner)
+        .unwrap();
+    let minimad::Line::Normal(composite) = &text.lines[0] else {
+        panic!("Expected a normal line")
+    };
+    let line: String = composite.compounds.iter().map(|c| c.src).collect();
+    assert_eq!(
+        line,
+        "See guide/advanced.md#tips, README.md and https://docs.rs."
+    );
+}
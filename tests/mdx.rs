@@ -0,0 +1,115 @@
+//! Conversion of MDX documents to templates
+
+use mdast2minimad::{
+    to_mdx_template, to_mdx_template_with_options, Interner, Options, Placeholder,
+};
+use minimad::{Composite, CompositeStyle, Compound, Line, TableRow};
+
+fn parse(source: &str) -> mdast2minimad::mdast::Node {
+    markdown::to_mdast(source, &markdown::ParseOptions::mdx()).unwrap()
+}
+
+#[test]
+fn expressions_are_placeholders() {
+    let ast = parse("# Hi {user.name}\n\n{summary}\n\nBye *{user.name}* and {user-name}");
+    let template = to_mdx_template(&ast).unwrap();
+    assert_eq!(
+        template.placeholders(),
+        [
+            Placeholder {
+                name: "user-name".to_owned(),
+                expression: "user.name".to_owned(),
+            },
+            Placeholder {
+                name: "summary".to_owned(),
+                expression: "summary".to_owned(),
+            },
+            Placeholder {
+                name: "user-name-2".to_owned(),
+                expression: "user-name".to_owned(),
+            },
+        ]
+    );
+
+    let template = template.template();
+    let mut expander = template.expander();
+    expander
+        .set("user-name", "Ada")
+        .set("user-name-2", "Bob")
+        .set_md("summary", "A **short** one");
+    assert_eq!(
+        expander.expand().lines,
+        [
+            Line::new_header(1, vec![Compound::raw_str("Hi "), Compound::raw_str("Ada")]),
+            Line::new_paragraph(vec![]),
+            Line::new_paragraph(vec![
+                Compound::raw_str("A "),
+                Compound::raw_str("short").bold(),
+                Compound::raw_str(" one"),
+            ]),
+            Line::new_paragraph(vec![]),
+            Line::new_paragraph(vec![
+                Compound::raw_str("Bye "),
+                Compound::raw_str("Ada").italic(),
+                Compound::raw_str(" and "),
+                Compound::raw_str("Bob"),
+            ]),
+        ]
+    );
+}
+
+#[test]
+fn placeholders_in_tables() {
+    let mut parse_options = markdown::ParseOptions::mdx();
+    parse_options.constructs.gfm_table = true;
+    let ast = markdown::to_mdast(
+        "| a | b |\n|---|---|\n| x | **{value}** y |",
+        &parse_options,
+    )
+    .unwrap();
+    let template = to_mdx_template(&ast).unwrap();
+    let template = template.template();
+    let mut expander = template.expander();
+    expander.set("value", "42");
+    let text = expander.expand();
+    assert_eq!(
+        text.lines[2],
+        Line::TableRow(TableRow {
+            cells: vec![
+                Composite {
+                    style: CompositeStyle::Paragraph,
+                    compounds: vec![Compound::raw_str("x")],
+                },
+                Composite {
+                    style: CompositeStyle::Paragraph,
+                    compounds: vec![Compound::raw_str("42").bold(), Compound::raw_str(" y")],
+                },
+            ]
+        })
+    );
+}
+
+#[test]
+fn merged_placeholders_are_split() {
+    let ast = parse("Total: {count} items");
+    let options = Options::builder().merge_compounds(true).build();
+    let template = to_mdx_template_with_options(&ast, options).unwrap();
+    let template = template.template();
+    let mut expander = template.expander();
+    expander.set("count", "3");
+    assert_eq!(
+        expander.expand().lines,
+        [Line::new_paragraph(vec![
+            Compound::raw_str("Total: "),
+            Compound::raw_str("3"),
+            Compound::raw_str(" items"),
+        ])]
+    );
+}
+
+#[test]
+fn expressions_are_unsupported_elsewhere() {
+    let interner = Interner::new();
+    let ast = parse("Hi {name}");
+    assert!(mdast2minimad::to_minimad(&ast, &interner).is_err());
+}
@@ -0,0 +1,97 @@
+use mdast2minimad::{
+    md_parse_options,
+    model::{to_simple_model, to_text_model, LineModel, SimpleBlock, Span, TextModel},
+    to_minimad, Interner, Options, OwnedText,
+};
+
+#[test]
+fn blocks_of_a_document() {
+    let source = "# Title
+
+First line
+second `line`
+
+- an item
+  - a nested one
+
+```
+let x = 1;
+let y = 2;
+```
+
+| a | b |
+|---|---|
+| 1 | 2 |
+";
+    let ast = markdown::to_mdast(source, &md_parse_options()).unwrap();
+    let model = to_simple_model(&ast, Options::default()).unwrap();
+
+    let code = |text: &str| Span {
+        code: true,
+        ..Span::plain(text)
+    };
+    assert_eq!(
+        model,
+        [
+            SimpleBlock::Heading {
+                level: 1,
+                spans: vec![Span::plain("Title")]
+            },
+            SimpleBlock::Paragraph {
+                lines: vec![
+                    vec![Span::plain("First line")],
+                    vec![Span::plain("second "), code("line")]
+                ]
+            },
+            SimpleBlock::ListItem {
+                depth: 0,
+                spans: vec![Span::plain("an item")]
+            },
+            SimpleBlock::ListItem {
+                depth: 1,
+                spans: vec![Span::plain("a nested one")]
+            },
+            SimpleBlock::Code {
+                lines: vec!["let x = 1;".to_owned(), "let y = 2;".to_owned()]
+            },
+            SimpleBlock::Table {
+                rows: vec![
+                    vec![vec![Span::plain("a")], vec![Span::plain("b")]],
+                    vec![vec![Span::plain("1")], vec![Span::plain("2")]],
+                ]
+            },
+        ]
+    );
+}
+
+#[test]
+fn text_model_round_trip() {
+    let interner = Interner::new();
+    let source = "# Title
+
+Some **bold** and ~~struck~~ text
+
+> a quote
+
+- an item
+  - a nested one
+
+```
+let x = 1;
+```
+
+| a | b |
+|:--|--:|
+| 1 | 2 |
+
+***
+";
+    let ast = markdown::to_mdast(source, &md_parse_options()).unwrap();
+    let text = to_minimad(&ast, &interner).unwrap();
+    let model = to_text_model(&ast, Options::default()).unwrap();
+
+    assert_eq!(model, TextModel::from(&text));
+    assert_eq!(model.text(), text);
+    assert_eq!(TextModel::from(&OwnedText::new(&text)), model);
+    assert!(model.lines.contains(&LineModel::HorizontalRule));
+}
@@ -0,0 +1,23 @@
+use mdast2minimad::{md_parse_options, NodeKind, UnknownNodeKind};
+
+#[test]
+fn names_round_trip() {
+    for &kind in NodeKind::ALL {
+        assert_eq!(kind.as_str().parse(), Ok(kind));
+    }
+    assert_eq!(
+        "Paragraf".parse::<NodeKind>(),
+        Err(UnknownNodeKind("Paragraf".to_owned()))
+    );
+}
+
+#[test]
+fn kinds_of_nodes() {
+    let ast = markdown::to_mdast("# Title\n\n- item", &md_parse_options()).unwrap();
+    assert_eq!(NodeKind::from(&ast), NodeKind::Root);
+    let children = ast.children().unwrap();
+    assert_eq!(
+        children.iter().map(NodeKind::from).collect::<Vec<_>>(),
+        [NodeKind::Heading, NodeKind::List]
+    );
+}
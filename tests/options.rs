@@ -0,0 +1,1492 @@
+//! Tests of the behaviour of the conversion options
+
+use mdast2minimad::{
+    md_parse_options, to_minimad_reporting, to_minimad_with_options, DeepHeading, HtmlMode,
+    IndentPolicy, Interner, KbdStyle, LinkMode, Loss, NodeKind, Numbering, Options, OptionsBuilder,
+    QuotedCode, SoftBreak, Styling, WidthClass, TRUNCATION_MARK,
+};
+use minimad::{Composite, CompositeStyle, Compound, Line, Text};
+
+/// Parse and convert a source with the given parse options
+macro_rules! convert_with {
+    ($source:expr, $parse_options:expr, $options:expr) => {{
+        let ast = markdown::to_mdast($source, &$parse_options).unwrap();
+        let ast = Box::leak(Box::new(ast));
+        let interner = Box::leak(Box::new(Interner::new()));
+        to_minimad_with_options(ast, $options, interner).unwrap()
+    }};
+}
+
+/// Parse and convert a source, returning the lines and the report
+macro_rules! convert {
+    ($source:expr, $options:expr) => {{
+        let ast = markdown::to_mdast($source, &md_parse_options()).unwrap();
+        let ast = Box::leak(Box::new(ast));
+        let interner = Box::leak(Box::new(Interner::new()));
+        to_minimad_reporting(ast, $options, interner).unwrap()
+    }};
+}
+
+#[test]
+fn badges() {
+    let (text, report) = convert!(
+        "Status [[status: stable]] and [[msrv: 1.80]]",
+        Options::builder().badges(true).build()
+    );
+    assert_eq!(
+        text.lines,
+        [Line::Normal(Composite::from(vec![
+            Compound::raw_str("Status "),
+            Compound::raw_str("[status: stable]").bold(),
+            Compound::raw_str(" and "),
+            Compound::raw_str("[msrv: 1.80]").bold(),
+        ]))]
+    );
+    let badges: Vec<_> = report
+        .badges
+        .iter()
+        .map(|badge| (badge.key, badge.value))
+        .collect();
+    assert_eq!(badges, [("status", "stable"), ("msrv", "1.80")]);
+}
+
+#[test]
+fn badges_are_opt_in() {
+    let (_, report) = convert!("Status [[status: stable]]", Options::default());
+    assert!(report.badges.is_empty());
+}
+
+/// Parse options with HTML enabled
+fn with_html() -> markdown::ParseOptions {
+    let mut parse_options = md_parse_options();
+    parse_options.constructs.html_flow = true;
+    parse_options.constructs.html_text = true;
+    parse_options
+}
+
+#[test]
+fn html_strip() {
+    let text = convert_with!(
+        "<!-- a comment -->\n\nSome <b>bold</b> text",
+        with_html(),
+        Options::builder().html_mode(HtmlMode::Strip).build()
+    );
+    assert_eq!(
+        text.lines,
+        [Line::new_paragraph(vec![
+            Compound::raw_str("Some "),
+            Compound::raw_str("bold"),
+            Compound::raw_str(" text"),
+        ])]
+    );
+}
+
+#[test]
+fn html_code() {
+    let text = convert_with!(
+        "Some <b>bold</b> text",
+        with_html(),
+        Options::builder().html_mode(HtmlMode::Code).build()
+    );
+    assert_eq!(
+        text.lines,
+        [Line::new_paragraph(vec![
+            Compound::raw_str("Some "),
+            Compound::raw_str("<b>").code(),
+            Compound::raw_str("bold"),
+            Compound::raw_str("</b>").code(),
+            Compound::raw_str(" text"),
+        ])]
+    );
+}
+
+#[test]
+fn html_translate() {
+    let text = convert_with!(
+        "Some <b>bold <i>and</i></b><br>more <s>text</s> <code>x</code>",
+        with_html(),
+        Options::builder().html_mode(HtmlMode::Translate).build()
+    );
+    assert_eq!(
+        text.lines,
+        [
+            Line::new_paragraph(vec![
+                Compound::raw_str("Some "),
+                Compound::raw_str("bold ").bold(),
+                Compound::raw_str("and").bold().italic(),
+            ]),
+            Line::new_paragraph(vec![
+                Compound::raw_str("more "),
+                Compound::raw_str("text").strikeout(),
+                Compound::raw_str(" "),
+                Compound::raw_str("x").code(),
+            ])
+        ]
+    );
+}
+
+#[test]
+fn math_label() {
+    let text = convert_with!(
+        "$$\nx^2\n$$",
+        md_parse_options(),
+        Options::builder().math_label(true).build()
+    );
+    assert_eq!(
+        text.lines,
+        [
+            Line::new_code(Compound::raw_str("math").italic()),
+            Line::new_code(Compound::raw_str("x^2")),
+        ]
+    );
+}
+
+#[test]
+fn code_fences() {
+    let text = convert_with!(
+        "```rust\nfn main() {}\n```",
+        md_parse_options(),
+        Options::builder().code_fences(true).build()
+    );
+    assert_eq!(
+        text,
+        Text {
+            lines: vec![
+                Line::new_code_fence(vec![Compound::raw_str("rust")]),
+                Line::new_code(Compound::raw_str("fn main() {}")),
+                Line::empty_code_fence(),
+            ]
+        }
+    );
+}
+
+#[test]
+fn code_fences_in_lists() {
+    let text = convert_with!(
+        "- item\n\n  ```rust\n  fn main() {}\n  ```",
+        md_parse_options(),
+        Options::builder().code_fences(true).build()
+    );
+    assert!(matches!(
+        &text.lines[0],
+        Line::Normal(Composite {
+            style: CompositeStyle::ListItem(0),
+            ..
+        })
+    ));
+    assert!(text.lines.contains(&Line::empty_code_fence()));
+}
+
+#[test]
+fn list_continuation() {
+    let text = convert_with!(
+        "- item\n\n  text\n\n      code\n\n  - nested\n\n    more",
+        md_parse_options(),
+        Options::builder().list_continuation("    ").build()
+    );
+    let continued = |style, src| {
+        Line::Normal(Composite {
+            style,
+            compounds: vec![Compound::raw_str("    "), src],
+        })
+    };
+    for line in [
+        continued(CompositeStyle::Paragraph, Compound::raw_str("text")),
+        continued(CompositeStyle::Code, Compound::raw_str("code")),
+        Line::new_list_item(1, vec![Compound::raw_str("nested")]),
+        Line::Normal(Composite {
+            style: CompositeStyle::Paragraph,
+            compounds: vec![
+                Compound::raw_str("    "),
+                Compound::raw_str("    "),
+                Compound::raw_str("more"),
+            ],
+        }),
+    ] {
+        assert!(
+            text.lines.contains(&line),
+            "{line:?} not in {:?}",
+            text.lines
+        );
+    }
+}
+
+#[test]
+fn ordered_list_numbering() {
+    let source = include_str!("sources/lists/ordered.md");
+    let markers = |numbering| {
+        let text = convert_with!(
+            source,
+            md_parse_options(),
+            Options::builder().numbering(numbering).build()
+        );
+        text.lines
+            .iter()
+            .filter_map(|line| match line {
+                Line::Normal(Composite { compounds, .. }) => compounds
+                    .iter()
+                    .find(|compound| !compound.src.trim().is_empty())
+                    .map(|compound| compound.src),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        markers(Numbering::Decimal),
+        ["1. ", "2. ", "1. ", "2. ", "A bullet", "1. ", "3. "]
+    );
+    assert_eq!(
+        markers(Numbering::Outline),
+        ["1. ", "2. ", "a. ", "b. ", "A bullet", "i. ", "3. "]
+    );
+    assert_eq!(
+        markers(Numbering::Hierarchical),
+        ["1. ", "2. ", "2.1. ", "2.2. ", "A bullet", "2.2.1. ", "3. "]
+    );
+}
+
+#[test]
+fn ordered_list_start() {
+    let text = convert_with!(
+        "1. a\n2. b\n\nInterruption\n\n3. c\n4. d\n\n   0. nested",
+        md_parse_options(),
+        Options::default()
+    );
+    let markers: Vec<_> = text
+        .lines
+        .iter()
+        .filter_map(|line| match line {
+            Line::Normal(Composite { compounds, .. }) => compounds
+                .iter()
+                .find(|compound| compound.src.ends_with(". "))
+                .map(|compound| compound.src),
+            _ => None,
+        })
+        .collect();
+    // numbers that cannot be written as letters are kept as they are
+    assert_eq!(markers, ["1. ", "2. ", "3. ", "4. ", "0. "]);
+}
+
+#[test]
+fn quoted_code() {
+    let source = include_str!("sources/quotes/with_code.md");
+    let quoted = |quoted_code| {
+        convert_with!(
+            source,
+            md_parse_options(),
+            Options::builder().quoted_code(quoted_code).build()
+        )
+    };
+    let start = [
+        Line::new_quote(vec![
+            Compound::raw_str("A "),
+            Compound::raw_str("quoted").italic(),
+            Compound::raw_str(" paragraph"),
+        ]),
+        Line::new_quote(vec![]),
+    ];
+    let end = [
+        Line::new_paragraph(vec![]),
+        Line::new_paragraph(vec![Compound::raw_str("After the quote")]),
+    ];
+
+    let text = quoted(QuotedCode::Quote);
+    assert_eq!(text.lines[..2], start);
+    assert_eq!(
+        text.lines[2],
+        Line::new_quote(vec![Compound::raw_str("let x = 1;").code()])
+    );
+    assert_eq!(text.lines[3..], end);
+
+    let text = quoted(QuotedCode::Code);
+    assert_eq!(text.lines[..2], start);
+    assert_eq!(
+        text.lines[2],
+        Line::new_code(Compound::raw_str("let x = 1;"))
+    );
+    assert_eq!(text.lines[3..], end);
+}
+
+#[cfg(feature = "gfm")]
+#[test]
+fn table_layout() {
+    let (_, report) = convert!(
+        "| a | long header |\n|---|---|\n| **bold** | é |\n| 3 | 4 |",
+        Options::default()
+    );
+    let [table] = &report.tables[..] else {
+        panic!("Expected a single table")
+    };
+    assert_eq!(table.column_widths, [4, 11]);
+    assert_eq!(table.position.as_ref().unwrap().start.line, 1);
+}
+
+#[cfg(feature = "gfm")]
+#[test]
+fn narrow_tables_are_key_values() {
+    let text = convert_with!(
+        "| a | b |\n|---|---|\n| 1 | 2 |\n| 3 | 4 |",
+        md_parse_options(),
+        Options::builder().width_class(WidthClass::Narrow).build()
+    );
+    assert_eq!(
+        text.lines,
+        [
+            Line::new_paragraph(vec![
+                Compound::raw_str("a").bold(),
+                Compound::raw_str(": "),
+                Compound::raw_str("1"),
+            ]),
+            Line::new_paragraph(vec![
+                Compound::raw_str("b").bold(),
+                Compound::raw_str(": "),
+                Compound::raw_str("2"),
+            ]),
+            Line::new_paragraph(vec![]),
+            Line::new_paragraph(vec![
+                Compound::raw_str("a").bold(),
+                Compound::raw_str(": "),
+                Compound::raw_str("3"),
+            ]),
+            Line::new_paragraph(vec![
+                Compound::raw_str("b").bold(),
+                Compound::raw_str(": "),
+                Compound::raw_str("4"),
+            ]),
+        ]
+    );
+}
+
+#[test]
+fn narrow_links_go_to_appendix() {
+    let text = convert_with!(
+        "See [the docs](https://docs.rs).",
+        md_parse_options(),
+        Options::builder().width_class(WidthClass::Narrow).build()
+    );
+    assert_eq!(
+        text.lines,
+        [
+            Line::new_paragraph(vec![
+                Compound::raw_str("See "),
+                Compound::raw_str("the docs"),
+                Compound::raw_str("."),
+            ]),
+            Line::new_paragraph(vec![]),
+            Line::new_paragraph(vec![Compound::raw_str("Links").bold()]),
+            Line::new_list_item(
+                0,
+                vec![
+                    Compound::raw_str("the docs"),
+                    Compound::raw_str(": "),
+                    Compound::raw_str("https://docs.rs"),
+                ]
+            ),
+        ]
+    );
+}
+
+#[test]
+fn narrow_code_is_truncated() {
+    let long = "x".repeat(50);
+    let text = convert_with!(
+        &format!("```\nshort\n{long}\n```"),
+        md_parse_options(),
+        Options::builder().width_class(WidthClass::Narrow).build()
+    );
+    assert_eq!(text.lines[0], Line::new_code(Compound::raw_str("short")));
+    let Line::Normal(Composite { compounds, .. }) = &text.lines[1] else {
+        panic!("Expected a normal line")
+    };
+    assert_eq!(compounds[0].src.len(), 40);
+    assert_eq!(compounds[1].src, "…");
+}
+
+#[test]
+fn wide_links_show_url() {
+    let text = convert_with!(
+        "See [the docs](https://docs.rs).",
+        md_parse_options(),
+        Options::builder().width_class(WidthClass::Wide).build()
+    );
+    assert_eq!(
+        text.lines,
+        [Line::new_paragraph(vec![
+            Compound::raw_str("See "),
+            Compound::raw_str("the docs"),
+            Compound::raw_str(" ("),
+            Compound::raw_str("https://docs.rs"),
+            Compound::raw_str(")"),
+            Compound::raw_str("."),
+        ])]
+    );
+}
+
+#[test]
+fn commonmark_soft_breaks() {
+    let text = convert_with!(
+        "one\ntwo  \nthree\\\nfour",
+        md_parse_options(),
+        Options::builder().soft_break(SoftBreak::Space).build()
+    );
+    assert_eq!(
+        text.lines,
+        [
+            Line::new_paragraph(vec![
+                Compound::raw_str("one"),
+                Compound::raw_str(" "),
+                Compound::raw_str("two"),
+            ]),
+            Line::new_paragraph(vec![Compound::raw_str("three")]),
+            Line::new_paragraph(vec![Compound::raw_str("four")]),
+        ]
+    );
+}
+
+#[test]
+fn preserved_soft_breaks() {
+    let text = convert_with!(
+        "one\ntwo",
+        md_parse_options(),
+        Options::builder().soft_break(SoftBreak::Preserve).build()
+    );
+    assert_eq!(
+        text.lines,
+        [Line::new_paragraph(vec![Compound::raw_str("one\ntwo")])]
+    );
+}
+
+#[test]
+fn keyboard_shortcuts() {
+    let text = convert_with!(
+        "Press Ctrl+Shift+T, not ctrl+ or Ctrlx+Y, then <kbd>Esc</kbd>",
+        with_html(),
+        Options::builder().kbd(Some(KbdStyle::default())).build()
+    );
+    assert_eq!(
+        text.lines,
+        [Line::new_paragraph(vec![
+            Compound::raw_str("Press "),
+            Compound::raw_str("[").code(),
+            Compound::raw_str("Ctrl+Shift+T").code(),
+            Compound::raw_str("]").code(),
+            Compound::raw_str(", not ctrl+ or Ctrlx+Y, then "),
+            Compound::raw_str("[").code(),
+            Compound::raw_str("Esc").code(),
+            Compound::raw_str("]").code(),
+        ])]
+    );
+}
+
+#[test]
+fn max_output_bytes() {
+    let (text, _) = convert!(
+        "# Title\n\nA first paragraph\n\nA second paragraph",
+        Options::builder().max_output_bytes(Some(12)).build()
+    );
+    assert_eq!(
+        text.lines,
+        [
+            Line::new_header(1, vec![Compound::raw_str("Title")]),
+            Line::new_paragraph(vec![]),
+            Line::new_paragraph(vec![Compound::raw_str("A first")]),
+            Line::new_paragraph(vec![Compound::raw_str(TRUNCATION_MARK).italic()]),
+        ]
+    );
+}
+
+#[test]
+fn excerpts() {
+    let source = "# Title\n\nA first paragraph\n\nA second paragraph";
+    let (text, _) = convert!(
+        source,
+        Options::builder()
+            .max_blocks(Some(2))
+            .truncation_mark("[more]")
+            .build()
+    );
+    assert_eq!(
+        text.lines,
+        [
+            Line::new_header(1, vec![Compound::raw_str("Title")]),
+            Line::new_paragraph(vec![]),
+            Line::new_paragraph(vec![Compound::raw_str("A first paragraph")]),
+            Line::new_paragraph(vec![Compound::raw_str("[more]").italic()]),
+        ]
+    );
+
+    let (text, _) = convert!(source, Options::builder().max_lines(Some(2)).build());
+    assert_eq!(
+        text.lines,
+        [
+            Line::new_header(1, vec![Compound::raw_str("Title")]),
+            Line::new_paragraph(vec![]),
+            Line::new_paragraph(vec![Compound::raw_str(TRUNCATION_MARK).italic()]),
+        ]
+    );
+
+    // nothing is cut, so there is no mark
+    let (text, _) = convert!(
+        source,
+        Options::builder()
+            .max_blocks(Some(3))
+            .max_lines(Some(5))
+            .build()
+    );
+    assert_eq!(text.lines.len(), 5);
+}
+
+#[test]
+fn truncation_in_every_entry_point() {
+    use mdast2minimad::{to_minimad_lines, to_minimad_lossy, ConversionCache};
+
+    let source = "One\n\nTwo\n\nThree\n\nFour\n\nFive";
+    let ast = markdown::to_mdast(source, &md_parse_options()).unwrap();
+    let interner = Interner::new();
+    fn mark(mark: &str) -> Line<'_> {
+        Line::new_paragraph(vec![Compound::raw_str(mark).italic()])
+    }
+    for (name, options, len) in [
+        (
+            "max_blocks",
+            Options::builder()
+                .max_blocks(Some(2))
+                .truncation_mark("[more]")
+                .build(),
+            4,
+        ),
+        (
+            "max_lines",
+            Options::builder().max_lines(Some(3)).build(),
+            4,
+        ),
+        (
+            "max_lines at a block end",
+            Options::builder().max_lines(Some(4)).build(),
+            5,
+        ),
+        (
+            "max_output_bytes",
+            Options::builder().max_output_bytes(Some(7)).build(),
+            6,
+        ),
+    ] {
+        let expected = to_minimad_with_options(&ast, options.clone(), &interner).unwrap();
+        assert_eq!(expected.lines.len(), len, "with {name}");
+        assert_eq!(
+            expected.lines.last(),
+            Some(&mark(&options.truncation_mark)),
+            "with {name}"
+        );
+
+        let lines: Result<Vec<_>, _> = to_minimad_lines(&ast, options.clone(), &interner).collect();
+        assert_eq!(lines.unwrap(), expected.lines, "lines with {name}");
+        let (lossy, _) = to_minimad_lossy(&ast, options.clone(), &interner);
+        assert_eq!(lossy, expected, "lossy with {name}");
+        let (reported, _) = to_minimad_reporting(&ast, options.clone(), &interner).unwrap();
+        assert_eq!(reported, expected, "reporting with {name}");
+        let mut cache = ConversionCache::new(options.clone());
+        for _ in 0..2 {
+            assert_eq!(
+                cache.convert(&ast).unwrap().text(),
+                expected,
+                "cached with {name}"
+            );
+        }
+    }
+}
+
+#[cfg(feature = "gfm")]
+#[test]
+fn footnotes() {
+    let (text, _) = convert!(
+        "A reference[^note] and an inline^[Inline text] note.\n\n[^note]: The *note*.",
+        Options::builder().inline_footnotes(true).build()
+    );
+    assert_eq!(
+        text.lines,
+        [
+            Line::new_paragraph(vec![
+                Compound::raw_str("A reference"),
+                Compound::raw_str("[1]"),
+                Compound::raw_str(" and an inline"),
+                Compound::raw_str("[2]"),
+                Compound::raw_str(" note."),
+            ]),
+            Line::new_paragraph(vec![]),
+            Line::new_paragraph(vec![Compound::raw_str("Footnotes").bold()]),
+            Line::new_paragraph(vec![
+                Compound::raw_str("[1]"),
+                Compound::raw_str(" "),
+                Compound::raw_str("The "),
+                Compound::raw_str("note").italic(),
+                Compound::raw_str("."),
+            ]),
+            Line::new_paragraph(vec![
+                Compound::raw_str("[2]"),
+                Compound::raw_str(" "),
+                Compound::raw_str("Inline text"),
+            ]),
+        ]
+    );
+}
+
+#[cfg(feature = "gfm")]
+#[test]
+fn multi_block_footnotes() {
+    let (text, _) = convert!(
+        "Text[^note]\n\n[^note]: First.\n\n    Second.\n\n    - item\n\n    ```\n    code\n    ```",
+        Options::default()
+    );
+    let footnotes = text
+        .lines
+        .iter()
+        .position(|line| line == &Line::new_paragraph(vec![Compound::raw_str("Footnotes").bold()]))
+        .unwrap();
+    let continued = |style, compounds: &[&'static str]| {
+        Line::Normal(Composite {
+            style,
+            compounds: compounds.iter().copied().map(Compound::raw_str).collect(),
+        })
+    };
+    assert_eq!(
+        text.lines[footnotes + 1..],
+        [
+            continued(CompositeStyle::Paragraph, &["[1]", " ", "First."]),
+            continued(CompositeStyle::Paragraph, &["    "]),
+            continued(CompositeStyle::Paragraph, &["    ", "Second."]),
+            continued(CompositeStyle::Paragraph, &["    "]),
+            // the bullet is aligned after the marker
+            continued(CompositeStyle::ListItem(4), &["item"]),
+            continued(CompositeStyle::Paragraph, &["    "]),
+            continued(CompositeStyle::Paragraph, &["    "]),
+            continued(CompositeStyle::Code, &["    ", "code"]),
+        ]
+    );
+}
+
+/// Options without spacing after any heading
+fn without_header_spacing() -> OptionsBuilder {
+    Options::builder().options(Options::new([false; 6], Styling::default()))
+}
+
+#[test]
+fn heading_offset() {
+    let source = "# One\n\n## Two\n\n###### Six";
+    let with_offset = |heading_offset| {
+        without_header_spacing()
+            .heading_offset(heading_offset)
+            .build()
+    };
+    let depths = |text: &minimad::Text<'_>| -> Vec<u8> {
+        text.lines
+            .iter()
+            .filter_map(|line| match line {
+                Line::Normal(Composite {
+                    style: CompositeStyle::Header(depth),
+                    ..
+                }) => Some(*depth),
+                _ => None,
+            })
+            .collect()
+    };
+
+    let (text, _) = convert!(source, with_offset(2));
+    assert_eq!(depths(&text), [3, 4, 8]);
+    let (text, _) = convert!(source, with_offset(-1));
+    assert_eq!(depths(&text), [1, 1, 5]);
+}
+
+#[test]
+fn max_heading_depth() {
+    let source = "# One\n\n#### Four";
+    let (text, _) = convert!(
+        source,
+        without_header_spacing()
+            .max_heading_depth(2, DeepHeading::Clamp)
+            .build()
+    );
+    assert_eq!(
+        text.lines,
+        [
+            Line::new_header(1, vec![Compound::raw_str("One")]),
+            Line::new_header(2, vec![Compound::raw_str("Four")]),
+        ]
+    );
+
+    let (text, _) = convert!(
+        source,
+        without_header_spacing()
+            .heading_offset(1)
+            .max_heading_depth(3, DeepHeading::BoldParagraph)
+            .build()
+    );
+    assert_eq!(
+        text.lines,
+        [
+            Line::new_header(2, vec![Compound::raw_str("One")]),
+            Line::new_paragraph(vec![Compound::raw_str("Four").bold()]),
+        ]
+    );
+}
+
+#[test]
+fn number_headings() {
+    let options = || without_header_spacing().number_headings(true).build();
+    let (text, _) = convert!(
+        "## One\n\n### Sub\n\n### Sub\n\n## Two\n\n### Sub",
+        options()
+    );
+    assert_eq!(
+        text.lines,
+        [
+            Line::new_header(2, vec![Compound::raw_str("1. "), Compound::raw_str("One")]),
+            Line::new_header(3, vec![Compound::raw_str("1.1 "), Compound::raw_str("Sub")]),
+            Line::new_header(3, vec![Compound::raw_str("1.2 "), Compound::raw_str("Sub")]),
+            Line::new_header(2, vec![Compound::raw_str("2. "), Compound::raw_str("Two")]),
+            Line::new_header(3, vec![Compound::raw_str("2.1 "), Compound::raw_str("Sub")]),
+        ]
+    );
+
+    // skipped levels are numbered 0, and the number takes the style of the heading
+    let mut options = options();
+    options.heading_style[2] = Styling {
+        italic: Some(true),
+        ..Default::default()
+    };
+    let (text, _) = convert!("# One\n\n### Deep", options);
+    assert_eq!(
+        text.lines,
+        [
+            Line::new_header(1, vec![Compound::raw_str("1. "), Compound::raw_str("One")]),
+            Line::new_header(
+                3,
+                vec![
+                    Compound::raw_str("1.0.1 ").italic(),
+                    Compound::raw_str("Deep").italic()
+                ]
+            ),
+        ]
+    );
+}
+
+#[test]
+fn heading_style() {
+    let mut options = Options::default();
+    options.heading_style[3] = Styling {
+        bold: Some(true),
+        ..Default::default()
+    };
+    let (text, _) = convert!("### Third\n\n#### Fourth *deep*", options);
+    assert_eq!(
+        text.lines,
+        [
+            Line::new_header(3, vec![Compound::raw_str("Third")]),
+            Line::new_header(
+                4,
+                vec![
+                    Compound::raw_str("Fourth ").bold(),
+                    Compound::raw_str("deep").bold().italic(),
+                ]
+            ),
+        ]
+    );
+}
+
+#[test]
+fn link_modes() {
+    let source = "See [the docs](https://docs.rs) and [again](https://docs.rs).";
+    let with_mode = |link_mode| Options::builder().link_mode(Some(link_mode)).build();
+
+    let (text, _) = convert!(source, with_mode(LinkMode::UrlOnly));
+    assert_eq!(
+        text.lines,
+        [Line::new_paragraph(vec![
+            Compound::raw_str("See "),
+            Compound::raw_str("https://docs.rs"),
+            Compound::raw_str(" and "),
+            Compound::raw_str("https://docs.rs"),
+            Compound::raw_str("."),
+        ])]
+    );
+
+    let (text, _) = convert!(
+        source,
+        with_mode(LinkMode::TextWithUrl {
+            open: " <".into(),
+            close: ">".into()
+        })
+    );
+    let Line::Normal(composite) = &text.lines[0] else {
+        panic!("Expected a normal line")
+    };
+    let line: String = composite.compounds.iter().map(|c| c.src).collect();
+    assert_eq!(
+        line,
+        "See the docs <https://docs.rs> and again <https://docs.rs>."
+    );
+
+    let (text, _) = convert!(source, with_mode(LinkMode::ReferenceFootnotes));
+    assert_eq!(
+        text.lines,
+        [
+            Line::new_paragraph(vec![
+                Compound::raw_str("See "),
+                Compound::raw_str("the docs"),
+                Compound::raw_str("[1]"),
+                Compound::raw_str(" and "),
+                Compound::raw_str("again"),
+                Compound::raw_str("[1]"),
+                Compound::raw_str("."),
+            ]),
+            Line::new_paragraph(vec![]),
+            Line::new_paragraph(vec![Compound::raw_str("Footnotes").bold()]),
+            Line::new_paragraph(vec![
+                Compound::raw_str("[1]"),
+                Compound::raw_str(" "),
+                Compound::raw_str("https://docs.rs"),
+            ]),
+        ]
+    );
+}
+
+#[cfg(feature = "osc8")]
+#[test]
+fn hyperlinks() {
+    let source = "See [the docs](https://docs.rs).";
+    let (text, _) = convert!(source, Options::builder().hyperlinks(true).build());
+    assert_eq!(
+        text.lines,
+        [Line::new_paragraph(vec![
+            Compound::raw_str("See "),
+            Compound::raw_str("\x1b]8;;https://docs.rs\x1b\\"),
+            Compound::raw_str("the docs"),
+            Compound::raw_str("\x1b]8;;\x1b\\"),
+            Compound::raw_str("."),
+        ])]
+    );
+}
+
+#[test]
+fn code_styling() {
+    let (text, _) = convert!(
+        "Run [the tool](https://docs.rs)",
+        Options::builder()
+            .links_style(Styling {
+                code: Some(true),
+                ..Default::default()
+            })
+            .build()
+    );
+    assert_eq!(
+        text.lines,
+        [Line::new_paragraph(vec![
+            Compound::raw_str("Run "),
+            Compound::raw_str("the tool").code(),
+        ])]
+    );
+}
+
+#[test]
+fn scheme_styles() {
+    const SCHEME_STYLES: &[(&str, Styling)] = &[
+        (
+            "mailto",
+            Styling {
+                bold: None,
+                italic: Some(true),
+                code: None,
+                strikeout: None,
+            },
+        ),
+        (
+            "#",
+            Styling {
+                bold: Some(false),
+                italic: None,
+                code: None,
+                strikeout: None,
+            },
+        ),
+    ];
+    let (text, _) = convert!(
+        "[Mail](mailto:me@example.com), [web](https://docs.rs) and [up](#top)",
+        Options::builder()
+            .links_style(Styling {
+                bold: Some(true),
+                ..Default::default()
+            })
+            .scheme_styles(SCHEME_STYLES.iter().copied())
+            .build()
+    );
+    assert_eq!(
+        text.lines,
+        [Line::new_paragraph(vec![
+            Compound::raw_str("Mail").bold().italic(),
+            Compound::raw_str(", "),
+            Compound::raw_str("web").bold(),
+            Compound::raw_str(" and "),
+            Compound::raw_str("up"),
+        ])]
+    );
+}
+
+#[test]
+fn autolinks() {
+    let (text, _) = convert!(
+        "Visit <https://docs.rs> or [the docs](https://docs.rs)",
+        Options::builder()
+            .link_mode(Some(LinkMode::PARENTHESIZED))
+            .autolink_style(Some(Styling {
+                italic: Some(true),
+                ..Default::default()
+            }))
+            .build()
+    );
+    assert_eq!(
+        text.lines,
+        [Line::new_paragraph(vec![
+            Compound::raw_str("Visit "),
+            Compound::raw_str("https://docs.rs").italic(),
+            Compound::raw_str(" or "),
+            Compound::raw_str("the docs"),
+            Compound::raw_str(" ("),
+            Compound::raw_str("https://docs.rs"),
+            Compound::raw_str(")"),
+        ])]
+    );
+}
+
+#[test]
+fn indent_policy() {
+    // the conversion recurses on each of the hundreds of levels
+    std::thread::Builder::new()
+        .stack_size(64 << 20)
+        .spawn(deep_list_policies)
+        .unwrap()
+        .join()
+        .unwrap()
+}
+
+fn deep_list_policies() {
+    use mdast2minimad::mdast;
+
+    let interner = Interner::new();
+
+    // a list nested more than `minimad` can represent
+    let mut ast = mdast::Node::Text(mdast::Text {
+        value: "deep".to_owned(),
+        position: None,
+    });
+    for _ in 0..=u8::MAX as usize + 1 {
+        ast = mdast::Node::List(mdast::List {
+            children: vec![mdast::Node::ListItem(mdast::ListItem {
+                children: vec![ast],
+                position: None,
+                spread: false,
+                checked: None,
+            })],
+            position: None,
+            ordered: false,
+            start: None,
+            spread: false,
+        });
+    }
+    let with_policy = |indent_policy| {
+        Options::builder()
+            .indent_policy(indent_policy)
+            // each level is a list and a list item
+            .max_depth(1024)
+            .build()
+    };
+    fn deepest<'a>(text: &minimad::Text<'a>) -> Option<Line<'a>> {
+        text.lines
+            .iter()
+            .find(|line| {
+                matches!(line, Line::Normal(composite)
+                    if composite.compounds.iter().any(|c| c.src == "deep"))
+            })
+            .cloned()
+    }
+
+    assert!(to_minimad_with_options(&ast, with_policy(IndentPolicy::Error), &interner).is_err());
+
+    let text = to_minimad_with_options(&ast, with_policy(IndentPolicy::Clamp), &interner).unwrap();
+    assert!(matches!(
+        deepest(&text),
+        Some(Line::Normal(Composite {
+            style: CompositeStyle::ListItem(u8::MAX),
+            ..
+        }))
+    ));
+
+    let text =
+        to_minimad_with_options(&ast, with_policy(IndentPolicy::TextPrefix), &interner).unwrap();
+    let Some(Line::Normal(Composite {
+        style: CompositeStyle::Paragraph,
+        compounds,
+    })) = deepest(&text)
+    else {
+        panic!("Expected the deepest item as a paragraph")
+    };
+    let line: String = compounds.iter().map(|c| c.src).collect();
+    assert_eq!(line, format!("{}• deep", "  ".repeat(u8::MAX as usize + 1)));
+}
+
+#[test]
+fn node_filter() {
+    use mdast2minimad::{mdast, Converter};
+
+    let interner = Interner::new();
+
+    let converter = Converter::builder()
+        .filter(|node| !matches!(node, mdast::Node::Strong(_) | mdast::Node::Code(_)))
+        .build();
+    let ast = markdown::to_mdast(
+        "Some **loud** text\n\n```\nhidden\n```\n\nThe end",
+        &md_parse_options(),
+    )
+    .unwrap();
+    assert_eq!(
+        converter.convert(&ast, &interner).unwrap().lines,
+        [
+            Line::new_paragraph(vec![Compound::raw_str("Some "), Compound::raw_str(" text")]),
+            Line::new_paragraph(vec![]),
+            Line::new_paragraph(vec![Compound::raw_str("The end")]),
+        ]
+    );
+}
+
+#[test]
+fn unsupported_handler() {
+    use mdast2minimad::{mdast, Converter, Handled};
+
+    let interner = Interner::new();
+
+    let converter = Converter::builder()
+        .on_unsupported(|node, sink| match node {
+            mdast::Node::ImageReference(mdast::ImageReference { alt, .. }) => {
+                sink.push(Compound::raw_str(alt).italic());
+                Handled::Yes
+            }
+            mdast::Node::MdxFlowExpression(mdast::MdxFlowExpression { value, .. }) => {
+                sink.block(
+                    CompositeStyle::Code,
+                    [vec![Compound::raw_str(sink.intern(format!("{{{value}}}")))]],
+                );
+                Handled::Yes
+            }
+            _ => Handled::No,
+        })
+        .build();
+    let ast = mdast::Node::Root(mdast::Root {
+        children: vec![
+            mdast::Node::Paragraph(mdast::Paragraph {
+                children: vec![
+                    mdast::Node::Text(mdast::Text {
+                        value: "A ".to_owned(),
+                        position: None,
+                    }),
+                    mdast::Node::ImageReference(mdast::ImageReference {
+                        alt: "cat".to_owned(),
+                        identifier: "cat".to_owned(),
+                        label: None,
+                        reference_kind: mdast::ReferenceKind::Shortcut,
+                        position: None,
+                    }),
+                ],
+                position: None,
+            }),
+            mdast::Node::MdxFlowExpression(mdast::MdxFlowExpression {
+                value: "answer".to_owned(),
+                position: None,
+                stops: vec![],
+            }),
+        ],
+        position: None,
+    });
+    assert_eq!(
+        converter.convert(&ast, &interner).unwrap().lines,
+        [
+            Line::new_paragraph(vec![
+                Compound::raw_str("A "),
+                Compound::raw_str("cat").italic()
+            ]),
+            Line::new_paragraph(vec![]),
+            Line::new_code(Compound::raw_str("{answer}")),
+        ]
+    );
+
+    // other nodes are still errors
+    let ast = mdast::Node::Definition(mdast::Definition {
+        url: "cat.png".to_owned(),
+        title: None,
+        identifier: "cat".to_owned(),
+        label: None,
+        position: None,
+    });
+    assert!(converter.convert(&ast, &interner).is_err());
+}
+
+#[test]
+fn image_text() {
+    use mdast2minimad::ImageText;
+
+    let source = "![A cat](img/cat.png) ![](img/dog.png \"A dog\") ![](img/bird.png) ![](/)";
+    let (text, _) = convert!(source, Options::default());
+    assert_eq!(
+        text.lines,
+        [Line::new_paragraph(vec![
+            Compound::raw_str("A cat").italic(),
+            Compound::raw_str(" "),
+            Compound::raw_str("A dog").italic(),
+            Compound::raw_str(" "),
+            Compound::raw_str("bird.png").italic(),
+            Compound::raw_str(" "),
+            Compound::raw_str("image").italic(),
+        ])]
+    );
+
+    let (text, _) = convert!(
+        source,
+        Options::builder()
+            .image_text([ImageText::UrlBasename])
+            .build()
+    );
+    let Line::Normal(composite) = &text.lines[0] else {
+        panic!("Expected a normal line")
+    };
+    let line: String = composite.compounds.iter().map(|c| c.src).collect();
+    assert_eq!(line, "cat.png dog.png bird.png image");
+}
+
+#[test]
+fn merged_compounds() {
+    let text = convert_with!(
+        "one\ntwo **three** four\nfive",
+        md_parse_options(),
+        Options::builder()
+            .soft_break(SoftBreak::Space)
+            .merge_compounds(true)
+            .build()
+    );
+    assert_eq!(
+        text.lines,
+        [Line::new_paragraph(vec![
+            Compound::raw_str("one two "),
+            Compound::raw_str("three").bold(),
+            Compound::raw_str(" four five"),
+        ])]
+    );
+}
+
+#[test]
+fn comment_directives() {
+    use mdast2minimad::{Converter, Directive};
+
+    let interner = Interner::new();
+
+    let ast = markdown::to_mdast(
+        "Text <!-- md2term: version --> and <!-- other comment -->\n\n<!-- md2term: rule -->",
+        &markdown::ParseOptions::default(),
+    )
+    .unwrap();
+    let converter = Converter::builder()
+        .html_mode(HtmlMode::Strip)
+        .directives("md2term:", |directive, sink| match directive {
+            "version" => {
+                sink.push(Compound::raw_str("1.0").bold());
+                Directive::Done
+            }
+            _ => Directive::Unknown,
+        })
+        .build();
+    assert_eq!(
+        converter.convert(&ast, &interner).unwrap().lines,
+        [Line::new_paragraph(vec![
+            Compound::raw_str("Text "),
+            Compound::raw_str("1.0").bold(),
+            Compound::raw_str(" and "),
+        ])]
+    );
+}
+
+#[test]
+fn global_indent() {
+    let text = convert_with!(
+        "# Title\n\nText\n\n- item\n  - nested\n\n---",
+        md_parse_options(),
+        Options::builder().indent(2).build()
+    );
+    assert_eq!(
+        text.lines,
+        [
+            Line::new_header(1, vec![Compound::raw_str("  "), Compound::raw_str("Title")]),
+            Line::new_paragraph(vec![]),
+            Line::new_paragraph(vec![Compound::raw_str("  "), Compound::raw_str("Text")]),
+            Line::new_paragraph(vec![]),
+            Line::new_list_item(2, vec![Compound::raw_str("item")]),
+            // blank lines are not indented
+            Line::new_paragraph(vec![Compound::raw_str("  ")]),
+            Line::new_list_item(3, vec![Compound::raw_str("nested")]),
+            Line::new_paragraph(vec![Compound::raw_str("  ")]),
+            Line::new_paragraph(vec![]),
+            Line::new_paragraph(vec![]),
+            Line::HorizontalRule,
+            Line::new_paragraph(vec![]),
+        ]
+    );
+}
+
+#[test]
+fn spacing_policy() {
+    use mdast2minimad::Spacing;
+
+    let source = "## Heading\n```\ncode\n```\nText\n- a\n\n- b\n\nEnd";
+    let options = |spacing| Options::builder().spacing(spacing).build();
+    let paragraph = |text| Line::new_paragraph(vec![Compound::raw_str(text)]);
+    let empty = || Line::new_paragraph(vec![]);
+
+    let text = convert_with!(source, md_parse_options(), options(Spacing::default()));
+    assert_eq!(
+        text.lines,
+        [
+            Line::new_header(2, vec![Compound::raw_str("Heading")]),
+            Line::new_code(Compound::raw_str("code")),
+            empty(),
+            paragraph("Text"),
+            empty(),
+            Line::new_list_item(0, vec![Compound::raw_str("a")]),
+            // the list is loose
+            empty(),
+            Line::new_list_item(0, vec![Compound::raw_str("b")]),
+            empty(),
+            empty(),
+            paragraph("End"),
+        ]
+    );
+
+    let text = convert_with!(
+        source,
+        md_parse_options(),
+        options(Spacing {
+            loose_items: false,
+            ..Default::default()
+        })
+    );
+    assert_eq!(
+        text.lines[5..8],
+        [
+            Line::new_list_item(0, vec![Compound::raw_str("a")]),
+            Line::new_list_item(0, vec![Compound::raw_str("b")]),
+            empty(),
+        ]
+    );
+
+    let text = convert_with!(
+        source,
+        md_parse_options(),
+        options(Spacing {
+            before_code: true,
+            after_code: false,
+            after_paragraphs: false,
+            before_lists: true,
+            loose_items: true,
+            collapse_blank_lines: true,
+            ..Default::default()
+        })
+    );
+    assert_eq!(
+        text.lines,
+        [
+            Line::new_header(2, vec![Compound::raw_str("Heading")]),
+            empty(),
+            Line::new_code(Compound::raw_str("code")),
+            paragraph("Text"),
+            empty(),
+            Line::new_list_item(0, vec![Compound::raw_str("a")]),
+            empty(),
+            Line::new_list_item(0, vec![Compound::raw_str("b")]),
+            empty(),
+            paragraph("End"),
+        ]
+    );
+}
+
+#[test]
+fn spacing_rules() {
+    use mdast2minimad::{NodeKind, Spacing, SpacingRule, DEFAULT_SPACING_RULES};
+
+    let paragraph = |text| Line::new_paragraph(vec![Compound::raw_str(text)]);
+    let empty = || Line::new_paragraph(vec![]);
+
+    // consecutive code blocks are kept apart by default
+    let source = "    a\n\n```\nb\n```";
+    let no_after_code = |rules: &[SpacingRule]| {
+        Options::builder()
+            .spacing(Spacing {
+                after_code: false,
+                rules: rules.into(),
+                ..Default::default()
+            })
+            .build()
+    };
+    let text = convert_with!(
+        source,
+        md_parse_options(),
+        no_after_code(DEFAULT_SPACING_RULES)
+    );
+    assert_eq!(
+        text.lines,
+        [
+            Line::new_code(Compound::raw_str("a")),
+            empty(),
+            Line::new_code(Compound::raw_str("b")),
+        ]
+    );
+    let text = convert_with!(source, md_parse_options(), no_after_code(&[]));
+    assert_eq!(
+        text.lines,
+        [
+            Line::new_code(Compound::raw_str("a")),
+            Line::new_code(Compound::raw_str("b")),
+        ]
+    );
+
+    // rules override the spacing asked by the blocks
+    const RULES: &[SpacingRule] = &[SpacingRule {
+        after: NodeKind::Heading,
+        before: NodeKind::Paragraph,
+        empty_line: false,
+    }];
+    let options = |rules: &[SpacingRule]| {
+        Options::builder()
+            .spacing(Spacing {
+                rules: rules.into(),
+                ..Default::default()
+            })
+            .build()
+    };
+    let text = convert_with!("# Title\n\nText", md_parse_options(), options(RULES));
+    assert_eq!(
+        text.lines,
+        [
+            Line::new_header(1, vec![Compound::raw_str("Title")]),
+            paragraph("Text"),
+        ]
+    );
+    let text = convert_with!(
+        "# Title\n\nText",
+        md_parse_options(),
+        options(DEFAULT_SPACING_RULES)
+    );
+    assert_eq!(
+        text.lines,
+        [
+            Line::new_header(1, vec![Compound::raw_str("Title")]),
+            empty(),
+            paragraph("Text"),
+        ]
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn options_from_config() {
+    use serde::{de::value::MapDeserializer, Deserialize};
+
+    let config = [
+        ("width_class", "Narrow"),
+        ("html_mode", "Strip"),
+        ("truncation_mark", "[more]"),
+        ("list_continuation", "    "),
+    ];
+    let options = Options::deserialize(MapDeserializer::<_, serde::de::value::Error>::new(
+        config.into_iter(),
+    ))
+    .unwrap();
+
+    assert_eq!(options.width_class, WidthClass::Narrow);
+    assert_eq!(options.html_mode, HtmlMode::Strip);
+    assert_eq!(options.truncation_mark, "[more]");
+    assert_eq!(options.list_continuation, "    ");
+    // the other fields keep their default
+    let default = Options::default();
+    assert_eq!(options.numbering, default.numbering);
+    assert_eq!(options.spacing, default.spacing);
+    assert_eq!(options.header_spacing, default.header_spacing);
+}
+
+#[test]
+fn options_builder() {
+    let options = Options::builder()
+        .header_spacing(2, true)
+        .width_class(WidthClass::Wide)
+        .max_lines(Some(3))
+        .build();
+    assert_eq!(
+        options.header_spacing,
+        [true, true, false, false, false, false]
+    );
+    assert_eq!(options.width_class, WidthClass::Wide);
+    assert_eq!(options.max_lines, Some(3));
+
+    let links_style = Styling {
+        italic: Some(true),
+        ..Default::default()
+    };
+    let options = Options::new([false; 6], links_style);
+    assert_eq!(options.header_spacing, [false; 6]);
+    assert_eq!(options.links_style.italic, Some(true));
+    assert_eq!(options.numbering, Options::default().numbering);
+}
+
+#[test]
+fn reports_lossy_decisions() {
+    let interner = Interner::new();
+    let source = "# One\n\n#### Four\n\nA [link](https://example.com), <https://example.org> and ![image](cat.png)\n\n<div>html</div>";
+    let ast = markdown::to_mdast(source, &with_html()).unwrap();
+    let options = Options::builder()
+        .max_heading_depth(2, DeepHeading::Clamp)
+        .html_mode(HtmlMode::Strip)
+        .build();
+    let (_, report) = to_minimad_reporting(&ast, options, &interner).unwrap();
+    let losses: Vec<_> = report
+        .losses
+        .iter()
+        .map(|decision| {
+            (
+                decision.node,
+                decision.loss,
+                decision.position.as_ref().unwrap().start.line,
+            )
+        })
+        .collect();
+    assert_eq!(
+        losses,
+        [
+            (
+                NodeKind::Heading,
+                Loss::ClampedHeading {
+                    depth: 4,
+                    rendered: 2
+                },
+                3
+            ),
+            (NodeKind::Link, Loss::DroppedUrl, 5),
+            (NodeKind::Image, Loss::DroppedUrl, 5),
+            (NodeKind::Html, Loss::DroppedHtml, 7),
+        ]
+    );
+    assert_eq!(
+        report.losses[0].to_string(),
+        "`Heading` node: heading of depth 4 rendered at depth 2"
+    );
+
+    // rendering the URLs loses nothing
+    let options = Options::builder()
+        .link_mode(Some(LinkMode::PARENTHESIZED))
+        .build();
+    let (_, report) = convert!("A [link](https://example.com)", options);
+    assert!(report.losses.is_empty());
+}
@@ -0,0 +1,33 @@
+use mdast2minimad::{md_parse_options, pages::paginate, to_minimad_reporting, Interner, Options};
+use minimad::{Compound, Line};
+
+#[test]
+fn form_feeds_break_pages() {
+    let interner = Interner::new();
+    let ast =
+        markdown::to_mdast("one\n\ntwo\u{c}three\n\nfour\n\nfive", &md_parse_options()).unwrap();
+    let (text, report) = to_minimad_reporting(
+        &ast,
+        Options::builder().form_feed_page_breaks(true).build(),
+        &interner,
+    )
+    .unwrap();
+    let paragraph = |text| Line::new_paragraph(vec![Compound::raw_str(text)]);
+    assert_eq!(report.page_breaks, [3]);
+    assert_eq!(
+        paginate(&text.lines, 3, &report.page_breaks),
+        [
+            &[
+                paragraph("one"),
+                Line::new_paragraph(vec![]),
+                paragraph("two")
+            ][..],
+            &[
+                paragraph("three"),
+                Line::new_paragraph(vec![]),
+                paragraph("four")
+            ][..],
+            &[paragraph("five")][..],
+        ]
+    );
+}
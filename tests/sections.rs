@@ -0,0 +1,279 @@
+use mdast2minimad::{
+    md_parse_options,
+    sections::{
+        extract_section, sections, to_minimad_section, to_minimad_sections, HeadingInfo,
+        SectionCache, SectionSelector,
+    },
+    to_minimad_reporting, Converter, HeadingAnchor, Interner, Options,
+};
+
+const DOC: &str = "Intro
+
+# Getting started
+
+Some text
+
+## Usage
+
+More text
+
+# Usage
+
+Again
+";
+
+#[test]
+fn splits_at_headings() {
+    let ast = markdown::to_mdast(DOC, &md_parse_options()).unwrap();
+    let slugs: Vec<_> = sections(&ast)
+        .into_iter()
+        .map(|section| section.slug)
+        .collect();
+    assert_eq!(slugs, ["", "getting-started", "usage", "usage-1"]);
+}
+
+#[test]
+fn caches_by_content() {
+    let interner = Interner::new();
+    let ast = markdown::to_mdast(DOC, &md_parse_options()).unwrap();
+    let sections = sections(&ast);
+    let mut cache = SectionCache::new(Converter::default());
+
+    assert!(!cache.contains(&sections[1]));
+    let text = cache.get(&sections[1]).unwrap().clone();
+    assert!(cache.contains(&sections[1]));
+    assert_eq!(
+        text.text(),
+        sections[1]
+            .section
+            .convert(&Converter::default(), &interner)
+            .unwrap()
+    );
+
+    // a moved section with the same content is still cached
+    let moved = markdown::to_mdast(&format!("\n\n\n{DOC}"), &md_parse_options()).unwrap();
+    let moved = mdast2minimad::sections::sections(&moved);
+    assert_eq!(
+        moved[1].section.content_hash(),
+        sections[1].section.content_hash()
+    );
+
+    assert!(cache.invalidate("getting-started"));
+    assert!(!cache.contains(&sections[1]));
+}
+
+#[test]
+fn cache_outlives_the_document() {
+    let mut cache = SectionCache::new(Converter::default());
+    let text = {
+        let ast = markdown::to_mdast(DOC, &md_parse_options()).unwrap();
+        cache.get(&sections(&ast)[1]).unwrap().clone()
+    };
+
+    let ast = markdown::to_mdast(DOC, &md_parse_options()).unwrap();
+    let sections = sections(&ast);
+    assert!(cache.contains(&sections[1]));
+    assert_eq!(cache.get(&sections[1]).unwrap(), &text);
+}
+
+#[test]
+fn cache_sees_the_references() {
+    let mut cache = SectionCache::new(Converter::default());
+    let before = markdown::to_mdast(
+        "# Notes\n\nA[^a] B[^b]\n\n[^a]: First\n\n[^b]: Second",
+        &md_parse_options(),
+    )
+    .unwrap();
+    cache.get(&sections(&before)[0]).unwrap();
+
+    let after = markdown::to_mdast(
+        "# Notes\n\nA[^b] B[^a]\n\n[^a]: First\n\n[^b]: Second",
+        &md_parse_options(),
+    )
+    .unwrap();
+    let after = sections(&after);
+    assert!(!cache.contains(&after[0]));
+    let interner = Interner::new();
+    assert_eq!(
+        cache.get(&after[0]).unwrap().text(),
+        after[0]
+            .section
+            .convert(&Converter::default(), &interner)
+            .unwrap()
+    );
+}
+
+#[test]
+fn hashes_deep_sections() {
+    use mdast2minimad::{mdast, sections::Section};
+
+    let mut node = mdast::Node::Text(mdast::Text {
+        value: "deep".into(),
+        position: None,
+    });
+    for _ in 0..100_000 {
+        node = mdast::Node::Blockquote(mdast::Blockquote {
+            children: vec![node],
+            position: None,
+        });
+    }
+    let nodes = [node];
+    let section = Section {
+        heading: None,
+        nodes: &nodes,
+    };
+    assert_eq!(section.content_hash(), section.content_hash());
+    // dropping the tree recurses too
+    std::mem::forget(nodes);
+}
+
+#[test]
+fn slug_normalization() {
+    use mdast2minimad::sections::{slug, slug_with, SlugOptions};
+
+    assert_eq!(slug("🚀 Launch - Now!"), "-launch---now");
+    assert_eq!(
+        slug_with(
+            "🚀 Launch - Now!",
+            &SlugOptions {
+                collapse_separators: true,
+                ..Default::default()
+            }
+        ),
+        "launch-now"
+    );
+    assert_eq!(
+        slug_with(
+            "🚀 Launch",
+            &SlugOptions {
+                keep_emoji: true,
+                ..Default::default()
+            }
+        ),
+        "🚀-launch"
+    );
+}
+
+#[cfg(feature = "transliterate")]
+#[test]
+fn slug_transliteration() {
+    use mdast2minimad::sections::{slug_with, SlugOptions};
+
+    assert_eq!(
+        slug_with(
+            "Café Crème",
+            &SlugOptions {
+                transliterate: true,
+                ..Default::default()
+            }
+        ),
+        "cafe-creme"
+    );
+}
+
+#[test]
+fn extracts_sections() {
+    let interner = Interner::new();
+    let ast = markdown::to_mdast(DOC, &md_parse_options()).unwrap();
+
+    // up to the next heading of the same depth, subsections included
+    let started = extract_section(&ast, SectionSelector::Title(" GETTING started ")).unwrap();
+    assert_eq!(started.len(), 4);
+    // duplicated slugs are told apart
+    let usage = extract_section(&ast, SectionSelector::Slug("usage-1")).unwrap();
+    assert_eq!(usage.len(), 2);
+    assert_eq!(usage[1].to_string(), "Again");
+    assert_eq!(
+        extract_section(&ast, SectionSelector::Slug("usage")).unwrap(),
+        &started[2..]
+    );
+    assert!(extract_section(&ast, SectionSelector::Title("Missing")).is_none());
+
+    let text = to_minimad_section(
+        &ast,
+        SectionSelector::Slug("usage-1"),
+        Options::default(),
+        &interner,
+    )
+    .unwrap()
+    .unwrap();
+    assert_eq!(
+        text.lines.last(),
+        Some(&minimad::Line::new_paragraph(vec![
+            minimad::Compound::raw_str("Again")
+        ]))
+    );
+    assert!(to_minimad_section(
+        &ast,
+        SectionSelector::Slug("missing"),
+        Options::default(),
+        &interner
+    )
+    .unwrap()
+    .is_none());
+}
+
+#[test]
+fn converts_top_level_sections() {
+    let interner = Interner::new();
+    let ast = markdown::to_mdast(DOC, &md_parse_options()).unwrap();
+    let sections = to_minimad_sections(&ast, &interner).unwrap();
+
+    let infos: Vec<_> = sections.iter().map(|(info, _)| info).collect();
+    assert_eq!(
+        infos,
+        [
+            &HeadingInfo::default(),
+            &HeadingInfo {
+                depth: 1,
+                title: "Getting started".to_owned(),
+                slug: "getting-started".to_owned(),
+            },
+            // the subsection took the plain slug
+            &HeadingInfo {
+                depth: 1,
+                title: "Usage".to_owned(),
+                slug: "usage-1".to_owned(),
+            },
+        ]
+    );
+    // subsections stay in their section
+    let (_, started) = &sections[1];
+    assert!(started
+        .lines
+        .iter()
+        .any(|line| line
+            == &minimad::Line::new_paragraph(vec![minimad::Compound::raw_str("More text")])));
+}
+
+#[test]
+fn reports_heading_anchors() {
+    let interner = Interner::new();
+    let ast = markdown::to_mdast(DOC, &md_parse_options()).unwrap();
+    let (text, report) = to_minimad_reporting(&ast, Options::default(), &interner).unwrap();
+
+    let slugs: Vec<_> = report
+        .anchors
+        .iter()
+        .map(|HeadingAnchor { slug, depth, .. }| (&**slug, *depth))
+        .collect();
+    assert_eq!(
+        slugs,
+        [("getting-started", 1), ("usage", 2), ("usage-1", 1)]
+    );
+    // the anchors point to the heading lines
+    for anchor in &report.anchors {
+        assert!(matches!(
+            text.lines[anchor.line],
+            minimad::Line::Normal(minimad::Composite {
+                style: minimad::CompositeStyle::Header(depth),
+                ..
+            }) if depth == anchor.depth
+        ));
+    }
+    let last = report.anchors.last().unwrap().line;
+    assert_eq!(
+        text.lines[last],
+        minimad::Line::new_header(1, vec![minimad::Compound::raw_str("Usage")])
+    );
+}
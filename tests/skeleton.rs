@@ -0,0 +1,67 @@
+use mdast2minimad::{
+    md_parse_options,
+    skeleton::{self, SkeletonOptions},
+    Interner,
+};
+use minimad::{Compound, Line};
+
+const DOCUMENT: &str = "# Title
+
+An introduction. With many sentences.
+
+## Usage
+
+- a list
+
+Some text.
+
+---
+
+## Details
+
+Details follow
+on many lines
+";
+
+#[test]
+fn headings_and_first_sentences() {
+    let interner = Interner::new();
+    let ast = markdown::to_mdast(DOCUMENT, &md_parse_options()).unwrap();
+
+    let text = skeleton::to_minimad(&ast, &SkeletonOptions::default(), &interner).unwrap();
+    assert_eq!(
+        text.lines,
+        [
+            Line::new_header(1, vec![Compound::raw_str("Title")]),
+            Line::new_paragraph(vec![]),
+            Line::new_header(2, vec![Compound::raw_str("Usage")]),
+            Line::HorizontalRule,
+            Line::new_paragraph(vec![]),
+            Line::new_header(2, vec![Compound::raw_str("Details")]),
+        ]
+    );
+
+    let text = skeleton::to_minimad(
+        &ast,
+        &SkeletonOptions {
+            first_sentence: true,
+            ..Default::default()
+        },
+        &interner,
+    )
+    .unwrap();
+    assert_eq!(
+        text.lines,
+        [
+            Line::new_header(1, vec![Compound::raw_str("Title")]),
+            Line::new_paragraph(vec![]),
+            Line::new_paragraph(vec![Compound::raw_str("An introduction.")]),
+            Line::new_paragraph(vec![]),
+            Line::new_header(2, vec![Compound::raw_str("Usage")]),
+            Line::HorizontalRule,
+            Line::new_paragraph(vec![]),
+            Line::new_header(2, vec![Compound::raw_str("Details")]),
+            Line::new_paragraph(vec![Compound::raw_str("Details follow")]),
+        ]
+    );
+}
@@ -0,0 +1,55 @@
+use mdast2minimad::{
+    md_parse_options,
+    slides::{slides, SlideOptions},
+    Interner, Styling,
+};
+use minimad::{Compound, Line};
+
+const DOCUMENT: &str = "---
+
+# Welcome
+
+## Subtitle
+
+---
+
+---
+
+## Agenda
+
+- one
+- two
+";
+
+#[test]
+fn split_at_rules() {
+    let interner = Interner::new();
+    let ast = markdown::to_mdast(DOCUMENT, &md_parse_options()).unwrap();
+    let slides = slides(
+        &ast,
+        &SlideOptions {
+            title_style: Some(Styling {
+                bold: Some(true),
+                italic: None,
+                code: None,
+                strikeout: None,
+            }),
+            ..Default::default()
+        },
+        &interner,
+    )
+    .unwrap();
+    assert_eq!(slides.len(), 2);
+    assert_eq!(
+        slides[0].lines,
+        [
+            Line::new_header(1, vec![Compound::raw_str("Welcome").bold()]),
+            Line::new_paragraph(vec![]),
+            Line::new_header(2, vec![Compound::raw_str("Subtitle")]),
+        ]
+    );
+    assert_eq!(
+        slides[1].lines[0],
+        Line::new_header(2, vec![Compound::raw_str("Agenda").bold()]),
+    );
+}
@@ -0,0 +1,35 @@
+use mdast2minimad::emitter::state::ContentState;
+use minimad::{CompositeStyle, Compound, Line};
+
+#[test]
+fn invalid_transitions_recover() {
+    let mut state = ContentState::default();
+    state.flow();
+    // text outside of a block starts a paragraph
+    state.line().push(Compound::raw_str("loose"));
+    assert_eq!(
+        state.take_invalid(),
+        Some("phrasing content outside of a paragraph")
+    );
+    // a block inside a line flushes it
+    let outer = state.open_phrasing(CompositeStyle::Quote);
+    assert_eq!(
+        state.take_invalid(),
+        Some("flow content inside phrasing content")
+    );
+    state.line().push(Compound::raw_str("quoted"));
+    state.close_phrasing(outer, true);
+    assert!(state.is_phrasing());
+    state.flush();
+
+    assert_eq!(state.model, None);
+    assert_eq!(
+        state.lines,
+        [
+            Line::new_paragraph(vec![Compound::raw_str("loose")]),
+            Line::new_quote(vec![Compound::raw_str("quoted")]),
+            // the rest of the interrupted line
+            Line::new_paragraph(vec![]),
+        ]
+    );
+}
@@ -0,0 +1,72 @@
+//! Checks of the converted texts as rendered by `termimad`
+
+use mdast2minimad::{md_parse_options, to_minimad_with_options, Interner, Options};
+use termimad::{FmtText, MadSkin, StyledChar};
+
+/// Render a source with the given bullet, dropping the blank lines and the trailing spaces
+fn render(source: &str, bullet: char, options: Options) -> Vec<String> {
+    let interner = Interner::new();
+    let ast = markdown::to_mdast(source, &md_parse_options()).unwrap();
+    let text = to_minimad_with_options(&ast, options, &interner).unwrap();
+    let mut skin = MadSkin::no_style();
+    skin.bullet = StyledChar::nude(bullet);
+    FmtText::from_text(&skin, text, Some(80))
+        .to_string()
+        .lines()
+        .map(str::trim_end)
+        .filter(|line| !line.is_empty())
+        .map(str::to_owned)
+        .collect()
+}
+
+#[test]
+fn continuations_are_aligned_under_the_bullets() {
+    let source =
+        "- first\n\n  continued\n\n  - nested\n\n    nested continued\n\n1. one\n\n   continued";
+    for (bullet, width, expected) in [
+        (
+            '•',
+            1,
+            [
+                "• first",
+                "  continued",
+                "  • nested",
+                "    nested continued",
+                "1. one",
+                "   continued",
+            ],
+        ),
+        (
+            '＊',
+            2,
+            [
+                "＊ first",
+                "   continued",
+                "   ＊ nested",
+                "      nested continued",
+                "1. one",
+                "   continued",
+            ],
+        ),
+    ] {
+        let options = Options::builder().bullet_width(Some(width)).build();
+        assert_eq!(render(source, bullet, options), expected);
+    }
+}
+
+#[cfg(feature = "termimad")]
+#[test]
+fn renders_in_one_call() {
+    let interner = Interner::new();
+    let source = "# Title\n\nSome **bold** text, long enough to be wrapped";
+    let ast = markdown::to_mdast(source, &md_parse_options()).unwrap();
+    let skin = MadSkin::no_style();
+    let rendered = mdast2minimad::render_to_string(&ast, &skin, Some(20)).unwrap();
+
+    let text = mdast2minimad::to_minimad(&ast, &interner).unwrap();
+    assert_eq!(
+        rendered,
+        FmtText::from_text(&skin, text, Some(20)).to_string()
+    );
+    assert!(rendered.lines().all(|line| line.chars().count() <= 20));
+}
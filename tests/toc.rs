@@ -0,0 +1,86 @@
+use mdast2minimad::{
+    md_parse_options,
+    toc::{toc, TocOptions},
+    Interner, Numbering,
+};
+use minimad::{Compound, Line};
+
+const DOCUMENT: &str = "Intro
+
+## Install
+
+Text
+
+## Usage
+
+### With *flags*
+
+#### Too deep
+
+- ## Not listed
+
+## Credits
+";
+
+#[test]
+fn depth_limited_toc() {
+    let interner = Interner::new();
+    let ast = markdown::to_mdast(DOCUMENT, &md_parse_options()).unwrap();
+    let text = toc(
+        &ast,
+        &TocOptions {
+            max_depth: 3,
+            ..Default::default()
+        },
+        &interner,
+    )
+    .unwrap();
+    assert_eq!(
+        text.lines,
+        [
+            Line::new_list_item(0, vec![Compound::raw_str("Install")]),
+            Line::new_list_item(0, vec![Compound::raw_str("Usage")]),
+            Line::new_list_item(
+                1,
+                vec![
+                    Compound::raw_str("With "),
+                    Compound::raw_str("flags").italic()
+                ]
+            ),
+            Line::new_list_item(0, vec![Compound::raw_str("Credits")]),
+        ]
+    );
+}
+
+#[test]
+fn numbered_toc() {
+    let interner = Interner::new();
+    let ast = markdown::to_mdast(DOCUMENT, &md_parse_options()).unwrap();
+    let text = toc(
+        &ast,
+        &TocOptions {
+            numbering: Some(Numbering::Hierarchical),
+            ..Default::default()
+        },
+        &interner,
+    )
+    .unwrap();
+    let titles: Vec<String> = text
+        .lines
+        .iter()
+        .map(|line| match line {
+            Line::Normal(composite) => composite.compounds.iter().map(|c| c.src).collect(),
+            _ => panic!("Expected only normal lines"),
+        })
+        .collect();
+    assert_eq!(
+        titles,
+        [
+            "1. Install",
+            "2. Usage",
+            "  2.1. With flags",
+            "    2.1.1. Too deep",
+            "3. Credits",
+        ]
+    );
+}
@@ -0,0 +1,110 @@
+//! Spans and events of the conversion, with the `tracing` feature
+
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use mdast2minimad::{md_parse_options, to_minimad, Interner};
+use tracing::{
+    field::{Field, Visit},
+    span, Event, Metadata, Subscriber,
+};
+
+/// Fields of a span or an event, as `name=value`
+#[derive(Default)]
+struct Fields(Vec<String>);
+
+impl Visit for Fields {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.push(format!("{}={value}", field.name()))
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0.push(format!("{}={value:?}", field.name()))
+    }
+}
+
+/// Subscriber keeping the spans and the events of the crate, indented by the entered spans
+#[derive(Default)]
+struct Collector {
+    next_id: AtomicU64,
+    entered: AtomicUsize,
+    records: Arc<Mutex<Vec<String>>>,
+}
+
+impl Collector {
+    fn push(&self, record: String) {
+        let indent = self.entered.load(Ordering::Relaxed) * 2;
+        self.records
+            .lock()
+            .unwrap()
+            .push(format!("{:indent$}{record}", ""));
+    }
+}
+
+impl Subscriber for Collector {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        metadata.target().starts_with("mdast2minimad")
+    }
+
+    fn new_span(&self, span: &span::Attributes<'_>) -> span::Id {
+        let mut fields = Fields::default();
+        span.record(&mut fields);
+        self.push(format!(
+            "span {}: {}",
+            span.metadata().name(),
+            fields.0.join(", ")
+        ));
+        span::Id::from_u64(self.next_id.fetch_add(1, Ordering::Relaxed) + 1)
+    }
+
+    fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+
+    fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut fields = Fields::default();
+        event.record(&mut fields);
+        self.push(format!("event: {}", fields.0.join(", ")));
+    }
+
+    fn enter(&self, _: &span::Id) {
+        self.entered.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn exit(&self, _: &span::Id) {
+        self.entered.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+#[test]
+fn traces_the_emitted_nodes() {
+    let collector = Collector::default();
+    let records = Arc::clone(&collector.records);
+    let interner = Interner::new();
+    let ast = markdown::to_mdast("# Title", &md_parse_options()).unwrap();
+    tracing::subscriber::with_default(collector, || to_minimad(&ast, &interner).unwrap());
+
+    let position = |end| format!("Some(1:1-1:{end} (0-{}))", end - 1);
+    assert_eq!(
+        *records.lock().unwrap(),
+        [
+            format!(
+                "span node: kind=Root, position={}, model=undefined",
+                position(8)
+            ),
+            "  event: message=enter flow".to_owned(),
+            format!(
+                "  span node: kind=Heading, position={}, model=flow",
+                position(8)
+            ),
+            "    event: message=open phrasing, style=Header(1), from=flow".to_owned(),
+            "    span node: kind=Text, position=Some(1:3-1:8 (2-7)), model=phrasing".to_owned(),
+            "    event: message=close phrasing, to=flow".to_owned(),
+        ]
+    );
+}
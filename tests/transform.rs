@@ -0,0 +1,121 @@
+use mdast2minimad::{
+    md_parse_options, to_minimad_lines, to_minimad_with_options,
+    transform::{
+        Highlight, LimitBlankRuns, Pass, SmartPunctuation, StripEmoji, TextTransform,
+        TrimTrailingBlankLines,
+    },
+    Converter, Interner, Options, Styling,
+};
+use minimad::{Compound, Line, Text};
+
+/// Append a line with the number of lines
+struct CountLines;
+
+impl TextTransform for CountLines {
+    fn apply<'a>(&self, text: &mut Text<'a>, interner: &'a Interner) {
+        let count = interner.intern(format!("{} lines", text.lines.len()));
+        text.lines
+            .push(Line::new_paragraph(vec![Compound::raw_str(count)]));
+    }
+}
+
+#[test]
+fn transforms_apply_in_order() {
+    let interner = Interner::new();
+    let ast = markdown::to_mdast("# Title\n\n\n\nText\n\n---", &md_parse_options()).unwrap();
+    let options = Options::builder()
+        .post(LimitBlankRuns(0))
+        .post(CountLines)
+        .post(TrimTrailingBlankLines)
+        .build();
+    let text = to_minimad_with_options(&ast, options.clone(), &interner).unwrap();
+    assert_eq!(
+        text.lines,
+        [
+            Line::new_header(1, vec![Compound::raw_str("Title")]),
+            Line::new_paragraph(vec![Compound::raw_str("Text")]),
+            Line::HorizontalRule,
+            Line::new_paragraph(vec![Compound::raw_str("3 lines")]),
+        ]
+    );
+
+    let lines: Result<Vec<_>, _> = to_minimad_lines(&ast, options, &interner).collect();
+    assert_eq!(lines.unwrap(), text.lines);
+}
+
+#[test]
+fn passes_skip_code() {
+    let interner = Interner::new();
+    let ast = markdown::to_mdast(
+        "\"Hi\" -- it's *done*... 🎉 `\"a\" -- 🎉`\n\n```\n\"x\" -- 🎉\n```",
+        &md_parse_options(),
+    )
+    .unwrap();
+    let converter = Converter::builder()
+        .post(Pass::new(SmartPunctuation))
+        .post(Pass::new(StripEmoji))
+        .build();
+    let text = converter.convert(&ast, &interner).unwrap();
+    assert_eq!(
+        text.lines[0],
+        Line::new_paragraph(vec![
+            Compound::raw_str("“Hi” – it’s "),
+            Compound::raw_str("done").italic(),
+            Compound::raw_str("…  "),
+            Compound::raw_str("\"a\" -- 🎉").code(),
+        ])
+    );
+    assert!(text
+        .lines
+        .contains(&Line::new_code(Compound::raw_str("\"x\" -- 🎉"))));
+
+    let converter = Converter::builder()
+        .post(Pass::new(StripEmoji).unprotected())
+        .build();
+    let text = converter.convert(&ast, &interner).unwrap();
+    assert!(text
+        .lines
+        .contains(&Line::new_code(Compound::raw_str("\"x\" -- "))));
+}
+
+#[test]
+fn highlights_matches() {
+    let interner = Interner::new();
+    let italic = Styling {
+        italic: Some(true),
+        ..Default::default()
+    };
+    let ast = markdown::to_mdast(
+        "Über **über** and `ÜBER`\n\nnothing here",
+        &md_parse_options(),
+    )
+    .unwrap();
+    let mut text = Converter::default().convert(&ast, &interner).unwrap();
+    assert_eq!(Highlight::new("über", italic).highlight(&mut text), 3);
+    assert_eq!(
+        text.lines[0],
+        Line::new_paragraph(vec![
+            Compound::raw_str("Über").italic(),
+            Compound::raw_str(" "),
+            Compound::raw_str("über").bold().italic(),
+            Compound::raw_str(" and "),
+            Compound::raw_str("ÜBER").code().italic(),
+        ])
+    );
+
+    // a match splits the compounds at both ends
+    let mut text = Text::from("abcabc");
+    let highlight = Highlight::new("cA", italic).case_sensitive();
+    assert_eq!(highlight.highlight(&mut text), 0);
+    let highlight = Highlight::new("ca", italic);
+    assert_eq!(highlight.highlight(&mut text), 1);
+    assert_eq!(
+        text.lines[0],
+        Line::new_paragraph(vec![
+            Compound::raw_str("ab"),
+            Compound::raw_str("ca").italic(),
+            Compound::raw_str("bc"),
+        ])
+    );
+    assert_eq!(Highlight::new("", italic).highlight(&mut text), 0);
+}